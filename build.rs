@@ -0,0 +1,26 @@
+//! Passes the version of the linked cairn crate to the editor as `SUMMIT_CAIRN_VERSION`, read
+//! from Cargo.lock. Without a lock file the variable is left unset and no version is shown.
+
+use std::path::Path;
+
+/// Version of cairn in `lock`, with the git revision when it comes from git, e.g. "0.1.0 (51460af)".
+fn cairn_version(lock: &str) -> Option<String> {
+    let entry = lock.split("[[package]]").find(|p| p.contains("name = \"cairn\""))?;
+    let field = |key: &str| entry.lines()
+        .find_map(|l| l.trim().strip_prefix(key))
+        .map(|v| v.trim().trim_start_matches('=').trim().trim_matches('"').to_string());
+    let version = field("version")?;
+    match field("source").and_then(|s| s.rsplit_once('#').map(|(_, rev)| rev.chars().take(7).collect::<String>())) {
+        Some(rev) => Some(format!("{} ({})", version, rev)),
+        None => Some(version),
+    }
+}
+
+fn main() {
+    let lock_path = Path::new(&std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default()).join("Cargo.lock");
+    println!("cargo:rerun-if-changed={}", lock_path.display());
+    let version = std::fs::read_to_string(&lock_path).ok().and_then(|lock| cairn_version(&lock));
+    if let Some(version) = version {
+        println!("cargo:rustc-env=SUMMIT_CAIRN_VERSION={}", version);
+    }
+}
@@ -1,158 +1,732 @@
 #![allow(dead_code, unused_imports, unused_variables)]
 
+pub mod tabs;
+
 use eframe::egui;
 use serde_json::Value;
 use log::{debug, info, warn, error};
-use std::time::Instant;
+use std::collections::HashMap;
+use std::io;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 use crate::config::keybindings::KeyBindings;
-use crate::ui::render::render_app;
-use crate::ui::input::handle_input;
-use crate::ui::dialogs::{show_open_dialog, show_key_bindings_dialog, show_celeste_path_dialog};
+use crate::config::recent::{load_last_mod, load_recent_maps};
+use crate::config::bookmarks::{load_bookmarks, MapBookmarks};
+use crate::config::last_views::{load_last_views, LastViews};
+use crate::config::room_colors::{load_room_colors, RoomColorOverrides};
+use crate::config::launch::{load_launch_settings, LaunchSettings};
+use crate::config::view_settings::{load_view_settings, ViewSettings};
+use crate::ui::render::{normalize_decal_path, render_app, LayerId, LevelRenderData, TileRenderMode, LAYER_COUNT};
+use crate::ui::entity_render::sized_entity_sprite;
+use crate::ui::input::{handle_input, CameraAnimation};
+use crate::ui::dialogs::{show_open_dialog, show_new_map_dialog, show_key_bindings_dialog, show_celeste_path_dialog, show_stamps_dialog, show_map_report, show_validation_dialog, show_history_dialog, show_entity_properties_dialog, show_reload_confirm_dialog, show_export_tileset_dialog, show_sprite_browser, show_room_properties_dialog, show_map_properties_dialog, show_layer_order_dialog, show_go_to_room_dialog, show_close_tab_confirm_dialog, show_find_dialog, show_new_trigger_dialog, show_review_changes_dialog, show_image_import_dialog, RoomPropertiesForm};
 use crate::ui::loading::show_loading_screen;
+use crate::ui::notifications::Notifications;
 use crate::data::assets::CelesteAssets;
-use crate::data::celeste_atlas::AtlasManager;
+use crate::data::celeste_atlas::{AtlasData, AtlasManager};
+use crate::data::map_model::{self, LevelNode, SolidsNode};
+use crate::data::mods::{ModMap, ModProject};
+use crate::data::stylegrounds::{parse_parallax, Parallax};
+use crate::data::tile_xml::{ensure_tileset_id_path_map_loaded_from_celeste, get_tileset_path_for_id, TILESET_ID_PATH_MAP_BG, TILESET_ID_PATH_MAP_FG};
+use tabs::MapTab;
+use crate::map::changes::{refresh_changes, MapSnapshot, RoomChange};
+use crate::map::clipboard::{TileClipboard, TileSelection};
+use crate::map::collectibles::{find_collectibles, Collectible};
+use crate::map::loader::{NEW_ROOM_HEIGHT, NEW_ROOM_NAME, NEW_ROOM_WIDTH};
+use crate::map::editor::{EditorTool, PaintStroke};
+use crate::map::entities::EntityDragState;
+use crate::map::entity_selection::{GroupDrag, ObjectSelection};
+use crate::map::resize::RoomResizeDrag;
+use crate::map::triggers::{NewTrigger, TriggerDraft, TriggerResizeDrag};
+use crate::map::search::SearchHit;
+use crate::map::history::{EditCommand, EditHistory};
+use crate::map::image_import::ImageImport;
+use crate::map::meta::MapMeta;
+use crate::map::stamps::Stamp;
+use crate::map::validation::count_overflow;
 
 /// Cached representation of a room’s layout with autotile cache.
 #[derive(Clone)]
 pub struct CachedRoom {
     pub level_data: crate::ui::render::LevelRenderData,
     pub json: serde_json::Value,
+    pub stats: RoomStats,
+    /// Solids/bg tiles lying outside the room bounds.
+    pub overflow_tiles: usize,
+    /// 1px-per-tile preview for the room list, built on first display.
+    pub thumbnail: Option<egui::TextureHandle>,
 }
 
-/// Represents a command to draw a sprite (texture) at a given position, scale, and tint.
-#[derive(Clone)]
-pub struct SpriteDrawCommand {
-    pub sprite_path: String,
-    pub pos: egui::Pos2,
-    pub size: egui::Vec2,
-    pub tint: egui::Color32,
+// Atlases loaded from the Celeste directory; Gameplay holds the map art, Gui the overlay icons
+const ATLAS_NAMES: [&str; 2] = ["Gameplay", "Gui"];
+// Drawable counts above which a room is flagged as medium / heavy
+const ROOM_MEDIUM_DRAWABLES: usize = 2000;
+const ROOM_HEAVY_DRAWABLES: usize = 5000;
+// How long the startup loading screen stays up
+const LOADING_SCREEN_DURATION: Duration = Duration::from_secs(2);
+
+/// Startup progress. Nothing is loaded in `new`, so the window and its loading screen come up
+/// before any asset work starts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LoadingState {
+    /// No frame drawn yet; the first `update` starts the atlas load.
+    NotStarted,
+    /// Atlases are decoding in the background while the loading screen shows.
+    Loading { since: Instant },
+    Done,
+}
+
+/// Per-room content counts, computed once in `cache_rooms`.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct RoomStats {
+    pub fg_tiles: usize,
+    /// Total characters in the solids grid, air included.
+    pub fg_cells: usize,
+    pub bg_tiles: usize,
+    pub decals: usize,
+    pub entities: usize,
+    /// Room area in tiles.
+    pub area: usize,
+    /// Most common non-air solids tile, the room's main tileset.
+    pub dominant_fg_tile: Option<char>,
+}
+
+impl RoomStats {
+    pub fn from_level(ld: &crate::ui::render::LevelRenderData, json: &Value) -> Self {
+        let count_tiles = |grid: &Vec<Vec<char>>| {
+            grid.iter().flatten().filter(|&&c| c != '0' && c != ' ').count()
+        };
+        let mut tile_counts: HashMap<char, usize> = HashMap::new();
+        for &c in ld.solids.iter().flatten().filter(|&&c| c != '0' && c != ' ') {
+            *tile_counts.entry(c).or_default() += 1;
+        }
+        // Ties go to the lower id so the choice doesn't change between runs
+        let dominant_fg_tile = tile_counts.into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+            .map(|(c, _)| c);
+        let mut decals = 0;
+        let mut entities = 0;
+        if let Some(children) = json["__children"].as_array() {
+            for child in children {
+                let len = child["__children"].as_array().map_or(0, |a| a.len());
+                match child["__name"].as_str() {
+                    Some("fgdecals") | Some("bgdecals") => decals += len,
+                    Some("entities") => entities += len,
+                    _ => {}
+                }
+            }
+        }
+        Self {
+            fg_tiles: count_tiles(&ld.solids),
+            fg_cells: ld.solids.iter().map(|r| r.len()).sum(),
+            bg_tiles: count_tiles(&ld.bg),
+            decals,
+            entities,
+            area: (ld.width / 8.0) as usize * (ld.height / 8.0) as usize,
+            dominant_fg_tile,
+        }
+    }
+
+    /// Fraction of the solids grid that is non-air, in 0..=1.
+    pub fn fill_ratio(&self) -> f32 {
+        if self.fg_cells == 0 { 0.0 } else { self.fg_tiles as f32 / self.fg_cells as f32 }
+    }
+
+    /// Counts as one line, e.g. "120 fg tiles, 40 bg tiles, 3 decals, 2 entities, 920 tiles of area".
+    pub fn summary(&self) -> String {
+        format!(
+            "{} fg tiles, {} bg tiles, {} decals, {} entities, {} tiles of area",
+            self.fg_tiles, self.bg_tiles, self.decals, self.entities, self.area
+        )
+    }
+
+    /// Number of things drawn for this room, a rough cost estimate.
+    pub fn drawables(&self) -> usize {
+        self.fg_tiles + self.bg_tiles + self.decals + self.entities
+    }
+
+    /// Short label and colour describing how heavy the room is to render.
+    pub fn heaviness(&self) -> (&'static str, egui::Color32) {
+        let n = self.drawables();
+        if n >= ROOM_HEAVY_DRAWABLES {
+            ("heavy", egui::Color32::from_rgb(220, 80, 80))
+        } else if n >= ROOM_MEDIUM_DRAWABLES {
+            ("medium", egui::Color32::from_rgb(220, 180, 60))
+        } else {
+            ("light", egui::Color32::from_rgb(100, 200, 100))
+        }
+    }
+
+    pub fn add(&mut self, other: &RoomStats) {
+        self.fg_tiles += other.fg_tiles;
+        self.fg_cells += other.fg_cells;
+        self.bg_tiles += other.bg_tiles;
+        self.decals += other.decals;
+        self.entities += other.entities;
+        self.area += other.area;
+    }
 }
 
 pub struct CelesteMapEditor {
+    /// Maps open in tabs. The active tab's map is in the fields below; see `MapTab`.
+    pub open_maps: Vec<MapTab>,
+    pub active_tab: usize,
+    /// Id of the tab waiting for confirmation before its unsaved changes are discarded.
+    pub close_tab_confirm: Option<u64>,
     pub map_data: Option<Value>,
     pub current_level_index: usize,
     pub camera_pos: egui::Vec2,
     pub dragging: bool,
     pub drag_start: Option<egui::Pos2>,
+    /// Keys down at the end of the last frame, to tell a fresh press from key repeat.
+    pub keys_held: std::collections::HashSet<egui::Key>,
     pub mouse_pos: egui::Pos2,
+    /// Screen area of the map canvas as of the last frame, for edge autoscroll.
+    pub canvas_rect: Option<egui::Rect>,
     pub bin_path: Option<String>,
-    pub temp_json_path: Option<String>,
+    /// Modification time of `bin_path` when it was last loaded or saved.
+    pub bin_modified: Option<std::time::SystemTime>,
+    /// Linked cairn version, shown in the status bar.
+    pub cairn_version: Option<String>,
     pub show_open_dialog: bool,
-    pub error_message: Option<String>,
+    pub show_new_map_dialog: bool,
+    /// Room settings entered in the New Map dialog.
+    pub new_map_room_name: String,
+    pub new_map_width: u32,
+    pub new_map_height: u32,
+    /// Dismissible error/warning/info cards shown over the canvas.
+    pub notifications: Notifications,
     pub level_names: Vec<String>,
+    /// Index in `level_names` of each room name, rebuilt by `extract_level_names`.
+    pub rooms_by_name: HashMap<String, usize>,
     pub zoom_level: f32,
+    /// Zoom or camera move in progress; see `ViewSettings::animate_view`.
+    pub camera_animation: Option<CameraAnimation>,
+    pub view_settings: ViewSettings,
+    pub launch_settings: LaunchSettings,
+    pub bookmarks: MapBookmarks,
+    /// Where each map was last left; restored when it is opened again.
+    pub last_views: LastViews,
+    pub room_colors: RoomColorOverrides,
     pub show_all_rooms: bool,
+    /// In single-room mode, draw touching rooms dimmed for context.
+    pub show_neighbor_rooms: bool,
+    pub show_room_list: bool,
+    /// Number checkpoints in map order and show the room order panel.
+    pub show_checkpoints: bool,
+    /// Mark berries, cassettes and hearts on the canvas and list them in a side panel.
+    pub show_collectibles: bool,
+    /// Room being dragged in the room order panel.
+    pub room_order_drag: Option<usize>,
+    /// Move rooms copied to another map clear of its existing rooms.
+    pub copy_room_to_free_space: bool,
+    /// Tint each room by how full its solids grid is.
+    pub show_heat_map: bool,
+    /// Debug builds: draw the raw solids characters over the tiles at high zoom.
+    pub show_solids_debug: bool,
+    /// Debug builds: show how much memory the loaded atlases hold.
+    pub show_memory_usage: bool,
+    /// Outline the game camera at the current room's spawns.
+    pub show_camera_frames: bool,
+    /// With camera frames on, also cut the room into screen-sized cells.
+    pub tile_camera_frames: bool,
+    /// Draw foreground tiles as plain white solids over transparent air, ignoring textures.
+    pub collision_debug_mode: bool,
     pub show_grid: bool,
+    /// Second, coarser grid every 20 tiles.
+    pub show_screen_grid: bool,
     pub show_labels: bool,
     pub key_bindings: KeyBindings,
+    /// Recently opened .bin paths, newest first.
+    pub recent_maps: Vec<String>,
+    /// Path typed or browsed to in the Open dialog. The open map's `bin_path` only changes once it loads.
+    pub open_path: String,
+    /// Map picked from the Open dialog's mod list, with its mod's name.
+    pub open_mod_map: Option<(String, ModMap)>,
+    /// Mods with maps in the Celeste Mods folder, scanned when the Open dialog first shows them.
+    pub mod_projects: Option<Vec<ModProject>>,
+    /// Mod a map was last opened from; its branch starts expanded in the Open dialog.
+    pub last_mod: Option<String>,
     pub show_key_bindings_dialog: bool,
     pub celeste_assets: CelesteAssets,
     pub show_celeste_path_dialog: bool,
+    /// Why the last folder picked in the Celeste path dialog was refused.
+    pub celeste_path_error: Option<String>,
+    /// Folder last picked with Browse in the Celeste path dialog, shown with whether it checks out.
+    pub celeste_path_picked: Option<std::path::PathBuf>,
     pub use_textures: bool,
     /// Cache for each room’s pre-parsed solids data.
     pub cached_rooms: Vec<CachedRoom>,
+    /// Collectibles of the cached rooms in list order, found once per `cache_rooms`.
+    pub collectibles: Vec<Collectible>,
+    /// ForegroundTiles.xml / BackgroundTiles.xml in the Celeste install; empty without one.
+    /// Shared by every room, so computed once by `update_xml_paths`.
+    pub fg_xml_path: String,
+    pub bg_xml_path: String,
     // Add AtlasManager for texture atlases
     pub atlas_manager: Option<AtlasManager>,
-    pub render_fgtiles_mode: bool, // If true, render fgdecals as tiles instead of solid blocks
+    /// Files missing from the Celeste install, found by the last asset check; listed in the
+    /// Celeste path dialog.
+    pub asset_problems: Vec<String>,
+    /// Background atlas decodes by atlas name, uploaded by `poll_atlas_load` as each finishes.
+    pub atlas_loads: Vec<(String, JoinHandle<io::Result<AtlasData>>)>,
     pub show_fgdecals: bool, // If true, render fgdecals on all rooms
-    pub static_shapes: Option<Vec<egui::Shape>>,
-    pub static_sprites: Option<Vec<SpriteDrawCommand>>,
-    pub static_dirty: bool,
     pub show_solid_tiles: bool,
-    pub show_tiles: bool,
-    pub is_loading: bool,
-    pub loading_start_time: Option<Instant>,
+    pub tile_render_mode: TileRenderMode,
+    /// Per-layer visibility, indexed by `LayerId::index`.
+    pub layer_visibility: [bool; LAYER_COUNT],
+    /// Parallax backgrounds from the map's Style element, parsed when rooms are cached.
+    pub parallax_layers: Vec<Parallax>,
+    pub loading_state: LoadingState,
+    /// Title last sent to the window, to avoid resending it every frame.
+    pub window_title: String,
+    pub selection: Option<TileSelection>,
+    pub history: EditHistory,
+    /// Unsaved changes since the map was loaded or saved.
+    pub is_dirty: bool,
+    /// The map as of its last load or save, for Review Changes.
+    pub saved_snapshot: Option<MapSnapshot>,
+    /// Rooms differing from `saved_snapshot`; refreshed while Review Changes or its overlay is shown.
+    pub map_changes: Vec<RoomChange>,
+    pub show_review_changes: bool,
+    /// Highlight the solids changed since the last load or save on the canvas.
+    pub show_change_overlay: bool,
+    pub show_reload_confirm: bool,
+    pub show_history: bool,
+    /// History window lists only the current room's edits.
+    pub history_room_only: bool,
+    pub show_entity_properties: bool,
+    /// Entity or trigger id whose section the Entity Properties window opens and scrolls to.
+    pub inspect_entity: Option<i64>,
+    pub active_tool: EditorTool,
+    /// Snap step in Celeste pixels for entity placement; None is off.
+    pub entity_snap: Option<u32>,
+    /// Grid in Celeste pixels that moved decals land on; 0 is off.
+    pub decal_snap_grid: u8,
+    /// Entity being dragged with the Entities tool.
+    pub entity_drag: Option<EntityDragState>,
+    /// Room border handle being dragged.
+    pub room_resize: Option<RoomResizeDrag>,
+    /// Rectangle being drawn with the Triggers tool.
+    pub trigger_draft: Option<TriggerDraft>,
+    /// Drawn trigger waiting for its name in the New Trigger window.
+    pub new_trigger: Option<NewTrigger>,
+    /// Trigger edge being dragged with the Triggers tool.
+    pub trigger_resize: Option<TriggerResizeDrag>,
+    /// Image waiting in the Import Room from Image window.
+    pub image_import: Option<ImageImport>,
+    /// Entities, triggers and decals picked with the Entities tool.
+    pub object_selection: Option<ObjectSelection>,
+    pub group_drag: Option<GroupDrag>,
+    /// Rubber band being dragged with the Entities tool: press and current screen positions.
+    pub selection_band: Option<(egui::Pos2, egui::Pos2)>,
+    /// Tile placed by the brush, fill and line tools.
+    pub brush_tile: char,
+    /// Anchor of a line being drawn: (room index, room-local tile).
+    pub line_start: Option<(usize, (i32, i32))>,
+    /// Brush or eraser drag in progress.
+    pub paint_stroke: Option<PaintStroke>,
+    /// Last tile painted and its room, where a Ctrl+click line starts.
+    pub last_painted: Option<(usize, (i32, i32))>,
+    pub clipboard: Option<TileClipboard>,
+    pub show_stamps_dialog: bool,
+    pub show_export_tileset_dialog: bool,
+    pub show_sprite_browser: bool,
+    /// Sprite browser state: atlas shown, key filter and the sprite being previewed.
+    pub sprite_browser_atlas: String,
+    pub sprite_browser_filter: String,
+    pub sprite_browser_selected: Option<String>,
+    pub show_room_properties: bool,
+    pub show_map_properties: bool,
+    pub show_layer_order: bool,
+    pub show_go_to_room: bool,
+    pub show_find: bool,
+    pub find_query: String,
+    /// Matches for `find_query`, refreshed when it changes.
+    pub find_results: Vec<SearchHit>,
+    /// Give the Find field keyboard focus on the next frame.
+    pub find_focus: bool,
+    /// Find result being flashed: room, room-local position and when it was picked.
+    pub find_flash: Option<(usize, f64, f64, Instant)>,
+    /// Text typed into the Go To Room # window.
+    pub go_to_room_input: String,
+    /// Layer being dragged in the layer order window.
+    pub layer_order_drag: Option<usize>,
+    /// Soloed layer and the visibility toggles to restore when the solo ends.
+    pub solo_layer: Option<(LayerId, [bool; LAYER_COUNT])>,
+    /// The open map's Everest `.meta.yaml`, if it has one.
+    pub map_meta: Option<MapMeta>,
+    pub room_properties: Option<RoomPropertiesForm>,
+    /// Tileset picked in the export dialog: (background, id).
+    pub export_tileset: Option<(bool, char)>,
+    pub show_map_report: bool,
+    pub show_validation: bool,
+    /// Also list bg tiles hidden under fg solids in the validation window.
+    pub lint_hidden_bg: bool,
+    /// Result of the last trim action, shown in the validation window.
+    pub validation_report: Option<String>,
+    pub stamps: Vec<Stamp>,
+    pub new_stamp_name: String,
 }
 
 impl Default for CelesteMapEditor {
     fn default() -> Self {
         Self {
+            open_maps: vec![MapTab::new()],
+            active_tab: 0,
+            close_tab_confirm: None,
             map_data: None,
             current_level_index: 0,
             camera_pos: egui::Vec2::new(0.0, 0.0),
             dragging: false,
             drag_start: None,
+            keys_held: std::collections::HashSet::new(),
             mouse_pos: egui::Pos2::new(0.0, 0.0),
+            canvas_rect: None,
             bin_path: None,
-            temp_json_path: None,
+            bin_modified: None,
+            cairn_version: crate::map::loader::cairn_version(),
             show_open_dialog: false,
-            error_message: None,
+            show_new_map_dialog: false,
+            new_map_room_name: NEW_ROOM_NAME.to_string(),
+            new_map_width: NEW_ROOM_WIDTH,
+            new_map_height: NEW_ROOM_HEIGHT,
+            notifications: Notifications::default(),
             level_names: Vec::new(),
+            rooms_by_name: HashMap::new(),
             zoom_level: 1.0,
+            camera_animation: None,
+            view_settings: ViewSettings::default(),
+            launch_settings: LaunchSettings::default(),
+            bookmarks: MapBookmarks::default(),
+            last_views: LastViews::default(),
+            room_colors: RoomColorOverrides::default(),
             show_all_rooms: true,
+            show_neighbor_rooms: false,
+            show_room_list: true,
+            show_checkpoints: false,
+            show_collectibles: false,
+            room_order_drag: None,
+            copy_room_to_free_space: true,
+            show_heat_map: false,
+            show_solids_debug: false,
+            show_memory_usage: false,
+            show_camera_frames: false,
+            tile_camera_frames: false,
+            collision_debug_mode: false,
             show_grid: true,
+            show_screen_grid: false,
             show_labels: true,
             key_bindings: KeyBindings::default(),
+            recent_maps: Vec::new(),
+            open_path: String::new(),
+            open_mod_map: None,
+            mod_projects: None,
+            last_mod: None,
             show_key_bindings_dialog: false,
             celeste_assets: CelesteAssets::new(),
             show_celeste_path_dialog: false,
+            celeste_path_error: None,
+            celeste_path_picked: None,
             use_textures: true,
             cached_rooms: Vec::new(),
+            collectibles: Vec::new(),
+            fg_xml_path: String::new(),
+            bg_xml_path: String::new(),
             atlas_manager: None, // Start with no atlas loaded
-            render_fgtiles_mode: false,
+            atlas_loads: Vec::new(),
+            asset_problems: Vec::new(),
             show_fgdecals: true,
-            static_shapes: None,
-            static_sprites: None,
-            static_dirty: true,
             show_solid_tiles: true,
-            show_tiles: true,
-            is_loading: true,
-            loading_start_time: None,
+            tile_render_mode: TileRenderMode::Full,
+            layer_visibility: [true; LAYER_COUNT],
+            parallax_layers: Vec::new(),
+            loading_state: LoadingState::NotStarted,
+            window_title: String::new(),
+            selection: None,
+            history: EditHistory::default(),
+            is_dirty: false,
+            saved_snapshot: None,
+            map_changes: Vec::new(),
+            show_review_changes: false,
+            show_change_overlay: false,
+            show_reload_confirm: false,
+            show_history: false,
+            history_room_only: false,
+            show_entity_properties: false,
+            inspect_entity: None,
+            active_tool: EditorTool::default(),
+            entity_snap: Some(8),
+            decal_snap_grid: 8,
+            entity_drag: None,
+            room_resize: None,
+            trigger_draft: None,
+            new_trigger: None,
+            image_import: None,
+            trigger_resize: None,
+            object_selection: None,
+            group_drag: None,
+            selection_band: None,
+            brush_tile: '9',
+            line_start: None,
+            paint_stroke: None,
+            last_painted: None,
+            clipboard: None,
+            show_stamps_dialog: false,
+            show_export_tileset_dialog: false,
+            show_sprite_browser: false,
+            sprite_browser_atlas: "Gameplay".to_string(),
+            sprite_browser_filter: String::new(),
+            sprite_browser_selected: None,
+            show_room_properties: false,
+            show_map_properties: false,
+            show_layer_order: false,
+            show_go_to_room: false,
+            show_find: false,
+            find_query: String::new(),
+            find_results: Vec::new(),
+            find_focus: false,
+            find_flash: None,
+            go_to_room_input: String::new(),
+            layer_order_drag: None,
+            solo_layer: None,
+            map_meta: None,
+            room_properties: None,
+            export_tileset: None,
+            show_map_report: false,
+            show_validation: false,
+            lint_hidden_bg: false,
+            validation_report: None,
+            stamps: Vec::new(),
+            new_stamp_name: String::new(),
         }
     }
 }
 
 impl CelesteMapEditor {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
         let mut editor = Self::default();
         editor.key_bindings.load();
-        // Check if Celeste assets are available, show dialog if not.
-        if let Some(ref celeste_dir) = editor.celeste_assets.celeste_dir {
-            // Initialize atlas manager if Celeste directory is found.
-            let mut atlas_manager = AtlasManager::new();
-            // Try to load the main atlas (e.g., Gameplay)
-            let ctx = &cc.egui_ctx;
-            let result = atlas_manager.load_atlas("Gameplay", celeste_dir, ctx);
+        editor.recent_maps = load_recent_maps();
+        editor.last_mod = load_last_mod();
+        editor.bookmarks = load_bookmarks();
+        editor.last_views = load_last_views();
+        editor.room_colors = load_room_colors();
+        editor.view_settings = load_view_settings();
+        editor.launch_settings = load_launch_settings();
+        editor
+    }
+
+    /// Start loading the Celeste assets, or ask where Celeste is when it isn't known yet.
+    /// Runs on the first frame, once the loading screen can be shown.
+    fn begin_loading(&mut self) {
+        if self.celeste_assets.celeste_dir.is_some() {
+            // Atlases are decoded off the main thread; the textures are created once they finish
+            self.recheck_assets();
+        } else {
+            self.update_xml_paths();
+            self.show_celeste_path_dialog = true;
+        }
+        self.loading_state = LoadingState::Loading { since: Instant::now() };
+    }
+
+    /// Recompute the tile XML paths; call when the Celeste directory changes.
+    pub fn update_xml_paths(&mut self) {
+        let path = |file: &str| self.celeste_assets.celeste_dir.as_deref()
+            .map(|dir| crate::data::tile_xml::tiles_xml_path(dir, file))
+            .unwrap_or_default();
+        self.fg_xml_path = path("ForegroundTiles.xml");
+        self.bg_xml_path = path("BackgroundTiles.xml");
+    }
+
+    /// Look over the Celeste install again: the tile XMLs now, the atlases as they load.
+    /// Call when the Celeste directory changes or its files were fixed.
+    pub fn recheck_assets(&mut self) {
+        self.asset_problems.clear();
+        self.update_xml_paths();
+        for path in [&self.fg_xml_path, &self.bg_xml_path] {
+            if !path.is_empty() && !std::path::Path::new(path).exists() {
+                self.asset_problems.push(format!("Missing {}", path));
+            }
+        }
+        if !self.asset_problems.is_empty() {
+            self.notifications.warn(format!("{}; tiles are drawn as plain colours.", self.asset_problems.join(", ")));
+        }
+        self.start_atlas_load();
+    }
+
+    /// Start decoding the atlases from the Celeste directory on background threads.
+    pub fn start_atlas_load(&mut self) {
+        let Some(celeste_dir) = &self.celeste_assets.celeste_dir else { return };
+        self.atlas_manager = None;
+        self.atlas_loads = ATLAS_NAMES.iter()
+            .map(|name| (name.to_string(), AtlasManager::spawn_load(name, celeste_dir)))
+            .collect();
+    }
+
+    /// Upload each atlas once its thread has finished; egui textures are created on the main thread.
+    fn poll_atlas_load(&mut self, ctx: &egui::Context) {
+        let (finished, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.atlas_loads)
+            .into_iter()
+            .partition(|(_, handle)| handle.is_finished());
+        self.atlas_loads = pending;
+        for (name, handle) in finished {
+            let result = handle.join()
+                .unwrap_or_else(|_| Err(io::Error::new(io::ErrorKind::Other, "the atlas loading thread panicked")));
             match result {
-                Ok(_) => {
-                    info!("Successfully initialized atlas manager");
-                    editor.atlas_manager = Some(atlas_manager);
+                Ok(data) => {
+                    if !data.missing_pages.is_empty() {
+                        let files: Vec<String> = data.missing_pages.iter().map(|p| p.display().to_string()).collect();
+                        self.notifications.warn(format!(
+                            "The {} atlas is incomplete; sprites on these pages are drawn as plain shapes: {}. \
+                             Fix the install, then use File > Set Celeste Path... > Re-check Assets.",
+                            name, files.join(", ")));
+                        self.asset_problems.extend(files.into_iter().map(|f| format!("Missing {}", f)));
+                    }
+                    self.atlas_manager.get_or_insert_with(AtlasManager::new).add_atlas(data, ctx);
+                    info!("Loaded the {} atlas", name);
+                    if name == "Gameplay" {
+                        self.note_missing_sprites();
+                    }
+                }
+                Err(e) if name == "Gameplay" => {
+                    self.notifications.warn(format!("Failed to load the Gameplay atlas, falling back to PNG loading: {}", e));
+                    self.asset_problems.push(format!("Gameplay atlas: {}", e));
                 }
+                // Only overlay icons come from the other atlases; their markers stand in
                 Err(e) => {
-                    warn!("Failed to initialize atlas manager, falling back to PNG loading: {}", e);
-                    editor.atlas_manager = None;
+                    warn!("Failed to load the {} atlas, overlays use plain markers: {}", name, e);
+                    self.asset_problems.push(format!("{} atlas: {}", name, e));
                 }
             }
-        } else {
-            editor.show_celeste_path_dialog = true;
         }
-        editor
     }
 
     /// Cache the LevelRenderData for each room. Call after map load or edit.
     pub fn cache_rooms(&mut self) {
-        self.cached_rooms.clear();
+        // Thumbnails of rooms whose tiles are unchanged are kept, so a brush tile rebuilds one texture
+        let mut old_rooms = std::mem::take(&mut self.cached_rooms);
+        self.parallax_layers = self.map_data.as_ref().map(parse_parallax).unwrap_or_default();
         if let Some(map) = &self.map_data {
             if let Some(children) = map["__children"].as_array() {
                 for child in children {
                     if child["__name"] == "levels" {
                         if let Some(levels) = child["__children"].as_array() {
-                            for level in levels {
-                                if level["__name"] == "level" {
-                                    if let Some(ld) = crate::ui::render::extract_level_data(level, self) {
-                                        self.cached_rooms.push(CachedRoom {
-                                            level_data: ld,
-                                            json: level.clone(),
-                                        });
-                                    }
+                            for (idx, level) in levels.iter().enumerate() {
+                                let ld = Some(level).filter(|l| l["__name"] == "level")
+                                    .and_then(|l| crate::ui::render::extract_level_data(l, self))
+                                    .unwrap_or_else(|| {
+                                        // An empty stand-in keeps cached room i on level i of the map
+                                        warn!("Level {} has no readable position, it is not drawn", idx);
+                                        LevelRenderData { name: level["name"].as_str().unwrap_or_default().to_string(), ..Default::default() }
+                                    });
+                                let stats = RoomStats::from_level(&ld, level);
+                                let (w, h) = ((ld.width / 8.0) as usize, (ld.height / 8.0) as usize);
+                                let overflow_tiles = count_overflow(&ld.solids, w, h) + count_overflow(&ld.bg, w, h);
+                                if overflow_tiles > 0 {
+                                    warn!("Room '{}' has {} tiles outside its bounds", ld.name, overflow_tiles);
                                 }
+                                let thumbnail = old_rooms.get_mut(self.cached_rooms.len())
+                                    .filter(|old| old.level_data.same_tiles(&ld))
+                                    .and_then(|old| old.thumbnail.take());
+                                self.cached_rooms.push(CachedRoom {
+                                    level_data: ld,
+                                    json: level.clone(),
+                                    stats,
+                                    overflow_tiles,
+                                    thumbnail,
+                                });
                             }
                         }
                     }
                 }
             }
         }
+        self.collectibles = find_collectibles(&self.cached_rooms);
+        if self.show_review_changes || self.show_change_overlay {
+            refresh_changes(self);
+        }
+        self.log_unmapped_tiles();
+        self.note_missing_sprites();
+    }
+
+    /// Log the tile ids used by the map that the tiles XMLs give no tileset, which draw as flat colour.
+    /// Done once per caching rather than per drawn tile.
+    fn log_unmapped_tiles(&self) {
+        ensure_tileset_id_path_map_loaded_from_celeste(self);
+        let layers: [(&str, _, fn(&CachedRoom) -> &Vec<Vec<char>>); 2] = [
+            ("FG", &TILESET_ID_PATH_MAP_FG, |r| &r.level_data.solids),
+            ("BG", &TILESET_ID_PATH_MAP_BG, |r| &r.level_data.bg),
+        ];
+        for (tag, cell, tiles) in layers {
+            let Some(map) = cell.get() else {
+                debug!("[{} TILE DEBUG] tileset id map not loaded", tag);
+                continue;
+            };
+            let mut unmapped: Vec<char> = self.cached_rooms.iter()
+                .flat_map(|r| tiles(r).iter().flatten().copied())
+                .filter(|&c| c != '0' && c != ' ' && get_tileset_path_for_id(map, c).is_none())
+                .collect();
+            unmapped.sort_unstable();
+            unmapped.dedup();
+            if !unmapped.is_empty() {
+                debug!("[{} TILE DEBUG] no tileset path for {:?}", tag, unmapped);
+            }
+        }
+    }
+
+    /// Tell the atlas manager which Gameplay sprites the map draws but the atlas lacks, for the
+    /// sprite browser. Done once per caching rather than per drawn sprite.
+    fn note_missing_sprites(&mut self) {
+        let Some(atlas_manager) = self.atlas_manager.as_mut() else { return };
+        let mut uses: HashMap<String, u32> = HashMap::new();
+        let layers: [(_, fn(&CachedRoom) -> &Vec<Vec<char>>); 2] = [
+            (&TILESET_ID_PATH_MAP_FG, |r| &r.level_data.solids),
+            (&TILESET_ID_PATH_MAP_BG, |r| &r.level_data.bg),
+        ];
+        for (cell, tiles) in layers {
+            let Some(map) = cell.get() else { continue };
+            // Counted per tile id first, so a map's many tiles don't each build a path
+            let mut counts: HashMap<char, u32> = HashMap::new();
+            for &c in self.cached_rooms.iter().flat_map(|r| tiles(r).iter().flatten()) {
+                *counts.entry(c).or_insert(0) += 1;
+            }
+            for (c, count) in counts {
+                if let Some(path) = get_tileset_path_for_id(map, c) {
+                    *uses.entry(format!("tilesets/{}", path)).or_insert(0) += count;
+                }
+            }
+        }
+        for layer in self.cached_rooms.iter().flat_map(|r| r.json["__children"].as_array().into_iter().flatten()) {
+            let decals = matches!(layer["__name"].as_str(), Some("fgdecals" | "bgdecals"));
+            let entities = layer["__name"] == "entities";
+            for obj in layer["__children"].as_array().into_iter().flatten() {
+                let path = if decals {
+                    Some(normalize_decal_path(obj["texture"].as_str().unwrap_or("")))
+                } else if entities {
+                    sized_entity_sprite(obj)
+                } else {
+                    None
+                };
+                if let Some(path) = path {
+                    *uses.entry(path).or_insert(0) += 1;
+                }
+            }
+        }
+        for layer in &self.parallax_layers {
+            *uses.entry(layer.texture.clone()).or_insert(0) += 1;
+        }
+        atlas_manager.note_missing_sprites("Gameplay", uses);
+    }
+
+    /// Sum of the cached per-room stats.
+    pub fn map_stats(&self) -> RoomStats {
+        let mut total = RoomStats::default();
+        for room in &self.cached_rooms {
+            total.add(&room.stats);
+        }
+        total
     }
 
     pub fn debug_map_structure(&self) {
@@ -226,143 +800,139 @@ impl CelesteMapEditor {
 
     pub fn extract_level_names(&mut self) {
         self.level_names.clear();
-        if let Some(map) = &self.map_data {
-            info!("Map structure: {}", map["__name"].as_str().unwrap_or("unknown"));
-
-            let mut found_levels = false;
-            if let Some(children) = map["__children"].as_array() {
-                info!("Map has {} top-level children", children.len());
-                for child in children {
-                    if let Some(name) = child["__name"].as_str() {
-                        info!("Child: {}", name);
-                        if name == "levels" {
-                            found_levels = true;
-                            if let Some(levels) = child["__children"].as_array() {
-                                info!("Found 'levels' with {} sub-elements", levels.len());
-                                for level in levels {
-                                    if level["__name"] == "level" {
-                                        if let Some(level_name) = level["name"].as_str() {
-                                            info!("Adding level: {}", level_name);
-                                            self.level_names.push(level_name.to_string());
-                                        } else {
-                                            warn!("Level has no name attribute!");
-                                        }
-                                    } else {
-                                        warn!("Non-level element in levels: {}", level["__name"].as_str().unwrap_or("unknown"));
-                                    }
-                                }
-                            } else {
-                                warn!("'levels' element has no children array!");
-                            }
-                        }
+        self.rooms_by_name.clear();
+        let Some(map) = &self.map_data else {
+            warn!("No map data available!");
+            return;
+        };
+        info!("Map structure: {}", map["__name"].as_str().unwrap_or("unknown"));
+        match map_model::parse_map(map) {
+            Some(model) => {
+                for level in &model.levels {
+                    if level.kind != "level" {
+                        warn!("Non-level element in levels: {}", level.kind);
+                    } else if level.name.is_empty() {
+                        warn!("Level has no name attribute!");
                     }
                 }
-
-                if !found_levels {
-                    warn!("WARNING: No 'levels' element found in map!");
-                }
-            } else {
-                warn!("Map has no children array!");
+                self.level_names = model.level_names();
             }
-        } else {
-            warn!("No map data available!");
+            None => warn!("WARNING: No readable 'levels' element found in map!"),
+        }
+        // Duplicate names keep the first room, as the game does when looking a room up
+        for (i, name) in self.level_names.iter().enumerate() {
+            self.rooms_by_name.entry(name.clone()).or_insert(i);
         }
-
         info!("Extracted {} level names", self.level_names.len());
     }
 
-    pub fn get_current_level(&self) -> Option<&Value> {
-        if let Some(map) = &self.map_data {
-            if let Some(children) = map["__children"].as_array() {
-                for child in children {
-                    if child["__name"] == "levels" {
-                        if let Some(levels) = child["__children"].as_array() {
-                            if self.current_level_index < levels.len() {
-                                return Some(&levels[self.current_level_index]);
-                            }
-                        }
-                        break;
-                    }
-                }
-            }
-        }
-        None
+    /// The current room, typed. Only this level is parsed.
+    pub fn get_current_level(&self) -> Option<LevelNode> {
+        LevelNode::from_value(self.get_current_level_json()?)
+    }
+
+    /// The current room's element of the Cairn JSON, for edits and lookups the model doesn't type.
+    pub fn get_current_level_json(&self) -> Option<&Value> {
+        map_model::levels(self.map_data.as_ref()?)?.get(self.current_level_index)
+    }
+
+    pub fn get_current_level_mut(&mut self) -> Option<&mut Value> {
+        self.get_level_mut(self.current_level_index)
+    }
+
+    pub fn get_level_mut(&mut self, index: usize) -> Option<&mut Value> {
+        map_model::levels_mut(self.map_data.as_mut()?)?.get_mut(index)
     }
 
     pub fn get_solids_data(&self) -> Option<String> {
-        if let Some(level) = self.get_current_level() {
-            for child in level["__children"].as_array()? {
-                if child["__name"] == "solids" {
-                    return child["innerText"].as_str().map(|s| s.to_string());
-                }
-            }
-        }
-        None
+        SolidsNode::of_level(self.get_current_level_json()?, "solids").map(|s| s.inner_text)
+    }
+
+    /// Replace the current room's solids without recording undo. Returns whether anything changed.
+    /// Only the solids element is read and written, not the whole level.
+    pub fn set_solids_data(&mut self, new_solids: &str) -> bool {
+        let Some(level) = self.get_current_level_mut() else { return false };
+        let Some(mut solids) = SolidsNode::of_level(level, "solids") else {
+            warn!("Current level has no solids element, solids not updated");
+            return false;
+        };
+        if solids.inner_text == new_solids { return false; }
+        solids.inner_text = new_solids.to_string();
+        solids.store_in(level);
+        self.cache_rooms();
+        self.is_dirty = true;
+        true
     }
 
     pub fn update_solids_data(&mut self, new_solids: &str) {
-        if let Some(map) = &mut self.map_data {
-            if let Some(children) = map["__children"].as_array_mut() {
-                for child in children {
-                    if child["__name"] == "levels" {
-                        if let Some(levels) = child["__children"].as_array_mut() {
-                            if let Some(level) = levels.get_mut(self.current_level_index) {
-                                if let Some(level_children) = level["__children"].as_array_mut() {
-                                    for lc in level_children {
-                                        if lc["__name"] == "solids" {
-                                            lc["innerText"] = serde_json::json!(new_solids);
-                                            self.cache_rooms();
-                                            self.static_dirty = true;
-                                            return;
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        return;
-                    }
-                }
-            }
-        }
+        let room_idx = self.current_level_index;
+        let Some(before) = self.get_current_level_json().cloned() else { return };
+        if !self.set_solids_data(new_solids) { return; }
+        let Some(after) = self.get_current_level_json().cloned() else { return };
+        self.history.push(EditCommand::LevelEdit {
+            room_idx,
+            description: "Edit tiles".to_string(),
+            before,
+            after,
+        });
     }
 
     pub fn screen_to_map(&self, pos: egui::Pos2) -> (i32, i32) {
-        let scaled_tile_size = crate::ui::render::TILE_SIZE * self.zoom_level;
-        let x = ((pos.x + self.camera_pos.x) / scaled_tile_size).floor() as i32;
-        let y = ((pos.y + self.camera_pos.y) / scaled_tile_size).floor() as i32;
-        (x, y)
+        crate::ui::transform::ViewTransform::of(self).screen_to_world_tile(pos)
+    }
+
+    /// Set the zoom, clamped to the shared zoom range, keeping the map point under the screen point
+    /// `anchor` where it is. Every zoom path goes through here.
+    pub fn set_zoom(&mut self, new_zoom: f32, anchor: egui::Pos2) {
+        let view = crate::ui::transform::ViewTransform::of(self).zoomed(new_zoom, anchor);
+        self.camera_pos = view.camera_pos;
+        self.zoom_level = view.zoom;
     }
 }
 
 impl eframe::App for CelesteMapEditor {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        if self.is_loading {
-            // Start timer on first update
-            if self.loading_start_time.is_none() {
-                self.loading_start_time = Some(Instant::now());
-            }
-            if let Some(start) = self.loading_start_time {
-                let elapsed = start.elapsed().as_secs_f32();
-                if elapsed < 2.0 {
-                    egui::Area::new("loading_blocker").interactable(false).show(ctx, |ui| {
-                        show_loading_screen(ctx);
-                    });
-                    ctx.request_repaint();
-                    return;
-                } else {
-                    self.is_loading = false;
-                    self.loading_start_time = None;
-                }
+        let title = match &self.celeste_assets.game_version {
+            Some(version) => format!("Summit - Celeste Map Editor (Celeste {})", version),
+            None => "Summit - Celeste Map Editor".to_string(),
+        };
+        if title != self.window_title {
+            _frame.set_window_title(&title);
+            self.window_title = title;
+        }
+        self.poll_atlas_load(ctx);
+        if !self.atlas_loads.is_empty() {
+            // Keep polling until the atlas arrives, even without input
+            ctx.request_repaint();
+        }
+        match self.loading_state {
+            LoadingState::NotStarted => self.begin_loading(),
+            LoadingState::Loading { since } if since.elapsed() >= LOADING_SCREEN_DURATION => {
+                self.loading_state = LoadingState::Done;
             }
+            _ => {}
+        }
+        if self.loading_state != LoadingState::Done {
+            egui::Area::new("loading_blocker").interactable(false).show(ctx, |ui| {
+                show_loading_screen(ctx);
+            });
+            ctx.request_repaint();
+            return;
         }
         // Handle user input.
         handle_input(self, ctx);
+        if self.camera_animation.is_some() {
+            ctx.request_repaint();
+        }
         // Render the application.
         render_app(self, ctx);
         // Show dialogs.
         if self.show_open_dialog {
             show_open_dialog(self, ctx);
         }
+        if self.show_new_map_dialog {
+            show_new_map_dialog(self, ctx);
+        }
         if self.show_key_bindings_dialog {
             show_key_bindings_dialog(self, ctx);
         }
@@ -370,5 +940,62 @@ impl eframe::App for CelesteMapEditor {
         if self.show_celeste_path_dialog {
             show_celeste_path_dialog(self, ctx);
         }
+        if self.show_stamps_dialog {
+            show_stamps_dialog(self, ctx);
+        }
+        if self.show_export_tileset_dialog {
+            show_export_tileset_dialog(self, ctx);
+        }
+        if self.show_sprite_browser {
+            show_sprite_browser(self, ctx);
+        }
+        if self.show_map_report {
+            show_map_report(self, ctx);
+        }
+        if self.show_validation {
+            show_validation_dialog(self, ctx);
+        }
+        if self.show_reload_confirm {
+            show_reload_confirm_dialog(self, ctx);
+        }
+        if self.show_history {
+            show_history_dialog(self, ctx);
+        }
+        if self.show_review_changes {
+            show_review_changes_dialog(self, ctx);
+        }
+        if self.show_map_properties {
+            show_map_properties_dialog(self, ctx);
+        }
+        if self.show_room_properties {
+            show_room_properties_dialog(self, ctx);
+        }
+        if self.show_layer_order {
+            show_layer_order_dialog(self, ctx);
+        }
+        if self.show_go_to_room {
+            show_go_to_room_dialog(self, ctx);
+        }
+        if self.show_find {
+            show_find_dialog(self, ctx);
+        }
+        if self.close_tab_confirm.is_some() {
+            show_close_tab_confirm_dialog(self, ctx);
+        }
+        if self.show_entity_properties {
+            show_entity_properties_dialog(self, ctx);
+        }
+        if self.new_trigger.is_some() {
+            show_new_trigger_dialog(self, ctx);
+        }
+        if self.image_import.is_some() {
+            show_image_import_dialog(self, ctx);
+        }
+        self.notifications.show(ctx);
+    }
+
+    fn on_close_event(&mut self) -> bool {
+        tabs::remember_open_views(self);
+        true
     }
 }
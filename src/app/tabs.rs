@@ -0,0 +1,256 @@
+use eframe::egui;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::mem::swap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::app::{CachedRoom, CelesteMapEditor};
+use crate::config::bookmarks::ViewBookmark;
+use crate::config::last_views::save_last_views;
+use crate::data::map_model;
+use crate::data::stylegrounds::Parallax;
+use crate::map::changes::{refresh_changes, MapSnapshot};
+use crate::map::clipboard::TileSelection;
+use crate::map::collectibles::Collectible;
+use crate::map::entity_selection::ObjectSelection;
+use crate::map::history::{EditCommand, EditHistory};
+use crate::map::meta::MapMeta;
+use crate::map::rooms::copy_room_into;
+use crate::ui::input::current_view;
+
+// Source of tab ids; starts at 1 so the placeholder's 0 never names a tab
+static NEXT_TAB_ID: AtomicU64 = AtomicU64::new(1);
+
+/// The per-map state of an open tab. The active tab's state lives in the editor's own fields so the
+/// rest of the editor works on it unchanged; its slot here holds only a placeholder.
+#[derive(Default)]
+pub struct MapTab {
+    /// Stays with the tab's slot while tabs around it close, unlike its index.
+    pub id: u64,
+    /// The map was edited while the tab was in the background; its rooms are cached again when
+    /// it is switched to. Stays with the slot, like `id`.
+    pub needs_recache: bool,
+    pub map_data: Option<Value>,
+    pub bin_path: Option<String>,
+    pub bin_modified: Option<std::time::SystemTime>,
+    pub map_meta: Option<MapMeta>,
+    pub level_names: Vec<String>,
+    pub rooms_by_name: HashMap<String, usize>,
+    pub cached_rooms: Vec<CachedRoom>,
+    pub collectibles: Vec<Collectible>,
+    pub parallax_layers: Vec<Parallax>,
+    pub current_level_index: usize,
+    pub camera_pos: egui::Vec2,
+    pub zoom_level: f32,
+    pub history: EditHistory,
+    pub is_dirty: bool,
+    pub saved_snapshot: Option<MapSnapshot>,
+    pub selection: Option<TileSelection>,
+    pub object_selection: Option<ObjectSelection>,
+    pub last_painted: Option<(usize, (i32, i32))>,
+    pub validation_report: Option<String>,
+}
+
+impl MapTab {
+    /// A tab with no map open, at the default zoom.
+    pub fn new() -> Self {
+        Self { id: NEXT_TAB_ID.fetch_add(1, Ordering::Relaxed), zoom_level: 1.0, ..Self::default() }
+    }
+
+    /// Exchange this tab's state with the editor's live fields.
+    fn swap_with(&mut self, editor: &mut CelesteMapEditor) {
+        swap(&mut self.map_data, &mut editor.map_data);
+        swap(&mut self.bin_path, &mut editor.bin_path);
+        swap(&mut self.bin_modified, &mut editor.bin_modified);
+        swap(&mut self.map_meta, &mut editor.map_meta);
+        swap(&mut self.level_names, &mut editor.level_names);
+        swap(&mut self.rooms_by_name, &mut editor.rooms_by_name);
+        swap(&mut self.cached_rooms, &mut editor.cached_rooms);
+        swap(&mut self.collectibles, &mut editor.collectibles);
+        swap(&mut self.parallax_layers, &mut editor.parallax_layers);
+        swap(&mut self.current_level_index, &mut editor.current_level_index);
+        swap(&mut self.camera_pos, &mut editor.camera_pos);
+        swap(&mut self.zoom_level, &mut editor.zoom_level);
+        swap(&mut self.history, &mut editor.history);
+        swap(&mut self.is_dirty, &mut editor.is_dirty);
+        swap(&mut self.saved_snapshot, &mut editor.saved_snapshot);
+        swap(&mut self.selection, &mut editor.selection);
+        swap(&mut self.object_selection, &mut editor.object_selection);
+        swap(&mut self.last_painted, &mut editor.last_painted);
+        swap(&mut self.validation_report, &mut editor.validation_report);
+    }
+}
+
+/// Tab label: the map's file name, or "Untitled", with `*` when it has unsaved changes.
+pub fn tab_title(editor: &CelesteMapEditor, idx: usize) -> String {
+    let (bin_path, has_map, dirty) = if idx == editor.active_tab {
+        (editor.bin_path.as_deref(), editor.map_data.is_some(), editor.is_dirty)
+    } else {
+        let Some(tab) = editor.open_maps.get(idx) else { return String::new() };
+        (tab.bin_path.as_deref(), tab.map_data.is_some(), tab.is_dirty)
+    };
+    let name = match bin_path {
+        Some(path) => Path::new(path).file_name().map_or(path.to_string(), |n| n.to_string_lossy().to_string()),
+        None if has_map => "Untitled".to_string(),
+        None => "Empty".to_string(),
+    };
+    if dirty { format!("{}*", name) } else { name }
+}
+
+/// Whether tab `idx` has unsaved changes.
+pub fn tab_is_dirty(editor: &CelesteMapEditor, idx: usize) -> bool {
+    if idx == editor.active_tab {
+        editor.is_dirty
+    } else {
+        editor.open_maps.get(idx).map_or(false, |tab| tab.is_dirty)
+    }
+}
+
+/// Make tab `idx` the one shown and edited.
+pub fn switch_tab(editor: &mut CelesteMapEditor, idx: usize) {
+    if idx == editor.active_tab || idx >= editor.open_maps.len() {
+        return;
+    }
+    // Drags and dialogs refer to rooms of the map being left
+    editor.paint_stroke = None;
+    editor.entity_drag = None;
+    editor.group_drag = None;
+    editor.room_resize = None;
+    editor.trigger_draft = None;
+    editor.new_trigger = None;
+    editor.trigger_resize = None;
+    editor.selection_band = None;
+    editor.line_start = None;
+    editor.room_properties = None;
+    editor.camera_animation = None;
+    editor.find_results.clear();
+    editor.find_flash = None;
+    editor.room_order_drag = None;
+    editor.inspect_entity = None;
+    editor.image_import = None;
+    // So are the prompts and the map picked in the Open dialog
+    editor.show_reload_confirm = false;
+    editor.show_entity_properties = false;
+    editor.show_open_dialog = false;
+    editor.open_mod_map = None;
+    // Park the live map in the current tab's slot, then bring the chosen tab's map in
+    let active = editor.active_tab;
+    let mut current = std::mem::take(&mut editor.open_maps[active]);
+    current.swap_with(editor);
+    editor.open_maps[active] = current;
+    let mut next = std::mem::take(&mut editor.open_maps[idx]);
+    next.swap_with(editor);
+    editor.open_maps[idx] = next;
+    editor.active_tab = idx;
+    if std::mem::take(&mut editor.open_maps[idx].needs_recache) {
+        editor.extract_level_names();
+        editor.cache_rooms();
+    }
+    refresh_changes(editor);
+}
+
+/// Copy room `index` of the active map into the map of tab `target`, as one undoable edit there.
+/// The copy is renamed or moved as `copy_room_into` decides; the tab's rooms are cached again
+/// when it is next shown.
+pub fn copy_room_to_tab(editor: &mut CelesteMapEditor, index: usize, target: usize) {
+    if target == editor.active_tab { return; }
+    let Some(room) = editor.cached_rooms.get(index).map(|r| r.json.clone()) else { return };
+    let title = tab_title(editor, target);
+    let free_space = editor.copy_room_to_free_space;
+    let Some(tab) = editor.open_maps.get_mut(target) else { return };
+    let Some(map) = tab.map_data.as_mut() else {
+        editor.notifications.warn(format!("{} has no map to copy the room into", title));
+        return;
+    };
+    let result = copy_room_into(&room, map, free_space).and_then(|name| {
+        let levels = map_model::levels(map).ok_or("Target map has no levels")?;
+        let room_idx = levels.len() - 1;
+        let level = levels[room_idx].clone();
+        let description = format!("Copy room {} from another map", name);
+        Ok((name, EditCommand::RoomAdd { room_idx, description, level }))
+    });
+    match result {
+        Ok((name, command)) => {
+            tab.history.push(command);
+            tab.is_dirty = true;
+            tab.needs_recache = true;
+            editor.notifications.info(format!("Copied room as {} into {}", name, title));
+        }
+        Err(e) => editor.notifications.error(format!("Could not copy room: {}", e)),
+    }
+}
+
+/// Open an empty tab and switch to it.
+pub fn new_tab(editor: &mut CelesteMapEditor) {
+    editor.open_maps.push(MapTab::new());
+    switch_tab(editor, editor.open_maps.len() - 1);
+}
+
+/// Note in `editor.last_views` where tab `idx` was left. Maps without a file have nothing to note.
+fn note_tab_view(editor: &mut CelesteMapEditor, idx: usize) {
+    let (bin_path, view) = if idx == editor.active_tab {
+        (editor.bin_path.clone(), current_view(editor))
+    } else {
+        let Some(tab) = editor.open_maps.get(idx) else { return };
+        let view = ViewBookmark {
+            camera_x: tab.camera_pos.x,
+            camera_y: tab.camera_pos.y,
+            zoom: tab.zoom_level,
+            show_all_rooms: editor.show_all_rooms,
+            room: tab.current_level_index,
+        };
+        (tab.bin_path.clone(), view)
+    };
+    if let Some(bin_path) = bin_path {
+        editor.last_views.insert(bin_path, view);
+    }
+}
+
+/// Remember the view of the active map, before it is replaced or closed.
+pub fn remember_view(editor: &mut CelesteMapEditor) {
+    note_tab_view(editor, editor.active_tab);
+    save_last_views(&editor.last_views);
+}
+
+/// Remember the view of every open map; called when the window closes.
+pub fn remember_open_views(editor: &mut CelesteMapEditor) {
+    for idx in 0..editor.open_maps.len() {
+        note_tab_view(editor, idx);
+    }
+    save_last_views(&editor.last_views);
+}
+
+/// Close tab `idx`, discarding unsaved changes; callers confirm first when `tab_is_dirty`.
+/// Closing the last tab leaves an empty one.
+pub fn close_tab(editor: &mut CelesteMapEditor, idx: usize) {
+    if idx >= editor.open_maps.len() {
+        return;
+    }
+    note_tab_view(editor, idx);
+    save_last_views(&editor.last_views);
+    if editor.open_maps.len() == 1 {
+        editor.open_maps.push(MapTab::new());
+    }
+    if idx == editor.active_tab {
+        switch_tab(editor, if idx + 1 < editor.open_maps.len() { idx + 1 } else { idx - 1 });
+    }
+    editor.open_maps.remove(idx);
+    if idx < editor.active_tab {
+        editor.active_tab -= 1;
+    }
+}
+
+/// Index of the tab with id `id`, if it is still open.
+pub fn tab_index(editor: &CelesteMapEditor, id: u64) -> Option<usize> {
+    editor.open_maps.iter().position(|tab| tab.id == id)
+}
+
+/// Ask before closing a tab with unsaved changes.
+pub fn request_close_tab(editor: &mut CelesteMapEditor, idx: usize) {
+    if tab_is_dirty(editor, idx) {
+        editor.close_tab_confirm = editor.open_maps.get(idx).map(|tab| tab.id);
+    } else {
+        close_tab(editor, idx);
+    }
+}
@@ -0,0 +1,37 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use log::debug;
+
+/// A saved view: where the camera was, how far zoomed, and in which display mode.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct ViewBookmark {
+    pub camera_x: f32,
+    pub camera_y: f32,
+    pub zoom: f32,
+    pub show_all_rooms: bool,
+    pub room: usize,
+}
+
+/// Bookmarks per map, keyed by .bin path, then by slot 1..9.
+pub type MapBookmarks = HashMap<String, BTreeMap<u8, ViewBookmark>>;
+
+fn bookmarks_path() -> PathBuf {
+    let config_dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+    config_dir.join("summit_bookmarks.json")
+}
+
+pub fn load_bookmarks() -> MapBookmarks {
+    std::fs::read_to_string(bookmarks_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_bookmarks(bookmarks: &MapBookmarks) {
+    if let Ok(json) = serde_json::to_string_pretty(bookmarks) {
+        if let Err(e) = std::fs::write(bookmarks_path(), json) {
+            debug!("Failed to save bookmarks: {}", e);
+        }
+    }
+}
@@ -18,6 +18,20 @@ pub struct KeyBindings {
     pub zoom_out: InputBinding,
     pub save: InputBinding,
     pub open: InputBinding,
+    pub toggle_grid: InputBinding,
+    pub toggle_labels: InputBinding,
+    pub toggle_all_rooms: InputBinding,
+    pub toggle_textures: InputBinding,
+    pub toggle_fg_decals: InputBinding,
+    pub eyedropper: InputBinding,
+    pub flood_fill: InputBinding,
+    pub line_tool: InputBinding,
+    /// Used together with Alt.
+    pub rect_select: InputBinding,
+    pub move_tool: InputBinding,
+    /// Used together with Ctrl+Shift.
+    pub go_to_room: InputBinding,
+    pub reset_view: InputBinding,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -35,6 +49,18 @@ pub enum BindingType {
     ZoomOut,
     Save,
     Open,
+    ToggleGrid,
+    ToggleLabels,
+    ToggleAllRooms,
+    ToggleTextures,
+    ToggleFgDecals,
+    Eyedropper,
+    FloodFill,
+    LineTool,
+    RectSelect,
+    MoveTool,
+    GoToRoom,
+    ResetView,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -46,6 +72,31 @@ struct SerializableKeyBindings {
     zoom_out: String,
     save: String,
     open: String,
+    // Missing in configs written before these bindings existed
+    #[serde(default)]
+    toggle_grid: String,
+    #[serde(default)]
+    toggle_labels: String,
+    #[serde(default)]
+    toggle_all_rooms: String,
+    #[serde(default)]
+    toggle_textures: String,
+    #[serde(default)]
+    toggle_fg_decals: String,
+    #[serde(default)]
+    eyedropper: String,
+    #[serde(default)]
+    flood_fill: String,
+    #[serde(default)]
+    line_tool: String,
+    #[serde(default)]
+    rect_select: String,
+    #[serde(default)]
+    move_tool: String,
+    #[serde(default)]
+    go_to_room: String,
+    #[serde(default)]
+    reset_view: String,
 }
 
 impl Default for KeyBindings {
@@ -58,6 +109,18 @@ impl Default for KeyBindings {
             zoom_out: InputBinding::Key(egui::Key::Q),
             save: InputBinding::Key(egui::Key::S),
             open: InputBinding::Key(egui::Key::O),
+            toggle_grid: InputBinding::Key(egui::Key::G),
+            toggle_labels: InputBinding::Key(egui::Key::N),
+            toggle_all_rooms: InputBinding::Key(egui::Key::Tab),
+            toggle_textures: InputBinding::Key(egui::Key::T),
+            toggle_fg_decals: InputBinding::Key(egui::Key::D),
+            eyedropper: InputBinding::Key(egui::Key::I),
+            flood_fill: InputBinding::Key(egui::Key::F),
+            line_tool: InputBinding::Key(egui::Key::L),
+            rect_select: InputBinding::Key(egui::Key::S),
+            move_tool: InputBinding::Key(egui::Key::M),
+            go_to_room: InputBinding::Key(egui::Key::G),
+            reset_view: InputBinding::Key(egui::Key::Home),
         }
     }
 }
@@ -82,6 +145,18 @@ impl KeyBindings {
             zoom_out: self.binding_to_string(&self.zoom_out),
             save: self.binding_to_string(&self.save),
             open: self.binding_to_string(&self.open),
+            toggle_grid: self.binding_to_string(&self.toggle_grid),
+            toggle_labels: self.binding_to_string(&self.toggle_labels),
+            toggle_all_rooms: self.binding_to_string(&self.toggle_all_rooms),
+            toggle_textures: self.binding_to_string(&self.toggle_textures),
+            toggle_fg_decals: self.binding_to_string(&self.toggle_fg_decals),
+            eyedropper: self.binding_to_string(&self.eyedropper),
+            flood_fill: self.binding_to_string(&self.flood_fill),
+            line_tool: self.binding_to_string(&self.line_tool),
+            rect_select: self.binding_to_string(&self.rect_select),
+            move_tool: self.binding_to_string(&self.move_tool),
+            go_to_room: self.binding_to_string(&self.go_to_room),
+            reset_view: self.binding_to_string(&self.reset_view),
         }
     }
 
@@ -105,6 +180,24 @@ impl KeyBindings {
         bindings.zoom_out = Self::parse_binding(&serial.zoom_out, bindings.zoom_out);
         bindings.save = Self::parse_binding(&serial.save, bindings.save);
         bindings.open = Self::parse_binding(&serial.open, bindings.open);
+        bindings.toggle_grid = Self::parse_binding(&serial.toggle_grid, bindings.toggle_grid);
+        bindings.toggle_labels = Self::parse_binding(&serial.toggle_labels, bindings.toggle_labels);
+        bindings.toggle_all_rooms = Self::parse_binding(&serial.toggle_all_rooms, bindings.toggle_all_rooms);
+        bindings.toggle_textures = Self::parse_binding(&serial.toggle_textures, bindings.toggle_textures);
+        bindings.toggle_fg_decals = Self::parse_binding(&serial.toggle_fg_decals, bindings.toggle_fg_decals);
+        bindings.eyedropper = Self::parse_binding(&serial.eyedropper, bindings.eyedropper);
+        bindings.flood_fill = Self::parse_binding(&serial.flood_fill, bindings.flood_fill);
+        bindings.line_tool = Self::parse_binding(&serial.line_tool, bindings.line_tool);
+        bindings.rect_select = Self::parse_binding(&serial.rect_select, bindings.rect_select);
+        bindings.move_tool = Self::parse_binding(&serial.move_tool, bindings.move_tool);
+        bindings.go_to_room = Self::parse_binding(&serial.go_to_room, bindings.go_to_room);
+        bindings.reset_view = Self::parse_binding(&serial.reset_view, bindings.reset_view);
+
+        // Labels were toggled with L until the Line tool took it; move a config saved before then to N
+        let old_labels_key = InputBinding::Key(egui::Key::L);
+        if bindings.toggle_labels == old_labels_key && bindings.line_tool == old_labels_key {
+            bindings.toggle_labels = Self::default().toggle_labels;
+        }
         
         bindings
     }
@@ -123,6 +216,15 @@ impl KeyBindings {
                 "A" => InputBinding::Key(egui::Key::A),
                 "W" => InputBinding::Key(egui::Key::W),
                 "D" => InputBinding::Key(egui::Key::D),
+                "G" => InputBinding::Key(egui::Key::G),
+                "L" => InputBinding::Key(egui::Key::L),
+                "T" => InputBinding::Key(egui::Key::T),
+                "I" => InputBinding::Key(egui::Key::I),
+                "F" => InputBinding::Key(egui::Key::F),
+                "M" => InputBinding::Key(egui::Key::M),
+                "N" => InputBinding::Key(egui::Key::N),
+                "Tab" => InputBinding::Key(egui::Key::Tab),
+                "Home" => InputBinding::Key(egui::Key::Home),
                 // Add more keys as needed
                 _ => default,
             }
@@ -142,6 +244,8 @@ impl KeyBindings {
     pub fn get_all_available_keys() -> Vec<egui::Key> {
         vec![
             egui::Key::Space,
+            egui::Key::Tab,
+            egui::Key::Home,
             egui::Key::A, egui::Key::B, egui::Key::C, egui::Key::D, egui::Key::E,
             egui::Key::F, egui::Key::G, egui::Key::H, egui::Key::I, egui::Key::J,
             egui::Key::K, egui::Key::L, egui::Key::M, egui::Key::N, egui::Key::O,
@@ -192,6 +296,18 @@ impl KeyBindings {
             BindingType::ZoomOut => &self.zoom_out,
             BindingType::Save => &self.save,
             BindingType::Open => &self.open,
+            BindingType::ToggleGrid => &self.toggle_grid,
+            BindingType::ToggleLabels => &self.toggle_labels,
+            BindingType::ToggleAllRooms => &self.toggle_all_rooms,
+            BindingType::ToggleTextures => &self.toggle_textures,
+            BindingType::ToggleFgDecals => &self.toggle_fg_decals,
+            BindingType::Eyedropper => &self.eyedropper,
+            BindingType::FloodFill => &self.flood_fill,
+            BindingType::LineTool => &self.line_tool,
+            BindingType::RectSelect => &self.rect_select,
+            BindingType::MoveTool => &self.move_tool,
+            BindingType::GoToRoom => &self.go_to_room,
+            BindingType::ResetView => &self.reset_view,
         };
         
         match binding {
@@ -209,6 +325,18 @@ impl KeyBindings {
             BindingType::ZoomOut => &self.zoom_out,
             BindingType::Save => &self.save,
             BindingType::Open => &self.open,
+            BindingType::ToggleGrid => &self.toggle_grid,
+            BindingType::ToggleLabels => &self.toggle_labels,
+            BindingType::ToggleAllRooms => &self.toggle_all_rooms,
+            BindingType::ToggleTextures => &self.toggle_textures,
+            BindingType::ToggleFgDecals => &self.toggle_fg_decals,
+            BindingType::Eyedropper => &self.eyedropper,
+            BindingType::FloodFill => &self.flood_fill,
+            BindingType::LineTool => &self.line_tool,
+            BindingType::RectSelect => &self.rect_select,
+            BindingType::MoveTool => &self.move_tool,
+            BindingType::GoToRoom => &self.go_to_room,
+            BindingType::ResetView => &self.reset_view,
         };
         
         match binding {
@@ -226,6 +354,18 @@ impl KeyBindings {
             BindingType::ZoomOut => &self.zoom_out,
             BindingType::Save => &self.save,
             BindingType::Open => &self.open,
+            BindingType::ToggleGrid => &self.toggle_grid,
+            BindingType::ToggleLabels => &self.toggle_labels,
+            BindingType::ToggleAllRooms => &self.toggle_all_rooms,
+            BindingType::ToggleTextures => &self.toggle_textures,
+            BindingType::ToggleFgDecals => &self.toggle_fg_decals,
+            BindingType::Eyedropper => &self.eyedropper,
+            BindingType::FloodFill => &self.flood_fill,
+            BindingType::LineTool => &self.line_tool,
+            BindingType::RectSelect => &self.rect_select,
+            BindingType::MoveTool => &self.move_tool,
+            BindingType::GoToRoom => &self.go_to_room,
+            BindingType::ResetView => &self.reset_view,
         };
         
         match binding {
@@ -243,6 +383,18 @@ impl KeyBindings {
             BindingType::ZoomOut => self.zoom_out = new_binding,
             BindingType::Save => self.save = new_binding,
             BindingType::Open => self.open = new_binding,
+            BindingType::ToggleGrid => self.toggle_grid = new_binding,
+            BindingType::ToggleLabels => self.toggle_labels = new_binding,
+            BindingType::ToggleAllRooms => self.toggle_all_rooms = new_binding,
+            BindingType::ToggleTextures => self.toggle_textures = new_binding,
+            BindingType::ToggleFgDecals => self.toggle_fg_decals = new_binding,
+            BindingType::Eyedropper => self.eyedropper = new_binding,
+            BindingType::FloodFill => self.flood_fill = new_binding,
+            BindingType::LineTool => self.line_tool = new_binding,
+            BindingType::RectSelect => self.rect_select = new_binding,
+            BindingType::MoveTool => self.move_tool = new_binding,
+            BindingType::GoToRoom => self.go_to_room = new_binding,
+            BindingType::ResetView => self.reset_view = new_binding,
         }
     }
 }
\ No newline at end of file
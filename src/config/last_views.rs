@@ -0,0 +1,28 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use log::debug;
+
+use crate::config::bookmarks::ViewBookmark;
+
+/// The view each map was last left at, keyed by .bin path, so reopening it comes back there.
+pub type LastViews = HashMap<String, ViewBookmark>;
+
+fn last_views_path() -> PathBuf {
+    let config_dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+    config_dir.join("summit_last_views.json")
+}
+
+pub fn load_last_views() -> LastViews {
+    std::fs::read_to_string(last_views_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_last_views(views: &LastViews) {
+    if let Ok(json) = serde_json::to_string_pretty(views) {
+        if let Err(e) = std::fs::write(last_views_path(), json) {
+            debug!("Failed to save last views: {}", e);
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use log::debug;
+
+/// Settings for File > Test in Celeste.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LaunchSettings {
+    /// Everest mod folder under `Mods/` that maps outside any mod are copied into.
+    #[serde(default = "default_mod_name")]
+    pub mod_name: String,
+    /// Pass `--console` so Everest opens its log console.
+    #[serde(default)]
+    pub console: bool,
+}
+
+fn default_mod_name() -> String {
+    "SummitTest".to_string()
+}
+
+impl Default for LaunchSettings {
+    fn default() -> Self {
+        Self { mod_name: default_mod_name(), console: false }
+    }
+}
+
+fn launch_settings_path() -> PathBuf {
+    let config_dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+    config_dir.join("summit_launch.json")
+}
+
+pub fn load_launch_settings() -> LaunchSettings {
+    std::fs::read_to_string(launch_settings_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_launch_settings(settings: &LaunchSettings) {
+    if let Ok(json) = serde_json::to_string_pretty(settings) {
+        if let Err(e) = std::fs::write(launch_settings_path(), json) {
+            debug!("Failed to save launch settings: {}", e);
+        }
+    }
+}
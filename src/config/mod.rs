@@ -1 +1,7 @@
-pub mod keybindings;
\ No newline at end of file
+pub mod bookmarks;
+pub mod keybindings;
+pub mod last_views;
+pub mod launch;
+pub mod recent;
+pub mod room_colors;
+pub mod view_settings;
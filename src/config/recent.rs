@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+use log::debug;
+
+// Number of maps kept in the recent list
+const MAX_RECENT_MAPS: usize = 10;
+
+fn recent_maps_path() -> PathBuf {
+    let config_dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+    config_dir.join("summit_recent_maps.json")
+}
+
+/// Most recently opened maps, newest first.
+pub fn load_recent_maps() -> Vec<String> {
+    std::fs::read_to_string(recent_maps_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Move `path` to the front of the recent list and persist it.
+pub fn add_recent_map(recent: &mut Vec<String>, path: &str) {
+    recent.retain(|p| p != path);
+    recent.insert(0, path.to_string());
+    recent.truncate(MAX_RECENT_MAPS);
+    if let Ok(json) = serde_json::to_string_pretty(recent) {
+        if let Err(e) = std::fs::write(recent_maps_path(), json) {
+            debug!("Failed to save recent maps: {}", e);
+        }
+    }
+}
+
+fn last_mod_path() -> PathBuf {
+    let config_dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+    config_dir.join("summit_last_mod.json")
+}
+
+/// Mod folder a map was last opened from in the Open dialog.
+pub fn load_last_mod() -> Option<String> {
+    std::fs::read_to_string(last_mod_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+}
+
+pub fn save_last_mod(name: &str) {
+    if let Ok(json) = serde_json::to_string(name) {
+        if let Err(e) = std::fs::write(last_mod_path(), json) {
+            debug!("Failed to save last mod: {}", e);
+        }
+    }
+}
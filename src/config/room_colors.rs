@@ -0,0 +1,26 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use log::debug;
+
+/// Editor-only room colours per map, keyed by .bin path, then by room name. Never written to the map.
+pub type RoomColorOverrides = HashMap<String, HashMap<String, [u8; 3]>>;
+
+fn room_colors_path() -> PathBuf {
+    let config_dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+    config_dir.join("summit_room_colors.json")
+}
+
+pub fn load_room_colors() -> RoomColorOverrides {
+    std::fs::read_to_string(room_colors_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_room_colors(colors: &RoomColorOverrides) {
+    if let Ok(json) = serde_json::to_string_pretty(colors) {
+        if let Err(e) = std::fs::write(room_colors_path(), json) {
+            debug!("Failed to save room colors: {}", e);
+        }
+    }
+}
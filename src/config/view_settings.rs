@@ -0,0 +1,125 @@
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use log::debug;
+use crate::ui::render::{normalize_layer_order, LayerId};
+
+/// Viewport preferences kept between sessions.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ViewSettings {
+    /// Ease zoom and camera jumps instead of applying them at once.
+    #[serde(default = "enabled")]
+    pub animate_view: bool,
+    /// Plain scrolling pans the view and Ctrl+scroll or pinch zooms, as on a trackpad.
+    /// Otherwise scrolling zooms and Shift+scroll pans.
+    #[serde(default)]
+    pub scroll_pans: bool,
+    /// Draw order of the render layers, back to front.
+    #[serde(default = "default_layer_order")]
+    pub layer_order: Vec<LayerId>,
+    /// Scroll the view while a drag holds the pointer near the canvas edge.
+    #[serde(default = "enabled")]
+    pub autoscroll: bool,
+    /// Autoscroll speed with the pointer right at the edge, in screen points per second.
+    #[serde(default = "default_autoscroll_speed")]
+    pub autoscroll_speed: f32,
+    /// Room name size at 100% zoom, in points; it scales with zoom within bounds.
+    #[serde(default = "default_label_size")]
+    pub label_size: f32,
+    /// Center room names on a translucent backing instead of the top-left corner.
+    #[serde(default)]
+    pub center_labels: bool,
+    /// Outline of the current room, sRGB. Amber against the blue of the others, which stays
+    /// distinct under the common kinds of colour blindness.
+    #[serde(default = "default_selected_outline")]
+    pub selected_outline: [u8; 3],
+    /// Outline of every other room, sRGB.
+    #[serde(default = "default_unselected_outline")]
+    pub unselected_outline: [u8; 3],
+    #[serde(default)]
+    pub selected_outline_style: OutlineStyle,
+}
+
+/// How the current room's outline is stroked, for more emphasis than colour alone.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutlineStyle {
+    #[default]
+    Solid,
+    Dashed,
+    /// Width and brightness pulse; repaints continuously.
+    Pulsing,
+}
+
+impl OutlineStyle {
+    pub const ALL: [OutlineStyle; 3] = [OutlineStyle::Solid, OutlineStyle::Dashed, OutlineStyle::Pulsing];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            OutlineStyle::Solid => "Solid",
+            OutlineStyle::Dashed => "Dashed",
+            OutlineStyle::Pulsing => "Pulsing",
+        }
+    }
+}
+
+fn enabled() -> bool {
+    true
+}
+
+fn default_autoscroll_speed() -> f32 {
+    600.0
+}
+
+fn default_label_size() -> f32 {
+    16.0
+}
+
+pub fn default_selected_outline() -> [u8; 3] {
+    [255, 176, 0]
+}
+
+pub fn default_unselected_outline() -> [u8; 3] {
+    [110, 130, 170]
+}
+
+fn default_layer_order() -> Vec<LayerId> {
+    LayerId::ALL.to_vec()
+}
+
+impl Default for ViewSettings {
+    fn default() -> Self {
+        Self {
+            animate_view: true,
+            scroll_pans: false,
+            layer_order: default_layer_order(),
+            autoscroll: true,
+            autoscroll_speed: default_autoscroll_speed(),
+            label_size: default_label_size(),
+            center_labels: false,
+            selected_outline: default_selected_outline(),
+            unselected_outline: default_unselected_outline(),
+            selected_outline_style: OutlineStyle::Solid,
+        }
+    }
+}
+
+fn view_settings_path() -> PathBuf {
+    let config_dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+    config_dir.join("summit_view_settings.json")
+}
+
+pub fn load_view_settings() -> ViewSettings {
+    let mut settings: ViewSettings = std::fs::read_to_string(view_settings_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    settings.layer_order = normalize_layer_order(&settings.layer_order);
+    settings
+}
+
+pub fn save_view_settings(settings: &ViewSettings) {
+    if let Ok(json) = serde_json::to_string_pretty(settings) {
+        if let Err(e) = std::fs::write(view_settings_path(), json) {
+            debug!("Failed to save view settings: {}", e);
+        }
+    }
+}
@@ -2,9 +2,15 @@
 
 use std::env;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Oldest Celeste release whose tile XMLs and atlases we know how to read
+const MIN_SUPPORTED_VERSION: (u32, u32, u32) = (1, 4, 0);
 
 pub struct CelesteAssets {
     pub celeste_dir: Option<PathBuf>,
+    /// Game version read from the install, if it could be determined.
+    pub game_version: Option<String>,
 }
 
 impl CelesteAssets {
@@ -19,6 +25,12 @@ impl CelesteAssets {
         }
         #[cfg(target_os = "windows")]
         {
+            if let Some(steam) = Self::windows_steam_install_path() {
+                let path = steam.join("steamapps").join("common").join("Celeste");
+                if path.exists() {
+                    return Some(path);
+                }
+            }
             if let Ok(appdata) = env::var("APPDATA") {
                 let path = PathBuf::from(appdata).join("Celeste");
                 if path.exists() {
@@ -36,17 +48,154 @@ impl CelesteAssets {
         }
         None
     }
+    /// Read Steam's install directory from the registry (32-bit view first, then 64-bit).
+    #[cfg(target_os = "windows")]
+    fn windows_steam_install_path() -> Option<PathBuf> {
+        use winreg::enums::HKEY_LOCAL_MACHINE;
+        use winreg::RegKey;
+
+        let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+        for subkey in ["SOFTWARE\\WOW6432Node\\Valve\\Steam", "SOFTWARE\\Valve\\Steam"] {
+            if let Ok(key) = hklm.open_subkey(subkey) {
+                if let Ok(install_path) = key.get_value::<String, _>("InstallPath") {
+                    return Some(PathBuf::from(install_path));
+                }
+            }
+        }
+        None
+    }
+
+    /// Directory containing Celeste.exe (inside the .app bundle on macOS).
+    pub fn game_root(celeste_dir: &Path) -> PathBuf {
+        let root = celeste_dir.to_path_buf();
+        #[cfg(target_os = "macos")]
+        let root = {
+            let app = if root.ends_with("Celeste.app") { root } else { root.join("Celeste.app") };
+            app.join("Contents").join("Resources")
+        };
+        root
+    }
+
+    /// Where a Celeste install at `celeste_dir` keeps ForegroundTiles.xml; the first file checked
+    /// when a folder is picked.
+    pub fn foreground_tiles_xml(celeste_dir: &Path) -> PathBuf {
+        Self::game_root(celeste_dir).join("Content").join("Graphics").join("ForegroundTiles.xml")
+    }
+
+    /// Everest's mod folder, `Mods/` next to the game files.
+    pub fn mods_dir(&self) -> Option<PathBuf> {
+        Some(Self::game_root(self.celeste_dir.as_deref()?).join("Mods"))
+    }
+
+    /// Command that starts the game, with Everest's `--console` flag if asked.
+    pub fn launch_command(&self, console: bool) -> Result<Command, String> {
+        let celeste_dir = self.celeste_dir.as_deref().ok_or("Celeste path is not set")?;
+        #[cfg(target_os = "macos")]
+        let mut command = {
+            let app = if celeste_dir.ends_with("Celeste.app") { celeste_dir.to_path_buf() } else { celeste_dir.join("Celeste.app") };
+            if !app.exists() {
+                return Err(format!("Celeste.app not found at {}", app.display()));
+            }
+            // `open` starts the bundle the way Finder would; game arguments follow --args
+            let mut command = Command::new("open");
+            command.arg("-a").arg(app).arg("--args");
+            command
+        };
+        #[cfg(target_os = "windows")]
+        let mut command = {
+            let root = Self::game_root(celeste_dir);
+            let exe = root.join("Celeste.exe");
+            if !exe.exists() {
+                return Err(format!("Celeste.exe not found at {}", exe.display()));
+            }
+            let mut command = Command::new(exe);
+            command.current_dir(root);
+            command
+        };
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        let mut command = {
+            let root = Self::game_root(celeste_dir);
+            // The launcher script first, then the raw binary of older installs
+            let exe = ["Celeste", "Celeste.bin.x86_64"].iter()
+                .map(|name| root.join(name))
+                .find(|p| p.exists())
+                .ok_or_else(|| format!("No Celeste executable found in {}", root.display()))?;
+            let mut command = Command::new(exe);
+            command.current_dir(root);
+            command
+        };
+        if console {
+            command.arg("--console");
+        }
+        Ok(command)
+    }
+
+    /// Read the game version from `version.txt`, falling back to Celeste.exe's ProductVersion.
+    pub fn detect_game_version(celeste_dir: &Path) -> Option<String> {
+        let root = Self::game_root(celeste_dir);
+        for candidate in [root.join("version.txt"), root.join("Content").join("version.txt")] {
+            if let Ok(text) = std::fs::read_to_string(&candidate) {
+                let version = text.trim();
+                if !version.is_empty() {
+                    return Some(version.to_string());
+                }
+            }
+        }
+        let exe = std::fs::read(root.join("Celeste.exe")).ok()?;
+        Self::read_product_version(&exe)
+    }
+
+    /// Pull the ProductVersion string out of a PE file's VS_VERSION_INFO resource.
+    fn read_product_version(exe: &[u8]) -> Option<String> {
+        let key: Vec<u8> = "ProductVersion\0".encode_utf16().flat_map(|c| c.to_le_bytes()).collect();
+        let start = exe.windows(key.len()).position(|w| w == key.as_slice())? + key.len();
+        // The value is 32-bit aligned after the key
+        let mut i = start + (4 - start % 4) % 4;
+        let mut units = Vec::new();
+        while i + 1 < exe.len() {
+            let unit = u16::from_le_bytes([exe[i], exe[i + 1]]);
+            if unit == 0 { break; }
+            units.push(unit);
+            i += 2;
+        }
+        let version = String::from_utf16(&units).ok()?;
+        let version = version.trim().to_string();
+        if version.is_empty() { None } else { Some(version) }
+    }
+
+    /// False when the version parses and is older than MIN_SUPPORTED_VERSION.
+    pub fn is_version_supported(version: &str) -> bool {
+        let mut parts = version.split(|c: char| !c.is_ascii_digit()).filter(|p| !p.is_empty()).map(|p| p.parse::<u32>().unwrap_or(0));
+        let parsed = (parts.next(), parts.next(), parts.next().unwrap_or(0));
+        match parsed {
+            (Some(major), Some(minor), patch) => (major, minor, patch) >= MIN_SUPPORTED_VERSION,
+            _ => true,
+        }
+    }
+
     pub fn new() -> Self {
         let detected = Self::detect_celeste_dir();
+        let game_version = detected.as_deref().and_then(Self::detect_game_version);
         Self {
             celeste_dir: detected,
+            game_version,
         }
     }
-    pub fn set_celeste_dir(&mut self, path: &Path) -> bool {
+    /// Use `path` as the Celeste install if it holds the tile XML and Gameplay atlas; otherwise
+    /// keep the current one and say which file is missing.
+    pub fn set_celeste_dir(&mut self, path: &Path) -> Result<(), String> {
+        let graphics = Self::game_root(path).join("Content").join("Graphics");
+        for expected in [Self::foreground_tiles_xml(path), graphics.join("Atlases").join("Gameplay.meta")] {
+            if !expected.exists() {
+                return Err(format!("Not a Celeste installation: {} is missing", expected.display()));
+            }
+        }
         self.celeste_dir = Some(path.to_path_buf());
-        true
+        self.game_version = Self::detect_game_version(path);
+        Ok(())
     }
     pub fn clear_celeste_dir(&mut self) {
         self.celeste_dir = None;
+        self.game_version = None;
     }
 }
\ No newline at end of file
@@ -1,16 +1,17 @@
 // src/binary_reader.rs
 #![allow(dead_code, unused_imports, unused_variables)]
-use std::io::{self, Read, Seek, SeekFrom};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
 use byteorder::{LittleEndian, ReadBytesExt};
 
-/// A helper for reading Celeste's binary formats
+/// A helper for reading Celeste's binary formats. Reads are buffered, and the reader also
+/// implements `BufRead` for formats with newline-delimited sections.
 pub struct BinaryReader<R: Read + Seek> {
-    reader: R,  // Changed to private for better encapsulation
+    reader: BufReader<R>,  // Changed to private for better encapsulation
 }
 
 impl<R: Read + Seek> BinaryReader<R> {
     pub fn new(reader: R) -> Self {
-        Self { reader }
+        Self { reader: BufReader::new(reader) }
     }
 
     /// Read a signed byte
@@ -58,15 +59,30 @@ impl<R: Read + Seek> BinaryReader<R> {
             .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
     }
 
+    /// Read one line, without its `\n` or `\r\n` ending. None at the end of the data.
+    pub fn read_line_str(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+        Ok(Some(line))
+    }
+
     /// Skip a number of bytes
     pub fn skip(&mut self, bytes: u64) -> io::Result<()> {
-        self.reader.seek(SeekFrom::Current(bytes as i64))?;
-        Ok(())
+        // Relative seeks stay inside the buffer when they can
+        self.reader.seek_relative(bytes as i64)
     }
 
     /// Get the current position in the file
     pub fn position(&mut self) -> io::Result<u64> {
-        self.reader.seek(SeekFrom::Current(0))
+        self.reader.stream_position()
     }
 
     /// Set the current position in the file
@@ -82,8 +98,25 @@ impl<R: Read + Seek> BinaryReader<R> {
         Ok(buffer)
     }
 
-    /// Access the internal reader (added to fix access without making reader public)
-    pub fn get_reader_mut(&mut self) -> &mut R {
+    /// Access the internal reader (added to fix access without making reader public).
+    /// This is the buffered reader: reading the inner one directly would skip buffered bytes.
+    pub fn get_reader_mut(&mut self) -> &mut BufReader<R> {
         &mut self.reader
     }
-}
\ No newline at end of file
+}
+
+impl<R: Read + Seek> Read for BinaryReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl<R: Read + Seek> BufRead for BinaryReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.reader.consume(amt)
+    }
+}
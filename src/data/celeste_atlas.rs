@@ -3,8 +3,9 @@
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{self, Read, Seek};
-use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
 use byteorder::{LittleEndian, ReadBytesExt};
 use eframe::egui;
 use image::RgbaImage;
@@ -34,14 +35,39 @@ pub struct Sprite {
     pub uv_rect: Option<egui::Rect>,
 }
 
+/// A decoded .data page, before its texture is created
+pub struct AtlasPage {
+    pub image: RgbaImage,
+    /// Pixels for the GPU texture; taken when the page is uploaded
+    pub color_image: Option<egui::ColorImage>,
+}
+
+/// Everything read from an atlas' .meta and .data files. Holds no egui handles, so it can be
+/// decoded on a background thread and uploaded on the main thread.
+pub struct AtlasData {
+    pub name: String,
+    /// Folder the pages were read from, for decoding them again later
+    pub dir: PathBuf,
+    pub data_files: Vec<String>,
+    /// Sprite path, data file and metadata, in file order
+    pub sprites: Vec<(String, String, SpriteMetadata)>,
+    pub pages: HashMap<String, AtlasPage>,
+    /// .data pages that could not be read; their sprites are left out
+    pub missing_pages: Vec<PathBuf>,
+}
+
 /// A Celeste texture atlas that contains multiple sprites
 pub struct Atlas {
     pub name: String,
     pub sprites: HashMap<String, Sprite>,
     pub textures: HashMap<String, egui::TextureHandle>,
     pub data_files: Vec<String>,
-    // Added to store raw image data for sprite extraction
-    pub images: HashMap<String, RgbaImage>,
+    /// Folder holding the .data pages
+    pub dir: PathBuf,
+    /// Page sizes in pixels, by data file
+    pub page_sizes: HashMap<String, [usize; 2]>,
+    /// CPU copies of the pages, only kept with `AtlasManager::keep_pixels`
+    pub pixels: HashMap<String, RgbaImage>,
 }
 
 lazy_static! {
@@ -49,16 +75,6 @@ lazy_static! {
 }
 
 impl Atlas {
-    pub fn new(name: &str) -> Self {
-        Self {
-            name: name.to_string(),
-            sprites: HashMap::new(),
-            textures: HashMap::new(),
-            data_files: Vec::new(),
-            images: HashMap::new(),
-        }
-    }
-
     pub fn get_sprite(&self, path: &str) -> Option<&Sprite> {
         self.sprites.get(path)
     }
@@ -67,38 +83,70 @@ impl Atlas {
 /// Manages multiple Celeste texture atlases
 pub struct AtlasManager {
     pub atlases: HashMap<String, Atlas>,
+    /// Keep each page's pixels after its texture is created. Off by default: the textures are all
+    /// drawing needs, and `with_atlas_image` decodes a page again when asked for its pixels.
+    pub keep_pixels: bool,
+    /// Sprites the open map uses but the atlas lacks, by atlas: path and how many times it is used.
+    /// Set by `note_missing_sprites`, so drawing never counts misses.
+    misses: HashMap<String, HashMap<String, u32>>,
     // Cache for faster atlas lookup by texture ID
     texture_id_to_atlas: HashMap<egui::TextureId, String>,
 }
 
+// Decoded data is handed across threads and the manager may be shared with worker threads
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<AtlasData>();
+    assert_send_sync::<AtlasManager>();
+};
+
 impl AtlasManager {
     pub fn new() -> Self {
         Self {
             atlases: HashMap::new(),
+            keep_pixels: false,
+            misses: HashMap::new(),
             texture_id_to_atlas: HashMap::new(),
         }
     }
 
-    /// Load a Celeste atlas from a .meta file
+    /// Load a Celeste atlas from a .meta file, blocking until its textures are created
     pub fn load_atlas(&mut self, name: &str, celeste_dir: &Path, ctx: &egui::Context) -> io::Result<()> {
-        debug!("Loading atlas '{}'", name);
+        let data = Self::decode_atlas(name, celeste_dir)?;
+        self.add_atlas(data, ctx);
+        Ok(())
+    }
+
+    /// Decode an atlas on a background thread. Join the handle and pass the result to `add_atlas`.
+    pub fn spawn_load(name: &str, celeste_dir: &Path) -> JoinHandle<io::Result<AtlasData>> {
+        let name = name.to_string();
+        let celeste_dir = celeste_dir.to_path_buf();
+        thread::spawn(move || Self::decode_atlas(&name, &celeste_dir))
+    }
+
+    /// Folder holding the .meta and .data files
+    fn atlas_dir(celeste_dir: &Path) -> PathBuf {
         // On MacOS, Celeste's assets are inside Celeste.app/Contents/Resources/Content/Graphics/Atlases
         // If the provided celeste_dir contains 'Celeste.app', use as-is. Otherwise, append 'Celeste.app'.
         let mut atlas_base = celeste_dir.to_path_buf();
         #[cfg(target_os = "macos")]
         {
-            use std::ffi::OsStr;
             if !celeste_dir.ends_with("Celeste.app") {
                 atlas_base = atlas_base.join("Celeste.app");
             }
             // Always append Contents/Resources
             atlas_base = atlas_base.join("Contents").join("Resources");
         }
-        let atlas_path = atlas_base
+        atlas_base
             .join("Content")
             .join("Graphics")
-            .join("Atlases");
+            .join("Atlases")
+    }
 
+    /// Read an atlas' sprites and decode its pages. Touches no egui state, so it is safe off the main thread.
+    pub fn decode_atlas(name: &str, celeste_dir: &Path) -> io::Result<AtlasData> {
+        debug!("Loading atlas '{}'", name);
+        let atlas_path = Self::atlas_dir(celeste_dir);
         let meta_path = atlas_path.join(format!("{}.meta", name));
 
         if !meta_path.exists() {
@@ -108,78 +156,143 @@ impl AtlasManager {
             ));
         }
 
-        let mut atlas = Atlas::new(name);
-        self.load_meta_file(&meta_path, &mut atlas, &atlas_path, ctx)?;
+        let mut data = AtlasData {
+            name: name.to_string(),
+            dir: atlas_path.clone(),
+            data_files: Vec::new(),
+            sprites: Vec::new(),
+            pages: HashMap::new(),
+            missing_pages: Vec::new(),
+        };
+        Self::load_meta_file(&meta_path, &mut data, &atlas_path)?;
 
-        debug!("Loaded {} sprites in atlas '{}'", atlas.sprites.len(), name);
-        debug!("Loaded {} textures in atlas '{}'", atlas.textures.len(), name);
-        debug!("Loaded {} images in atlas '{}'", atlas.images.len(), name);
+        debug!("Loaded {} sprites in atlas '{}'", data.sprites.len(), name);
+        debug!("Loaded {} pages in atlas '{}'", data.pages.len(), name);
+        Ok(data)
+    }
 
-        // Update texture ID to atlas mapping
-        for texture in atlas.textures.values() {
-            self.texture_id_to_atlas.insert(texture.id(), name.to_string());
+    /// Create the textures for decoded atlas data and make its sprites available. Must run on the main thread.
+    pub fn add_atlas(&mut self, mut data: AtlasData, ctx: &egui::Context) {
+        let name = data.name.clone();
+        // Loading the same atlas again replaces it; free its pages first so both sets don't sit on the GPU
+        self.unload_atlas(&name);
+        let mut textures = HashMap::new();
+        for (data_file, page) in data.pages.iter_mut() {
+            let Some(color_image) = page.color_image.take() else { continue };
+            let texture_name = format!("{}_{}", name, data_file);
+            let texture_handle = ctx.load_texture(texture_name, color_image, egui::TextureFilter::Nearest);
+            // Update texture ID to atlas mapping
+            self.texture_id_to_atlas.insert(texture_handle.id(), name.clone());
+            textures.insert(data_file.clone(), texture_handle);
         }
+        debug!("Loaded {} textures in atlas '{}'", textures.len(), name);
 
-        // Register all sprites in the global mapping
-        for (path, sprite) in &atlas.sprites {
-            // Ensure keys are stored as-is (should already be normalized with "decals/" prefix)
-            Self::register_sprite_global(name, path, sprite);
+        let mut sprites = HashMap::new();
+        for (path, data_file, metadata) in data.sprites.drain(..) {
+            let (Some(texture), Some(page)) = (textures.get(&data_file), data.pages.get(&data_file)) else { continue };
+            // Size needed for UV calculations
+            let atlas_width = page.image.width() as f32;
+            let atlas_height = page.image.height() as f32;
+
+            // Pre-compute UV coordinates
+            let uv_min = egui::pos2(
+                metadata.x as f32 / atlas_width,
+                metadata.y as f32 / atlas_height,
+            );
+            let uv_max = egui::pos2(
+                (metadata.x as f32 + metadata.width as f32) / atlas_width,
+                (metadata.y as f32 + metadata.height as f32) / atlas_height,
+            );
+            let uv_rect = egui::Rect::from_min_max(uv_min, uv_max);
+
+            let sprite = Sprite {
+                metadata,
+                texture_id: texture.id(),
+                data_file,
+                uv_rect: Some(uv_rect),
+            };
+            // Register all sprites in the global mapping
+            Self::register_sprite_global(&name, &path, &sprite);
+            sprites.insert(path, sprite);
         }
 
-        self.atlases.insert(name.to_string(), atlas);
+        let page_sizes = data.pages.iter()
+            .map(|(file, page)| (file.clone(), [page.image.width() as usize, page.image.height() as usize]))
+            .collect();
+        // The textures hold the pixels now; the CPU copies are dropped here unless asked for
+        let pixels = if self.keep_pixels {
+            data.pages.drain().map(|(file, page)| (file, page.image)).collect()
+        } else {
+            HashMap::new()
+        };
+        let atlas = Atlas {
+            name: name.clone(),
+            sprites,
+            textures,
+            data_files: data.data_files,
+            dir: data.dir,
+            page_sizes,
+            pixels,
+        };
+        self.atlases.insert(name, atlas);
+    }
 
-        Ok(())
+    /// Drop an atlas and everything pointing at its textures. Dropping the handles frees the
+    /// textures in egui.
+    fn unload_atlas(&mut self, name: &str) {
+        let Some(old) = self.atlases.remove(name) else { return };
+        self.texture_id_to_atlas.retain(|_, atlas| atlas != name);
+        GLOBAL_SPRITE_MAP.lock().unwrap().retain(|_, (atlas, _)| atlas != name);
+        debug!("Freed {} textures of atlas '{}' before reloading it", old.textures.len(), name);
     }
 
     /// Load a .meta file and parse its contents
-    fn load_meta_file(&self, meta_path: &Path, atlas: &mut Atlas, atlas_dir: &Path, ctx: &egui::Context) -> io::Result<()> {
+    fn load_meta_file(meta_path: &Path, data: &mut AtlasData, atlas_dir: &Path) -> io::Result<()> {
         let mut file = File::open(meta_path)?;
 
         // Split into smaller functions for clarity
-        self.read_meta_header(&mut file)?;
-        self.read_atlas_data(&mut file, atlas, atlas_dir, ctx)
+        Self::read_meta_header(&mut file)?;
+        Self::read_atlas_data(&mut file, data, atlas_dir)
     }
 
     /// Read the meta file header
-    fn read_meta_header(&self, file: &mut File) -> io::Result<()> {
+    fn read_meta_header(file: &mut File) -> io::Result<()> {
         // Skip header (4 bytes signature + variable-length string + 4 bytes value)
         let _ = file.read_i32::<LittleEndian>()?;
-        self.read_string(file)?;
+        Self::read_string(file)?;
         let _ = file.read_i32::<LittleEndian>()?;
         Ok(())
     }
 
     /// Read the actual atlas data
-    fn read_atlas_data(&self, file: &mut File, atlas: &mut Atlas, atlas_dir: &Path, ctx: &egui::Context) -> io::Result<()> {
+    fn read_atlas_data(file: &mut File, data: &mut AtlasData, atlas_dir: &Path) -> io::Result<()> {
         // Read count of data files
         let count = file.read_i16::<LittleEndian>()?;
 
         // Read each data file
         for _ in 0..count {
-            let data_file = self.read_string(file)?;
-            atlas.data_files.push(data_file.clone());
+            let data_file = Self::read_string(file)?;
+            data.data_files.push(data_file.clone());
 
             let sprites_count = file.read_i16::<LittleEndian>()?;
 
             let data_path = atlas_dir.join(format!("{}.data", data_file));
-            let image = self.load_data_file(&data_path)?;
-
-            // Store the raw image for later sprite extraction
-            atlas.images.insert(data_file.clone(), image.clone());
-
-            // Create texture and add to atlas
-            let texture_name = format!("{}_{}", atlas.name, data_file);
-            let texture_handle = self.add_image_to_egui(ctx, &image, &texture_name);
-            let texture_id = texture_handle.id();
-            atlas.textures.insert(data_file.clone(), texture_handle);
-
-            // Size needed for UV calculations
-            let atlas_width = image.width() as f32;
-            let atlas_height = image.height() as f32;
+            // A missing or broken page only loses its own sprites; the sprite list is still read
+            // so the following pages line up
+            match Self::load_data_file(&data_path) {
+                Ok(image) => {
+                    let color_image = Self::to_color_image(&image);
+                    data.pages.insert(data_file.clone(), AtlasPage { image, color_image: Some(color_image) });
+                }
+                Err(e) => {
+                    warn!("Skipping atlas page {}: {}", data_path.display(), e);
+                    data.missing_pages.push(data_path);
+                }
+            }
 
             // Read each sprite in the data file
             for _ in 0..sprites_count {
-                let path = self.read_string(file)?;
+                let path = Self::read_string(file)?;
                 let path = path.replace("\\", "/");
 
                 let metadata = SpriteMetadata {
@@ -193,25 +306,7 @@ impl AtlasManager {
                     real_height: file.read_i16::<LittleEndian>()?,
                 };
 
-                // Pre-compute UV coordinates
-                let uv_min = egui::pos2(
-                    metadata.x as f32 / atlas_width,
-                    metadata.y as f32 / atlas_height,
-                );
-                let uv_max = egui::pos2(
-                    (metadata.x as f32 + metadata.width as f32) / atlas_width,
-                    (metadata.y as f32 + metadata.height as f32) / atlas_height,
-                );
-                let uv_rect = egui::Rect::from_min_max(uv_min, uv_max);
-
-                let sprite = Sprite {
-                    metadata,
-                    texture_id,
-                    data_file: data_file.clone(),
-                    uv_rect: Some(uv_rect),
-                };
-
-                atlas.sprites.insert(path, sprite);
+                data.sprites.push((path, data_file.clone(), metadata));
             }
         }
 
@@ -219,7 +314,7 @@ impl AtlasManager {
     }
 
     /// Read a variable-length string from a binary file
-    fn read_string<R: Read>(&self, reader: &mut R) -> io::Result<String> {
+    fn read_string<R: Read>(reader: &mut R) -> io::Result<String> {
         let length = reader.read_u8()? as usize;
         let mut buffer = vec![0u8; length];
         reader.read_exact(&mut buffer)?;
@@ -229,7 +324,7 @@ impl AtlasManager {
     }
 
     /// Load a Celeste .data file which contains a run-length encoded image
-    pub fn load_data_file(&self, data_path: &Path) -> io::Result<RgbaImage> {
+    pub fn load_data_file(data_path: &Path) -> io::Result<RgbaImage> {
         use std::io::Read;
         debug!("Attempting to open .data file: {}", data_path.display());
         let mut file = File::open(data_path)?;
@@ -288,17 +383,15 @@ impl AtlasManager {
         Ok(image)
     }
 
-    /// Convert RgbaImage to egui texture
-    fn add_image_to_egui(&self, ctx: &egui::Context, image: &RgbaImage, name: &str) -> egui::TextureHandle {
+    /// Convert RgbaImage to the pixel format egui uploads
+    fn to_color_image(image: &RgbaImage) -> egui::ColorImage {
         let size = [image.width() as usize, image.height() as usize];
         let pixels = image.as_flat_samples();
 
-        let color_image = egui::ColorImage::from_rgba_unmultiplied(
+        egui::ColorImage::from_rgba_unmultiplied(
             size,
             pixels.as_slice()
-        );
-
-        ctx.load_texture(name, color_image, egui::TextureFilter::Nearest)
+        )
     }
 
     /// Get a sprite by path from a specific atlas
@@ -316,19 +409,95 @@ impl AtlasManager {
         }
     }
 
-    /// Get the raw image data from an atlas
-    pub fn get_atlas_image(&self, atlas_name: &str, data_file: &str) -> Option<&RgbaImage> {
-        debug!("get_atlas_image('{}', '{}')", atlas_name, data_file);
-        self.atlases.get(atlas_name)?.images.get(data_file)
+    /// Keep the sprites of `uses`, the paths the map draws from `atlas_name` and how many times,
+    /// that the atlas lacks. Replaces what was noted for that atlas before.
+    pub fn note_missing_sprites(&mut self, atlas_name: &str, mut uses: HashMap<String, u32>) {
+        let atlas = self.atlases.get(atlas_name);
+        uses.retain(|path, _| atlas.and_then(|a| a.get_sprite(path)).is_none());
+        self.misses.insert(atlas_name.to_string(), uses);
+    }
+
+    /// Sprites the map uses but the atlases lack: atlas, path and how many times, by path.
+    pub fn missing_sprites(&self) -> Vec<(String, String, u32)> {
+        let mut list: Vec<(String, String, u32)> = self.misses.iter()
+            .flat_map(|(atlas, paths)| paths.iter().map(move |(path, count)| (atlas.clone(), path.clone(), *count)))
+            .collect();
+        list.sort_by(|a, b| a.1.cmp(&b.1));
+        list
+    }
+
+    /// Whether the named atlas has been loaded.
+    pub fn has_atlas(&self, atlas_name: &str) -> bool {
+        self.atlases.contains_key(atlas_name)
+    }
+
+    /// Find a sprite in any loaded atlas, Gameplay first.
+    pub fn get_sprite_any(&self, sprite_path: &str) -> Option<&Sprite> {
+        self.atlases.get("Gameplay")
+            .and_then(|atlas| atlas.get_sprite(sprite_path))
+            .or_else(|| self.atlases.values().find_map(|atlas| atlas.get_sprite(sprite_path)))
+    }
+
+    /// Run `f` on the raw image data of an atlas page. Unless the pixels were kept, the page is
+    /// decoded again from its .data file and dropped afterwards.
+    pub fn with_atlas_image<R>(&self, atlas_name: &str, data_file: &str, f: impl FnOnce(&RgbaImage) -> R) -> Option<R> {
+        debug!("with_atlas_image('{}', '{}')", atlas_name, data_file);
+        let atlas = self.atlases.get(atlas_name)?;
+        if let Some(image) = atlas.pixels.get(data_file) {
+            return Some(f(image));
+        }
+        if !atlas.page_sizes.contains_key(data_file) {
+            return None;
+        }
+        match Self::load_data_file(&atlas.dir.join(format!("{}.data", data_file))) {
+            Ok(image) => Some(f(&image)),
+            Err(e) => {
+                warn!("Failed to decode atlas page '{}': {}", data_file, e);
+                None
+            }
+        }
+    }
+
+    /// Approximate memory held by the loaded atlases, in bytes: GPU textures and CPU page copies.
+    pub fn memory_usage(&self) -> (usize, usize) {
+        let textures = self.atlases.values()
+            .flat_map(|a| a.page_sizes.values())
+            .map(|[w, h]| w * h * 4)
+            .sum();
+        let pixels = self.atlases.values()
+            .flat_map(|a| a.pixels.values())
+            .map(|image| image.as_raw().len())
+            .sum();
+        (textures, pixels)
+    }
+
+    /// Write a sprite's region of its atlas page to a PNG (or any format `image` infers from the extension).
+    pub fn export_sprite(&self, atlas_name: &str, sprite_path: &str, out_path: &Path) -> Result<(), String> {
+        let sprite = self.get_sprite(atlas_name, sprite_path)
+            .ok_or_else(|| format!("Sprite '{}' not found in atlas '{}'", sprite_path, atlas_name))?;
+        let m = &sprite.metadata;
+        let cropped = self.with_atlas_image(atlas_name, &sprite.data_file, |image| {
+            image::imageops::crop_imm(image, m.x.max(0) as u32, m.y.max(0) as u32, m.width.max(0) as u32, m.height.max(0) as u32).to_image()
+        }).ok_or_else(|| format!("Atlas page '{}' is not loaded", sprite.data_file))?;
+        cropped
+            .save(out_path)
+            .map_err(|e| format!("Failed to write {}: {}", out_path.display(), e))
     }
 
-    /// Draw a sprite to the screen
-    pub fn draw_sprite(&self, sprite: &Sprite, painter: &egui::Painter, rect: egui::Rect, tint: egui::Color32) {
+    /// Swap the UV edges so the sprite is mirrored horizontally and/or vertically
+    fn flip_uv(uv: egui::Rect, flip_x: bool, flip_y: bool) -> egui::Rect {
+        let (min_x, max_x) = if flip_x { (uv.max.x, uv.min.x) } else { (uv.min.x, uv.max.x) };
+        let (min_y, max_y) = if flip_y { (uv.max.y, uv.min.y) } else { (uv.min.y, uv.max.y) };
+        egui::Rect::from_min_max(egui::pos2(min_x, min_y), egui::pos2(max_x, max_y))
+    }
+
+    /// Draw a sprite to the screen, optionally mirrored (e.g. decals with negative scale)
+    pub fn draw_sprite(&self, sprite: &Sprite, painter: &egui::Painter, rect: egui::Rect, tint: egui::Color32, flip_x: bool, flip_y: bool) {
         // Use the pre-computed UV coordinates if available
         if let Some(uv_rect) = &sprite.uv_rect {
             // Create mesh for the sprite
             let mut mesh = egui::epaint::Mesh::with_texture(sprite.texture_id);
-            mesh.add_rect_with_uv(rect, *uv_rect, tint);
+            mesh.add_rect_with_uv(rect, Self::flip_uv(*uv_rect, flip_x, flip_y), tint);
             painter.add(egui::epaint::Shape::mesh(mesh));
             return;
         }
@@ -362,7 +531,7 @@ impl AtlasManager {
             (sprite_y + sprite.metadata.height as f32) / atlas_height,
         );
 
-        let uv_rect = egui::Rect::from_min_max(uv_min, uv_max);
+        let uv_rect = Self::flip_uv(egui::Rect::from_min_max(uv_min, uv_max), flip_x, flip_y);
 
         // Create mesh for the sprite
         let mut mesh = egui::epaint::Mesh::with_texture(sprite.texture_id);
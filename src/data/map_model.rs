@@ -0,0 +1,421 @@
+use log::warn;
+use serde::{Serialize, Deserialize};
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+use std::ops::{Add, Sub};
+
+/// Celeste pixels per tile.
+pub const PIXELS_PER_TILE: f64 = 8.0;
+/// Room children whose elements carry entity ids.
+pub const ID_LAYERS: [&str; 2] = ["entities", "triggers"];
+
+/// A position or length in Celeste pixels, the unit rooms, entities and decals use in map files.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct CelestePixels(pub f64);
+
+/// A position or length in whole tiles.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TileCoord(pub i32);
+
+impl CelestePixels {
+    /// Read a numeric attribute, treating missing or non-numeric values as 0.
+    pub fn attr(value: &Value) -> Self {
+        CelestePixels(value.as_f64().unwrap_or(0.0))
+    }
+
+    /// The tile containing this pixel.
+    pub fn to_tiles(self) -> TileCoord {
+        TileCoord((self.0 / PIXELS_PER_TILE).floor() as i32)
+    }
+}
+
+impl TileCoord {
+    pub fn to_pixels(self) -> CelestePixels {
+        CelestePixels(self.0 as f64 * PIXELS_PER_TILE)
+    }
+}
+
+impl Add for CelestePixels {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self { CelestePixels(self.0 + rhs.0) }
+}
+
+impl Sub for CelestePixels {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self { CelestePixels(self.0 - rhs.0) }
+}
+
+impl Add for TileCoord {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self { TileCoord(self.0 + rhs.0) }
+}
+
+impl Sub for TileCoord {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self { TileCoord(self.0 - rhs.0) }
+}
+
+/// Room size in tiles, from a level's `width`/`height` attributes.
+pub fn level_size_tiles(level: &Value) -> (TileCoord, TileCoord) {
+    (CelestePixels::attr(&level["width"]).to_tiles(), CelestePixels::attr(&level["height"]).to_tiles())
+}
+
+/// Typed view over a map decoded by Cairn.
+/// Only the parts the editor touches are typed; everything else lives in `extra`
+/// or `root` so a parse / write round trip leaves the map untouched.
+#[derive(Clone, Debug)]
+pub struct MapData {
+    pub levels: Vec<LevelNode>,
+    /// Levels that could not be read, with their index in the map's level list. They are written
+    /// back untouched.
+    pub unreadable: Vec<(usize, Value)>,
+    /// The map root with the level list emptied out, kept verbatim.
+    root: Value,
+}
+
+impl MapData {
+    pub fn package(&self) -> Option<&str> {
+        self.root["package"].as_str()
+    }
+
+    /// Names of the elements of the level list in map order, so name i belongs to level i. An
+    /// unreadable level keeps its place, named by its raw `name` attribute.
+    pub fn level_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.levels.iter().map(|l| l.name.clone()).collect();
+        for (idx, level) in &self.unreadable {
+            let name = match &level["name"] {
+                Value::String(s) => s.clone(),
+                Value::Null => String::new(),
+                other => other.to_string(),
+            };
+            names.insert((*idx).min(names.len()), name);
+        }
+        names
+    }
+
+    /// One past the highest entity or trigger id in any room, unreadable ones included.
+    pub fn next_entity_id(&self) -> u32 {
+        let readable = self.levels.iter().filter_map(LevelNode::max_entity_id);
+        let unreadable = self.unreadable.iter().flat_map(|(_, level)| level_entity_ids(level));
+        readable.chain(unreadable).max().map_or(0, |m| (m + 1).max(0) as u32)
+    }
+
+    /// Position in `levels` of level `room` of the map, the editor's room index. None if that
+    /// level is unreadable or there is no such level.
+    fn readable_index(&self, room: usize) -> Option<usize> {
+        let skipped = self.unreadable.iter().take_while(|(idx, _)| *idx < room).count();
+        if self.unreadable.get(skipped).map_or(false, |(idx, _)| *idx == room) { return None; }
+        Some(room - skipped)
+    }
+
+    /// Room `room` of the map, counted like the editor counts rooms.
+    pub fn level(&self, room: usize) -> Option<&LevelNode> {
+        self.levels.get(self.readable_index(room)?)
+    }
+
+    pub fn level_mut(&mut self, room: usize) -> Option<&mut LevelNode> {
+        let idx = self.readable_index(room)?;
+        self.levels.get_mut(idx)
+    }
+
+    /// Add `entity` to the `layer` element (`entities` or `triggers`) of room `room` under an id no
+    /// other entity or trigger of the map uses. Returns the id, or None if the room is unreadable
+    /// or missing.
+    pub fn add_entity(&mut self, room: usize, layer: &str, entity: Entity) -> Option<u32> {
+        self.add_element(room, layer, entity.to_value())
+    }
+
+    /// `add_entity` for an element already in Cairn form, such as a pasted object. Its `id` is replaced.
+    pub fn add_element(&mut self, room: usize, layer: &str, mut element: Value) -> Option<u32> {
+        let id = self.next_entity_id();
+        element["id"] = Value::from(id);
+        self.level_mut(room)?.push_child(layer, element);
+        Some(id)
+    }
+}
+
+/// An attribute value of an entity, in the types Cairn writes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttributeValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl AttributeValue {
+    pub fn to_value(&self) -> Value {
+        match self {
+            AttributeValue::Bool(b) => Value::from(*b),
+            AttributeValue::Int(i) => Value::from(*i),
+            AttributeValue::Float(f) => Value::from(*f),
+            AttributeValue::String(s) => Value::from(s.as_str()),
+        }
+    }
+}
+
+impl From<bool> for AttributeValue {
+    fn from(b: bool) -> Self { AttributeValue::Bool(b) }
+}
+
+impl From<i64> for AttributeValue {
+    fn from(i: i64) -> Self { AttributeValue::Int(i) }
+}
+
+impl From<f64> for AttributeValue {
+    fn from(f: f64) -> Self { AttributeValue::Float(f) }
+}
+
+impl From<&str> for AttributeValue {
+    fn from(s: &str) -> Self { AttributeValue::String(s.to_string()) }
+}
+
+impl From<String> for AttributeValue {
+    fn from(s: String) -> Self { AttributeValue::String(s) }
+}
+
+/// An entity to add to a room: `Entity::new("strawberry", 16.0, 32.0).with_attribute("winged", true)`.
+#[derive(Clone, Debug)]
+pub struct Entity {
+    pub name: String,
+    /// Replaced by `MapData::add_entity` with one free across the map.
+    pub id: u32,
+    /// Position relative to the room, in Celeste pixels.
+    pub x: f64,
+    pub y: f64,
+    pub attributes: HashMap<String, AttributeValue>,
+}
+
+impl Entity {
+    pub fn new(name: impl Into<String>, x: f64, y: f64) -> Self {
+        Self { name: name.into(), id: 0, x, y, attributes: HashMap::new() }
+    }
+
+    pub fn with_attribute(mut self, key: impl Into<String>, value: impl Into<AttributeValue>) -> Self {
+        self.attributes.insert(key.into(), value.into());
+        self
+    }
+
+    /// The entity as a Cairn element. Whole-pixel positions are written as integers, the type the game uses.
+    pub fn to_value(&self) -> Value {
+        let pixels = |v: f64| if v.fract() == 0.0 { Value::from(v as i64) } else { Value::from(v) };
+        let mut element: Map<String, Value> = self.attributes.iter().map(|(k, v)| (k.clone(), v.to_value())).collect();
+        element.insert("__name".to_string(), Value::from(self.name.as_str()));
+        element.insert("id".to_string(), Value::from(self.id));
+        element.insert("x".to_string(), pixels(self.x));
+        element.insert("y".to_string(), pixels(self.y));
+        Value::Object(element)
+    }
+}
+
+/// A `level` element: one room.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LevelNode {
+    #[serde(rename = "__name", default)]
+    pub kind: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default = "zero")]
+    pub x: Number,
+    #[serde(default = "zero")]
+    pub y: Number,
+    #[serde(default = "zero")]
+    pub width: Number,
+    #[serde(default = "zero")]
+    pub height: Number,
+    #[serde(rename = "__children", default)]
+    pub children: Vec<LevelChild>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A child of a level. Tile grids are typed, anything else (entities, decals, triggers) is kept as JSON.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LevelChild {
+    Tiles(SolidsNode),
+    Other(Value),
+}
+
+/// A tile grid element (`solids`, `bg`, `objtiles`...) holding its tiles as text rows.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SolidsNode {
+    #[serde(rename = "__name")]
+    pub name: String,
+    #[serde(rename = "innerText")]
+    pub inner_text: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl SolidsNode {
+    /// The tile grid `name` of a Cairn level element, read on its own without parsing the rest of
+    /// the level. A grid element without text reads as empty.
+    pub fn of_level(level: &Value, name: &str) -> Option<SolidsNode> {
+        let child = level["__children"].as_array()?.iter().find(|c| c["__name"] == name)?;
+        let extra = child.as_object()?.iter()
+            .filter(|(k, _)| *k != "__name" && *k != "innerText")
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        let inner_text = child["innerText"].as_str().unwrap_or_default().to_string();
+        Some(SolidsNode { name: name.to_string(), inner_text, extra })
+    }
+
+    /// Write this grid over the level's element of the same name, leaving the other children as
+    /// they are. Returns false if the level has no such element.
+    pub fn store_in(&self, level: &mut Value) -> bool {
+        let child = level.get_mut("__children").and_then(Value::as_array_mut)
+            .and_then(|children| children.iter_mut().find(|c| c["__name"] == self.name.as_str()));
+        match (child, serde_json::to_value(self)) {
+            (Some(child), Ok(value)) => {
+                *child = value;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn zero() -> Number {
+    Number::from(0)
+}
+
+impl LevelNode {
+    pub fn from_value(level: &Value) -> Option<LevelNode> {
+        serde_json::from_value(level.clone()).ok()
+    }
+
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    /// Returns (x, y, width, height).
+    pub fn bounds(&self) -> (CelestePixels, CelestePixels, CelestePixels, CelestePixels) {
+        let f = |n: &Number| CelestePixels(n.as_f64().unwrap_or(0.0));
+        (f(&self.x), f(&self.y), f(&self.width), f(&self.height))
+    }
+
+    pub fn tiles(&self, name: &str) -> Option<&SolidsNode> {
+        self.children.iter().find_map(|c| match c {
+            LevelChild::Tiles(t) if t.name == name => Some(t),
+            _ => None,
+        })
+    }
+
+    pub fn solids(&self) -> Option<&SolidsNode> {
+        self.tiles("solids")
+    }
+
+    /// Highest entity or trigger id in this room.
+    pub fn max_entity_id(&self) -> Option<i64> {
+        self.children.iter()
+            .filter_map(|c| match c {
+                LevelChild::Other(v) => Some(v),
+                _ => None,
+            })
+            .flat_map(child_entity_ids)
+            .max()
+    }
+
+    /// Append `element` as it is, id included, to the room's `layer` element, creating it if missing.
+    /// Use `MapData::add_entity` to give an entity or trigger a free id.
+    pub fn push_child(&mut self, layer: &str, element: Value) {
+        for child in &mut self.children {
+            if let LevelChild::Other(v) = child {
+                if v["__name"] == layer {
+                    if !v["__children"].is_array() {
+                        v["__children"] = Value::Array(Vec::new());
+                    }
+                    v["__children"].as_array_mut().unwrap().push(element);
+                    return;
+                }
+            }
+        }
+        self.children.push(LevelChild::Other(serde_json::json!({ "__name": layer, "__children": [element] })));
+    }
+
+    /// Replace the text of the named tile grid. A grid element without text is upgraded in place.
+    /// Returns false if the level has no such element.
+    pub fn set_tiles(&mut self, name: &str, text: &str) -> bool {
+        for child in &mut self.children {
+            match child {
+                LevelChild::Tiles(t) if t.name == name => {
+                    t.inner_text = text.to_string();
+                    return true;
+                }
+                LevelChild::Other(v) if v["__name"] == name => {
+                    let extra = v.as_object().cloned().unwrap_or_default().into_iter()
+                        .filter(|(k, _)| k != "__name" && k != "innerText")
+                        .collect();
+                    *child = LevelChild::Tiles(SolidsNode { name: name.to_string(), inner_text: text.to_string(), extra });
+                    return true;
+                }
+                _ => {}
+            }
+        }
+        false
+    }
+}
+
+/// The `__children` list of the map's `levels` element.
+pub fn levels(map: &Value) -> Option<&Vec<Value>> {
+    map["__children"].as_array()?
+        .iter()
+        .find(|c| c["__name"] == "levels")?
+        ["__children"].as_array()
+}
+
+pub fn levels_mut(map: &mut Value) -> Option<&mut Vec<Value>> {
+    map["__children"].as_array_mut()?
+        .iter_mut()
+        .find(|c| c["__name"] == "levels")?
+        ["__children"].as_array_mut()
+}
+
+/// Ids of the elements of a level child, if it is one of `ID_LAYERS`.
+fn child_entity_ids(child: &Value) -> impl Iterator<Item = i64> + '_ {
+    let ids = child["__name"].as_str().map_or(false, |n| ID_LAYERS.contains(&n));
+    child["__children"].as_array().filter(|_| ids).into_iter().flatten().filter_map(|e| e["id"].as_i64())
+}
+
+/// Ids of the entities and triggers of a level element.
+pub fn level_entity_ids(level: &Value) -> impl Iterator<Item = i64> + '_ {
+    level["__children"].as_array().into_iter().flatten().flat_map(child_entity_ids)
+}
+
+/// One past the highest entity or trigger id in any room of the Cairn JSON `map`, from which a
+/// copied room renumbers ids that clash. `MapData::next_entity_id` is the same on the model.
+pub fn next_entity_id(map: &Value) -> i64 {
+    levels(map).into_iter().flatten().flat_map(level_entity_ids).max().map_or(0, |m| (m + 1).max(0))
+}
+
+/// Parse a Cairn JSON map into the typed model. Returns None if the map has no `levels` element.
+/// A level that cannot be read is left out of `levels` and kept in `unreadable`.
+pub fn parse_map(json: &Value) -> Option<MapData> {
+    let mut levels = Vec::new();
+    let mut unreadable = Vec::new();
+    for (idx, level) in self::levels(json)?.iter().enumerate() {
+        match LevelNode::from_value(level) {
+            Some(node) => levels.push(node),
+            None => {
+                warn!("Level {} could not be read, it is kept as is", idx);
+                unreadable.push((idx, level.clone()));
+            }
+        }
+    }
+    let mut root = json.clone();
+    levels_mut(&mut root)?.clear();
+    Some(MapData { levels, unreadable, root })
+}
+
+/// Turn the typed model back into Cairn JSON.
+pub fn write_map(map: &MapData) -> Value {
+    let mut root = map.root.clone();
+    if let Some(levels) = levels_mut(&mut root) {
+        *levels = map.levels.iter().map(LevelNode::to_value).collect();
+        for (idx, level) in &map.unreadable {
+            levels.insert((*idx).min(levels.len()), level.clone());
+        }
+    }
+    root
+}
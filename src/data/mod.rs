@@ -1,5 +1,9 @@
 pub mod assets;
 pub mod binary_reader;
 pub mod tile_xml;
+pub mod trigger_catalogue;
 pub mod xnb_reader;
 pub mod celeste_atlas;
+pub mod map_model;
+pub mod mods;
+pub mod stylegrounds;
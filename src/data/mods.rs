@@ -0,0 +1,145 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use log::debug;
+
+/// A map of an Everest mod, found under its `Maps` folder.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModMap {
+    /// Folders between `Maps/` and the file, e.g. "Author/Campaign"; empty for a map right in `Maps/`.
+    pub campaign: String,
+    /// File name without `.bin`.
+    pub name: String,
+    /// The .bin, or the zip holding it.
+    pub path: PathBuf,
+    /// Name of the .bin inside the zip, for a zipped mod.
+    pub zip_entry: Option<String>,
+}
+
+/// A mod folder or zip holding at least one map.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModProject {
+    pub name: String,
+    pub dir: PathBuf,
+    /// Packed as a zip; its maps open read-only.
+    pub zipped: bool,
+    /// Sorted by campaign, then name.
+    pub maps: Vec<ModMap>,
+}
+
+impl ModProject {
+    /// The maps grouped by campaign, in order.
+    pub fn campaigns(&self) -> Vec<(&str, Vec<&ModMap>)> {
+        let mut campaigns: Vec<(&str, Vec<&ModMap>)> = Vec::new();
+        for map in &self.maps {
+            match campaigns.last_mut() {
+                Some((campaign, maps)) if *campaign == map.campaign => maps.push(map),
+                _ => campaigns.push((&map.campaign, vec![map])),
+            }
+        }
+        campaigns
+    }
+}
+
+fn is_ext(path: &Path, ext: &str) -> bool {
+    path.extension().map_or(false, |e| e.eq_ignore_ascii_case(ext))
+}
+
+/// Every `.bin` below `dir`, with the folders between `root` and it.
+fn collect_maps(root: &Path, dir: &Path, maps: &mut Vec<ModMap>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_maps(root, &path, maps);
+            continue;
+        }
+        let Some(name) = path.file_stem().filter(|_| is_ext(&path, "bin")).map(|s| s.to_string_lossy().to_string()) else { continue };
+        let campaign = path.parent()
+            .and_then(|p| p.strip_prefix(root).ok())
+            .map(|p| p.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect::<Vec<_>>().join("/"))
+            .unwrap_or_default();
+        maps.push(ModMap { campaign, name, path, zip_entry: None });
+    }
+}
+
+/// Every `.bin` under `Maps/` in the zip at `path`. Only the zip's directory is read.
+fn zipped_maps(path: &Path) -> Vec<ModMap> {
+    let archive = File::open(path).map_err(|e| e.to_string())
+        .and_then(|f| zip::ZipArchive::new(BufReader::new(f)).map_err(|e| e.to_string()));
+    let archive = match archive {
+        Ok(archive) => archive,
+        Err(e) => {
+            debug!("Could not read {}: {}", path.display(), e);
+            return Vec::new();
+        }
+    };
+    archive.file_names()
+        .filter_map(|entry| {
+            let file = Path::new(entry.strip_prefix("Maps/")?);
+            if !is_ext(file, "bin") { return None; }
+            Some(ModMap {
+                campaign: file.parent().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
+                name: file.file_stem()?.to_string_lossy().to_string(),
+                path: path.to_path_buf(),
+                zip_entry: Some(entry.to_string()),
+            })
+        })
+        .collect()
+}
+
+/// Mod folders and zips in `mods_dir` with maps under `Maps/`, sorted by name ignoring case.
+pub fn scan_mods(mods_dir: &Path) -> Vec<ModProject> {
+    let Ok(entries) = std::fs::read_dir(mods_dir) else {
+        debug!("No mods folder at {}", mods_dir.display());
+        return Vec::new();
+    };
+    let mut projects: Vec<ModProject> = entries.flatten()
+        .map(|e| e.path())
+        .filter_map(|dir| {
+            let zipped = dir.is_file() && is_ext(&dir, "zip");
+            let mut maps = if zipped {
+                zipped_maps(&dir)
+            } else if dir.is_dir() {
+                let maps_dir = dir.join("Maps");
+                let mut maps = Vec::new();
+                collect_maps(&maps_dir, &maps_dir, &mut maps);
+                maps
+            } else {
+                return None;
+            };
+            if maps.is_empty() { return None; }
+            maps.sort_by(|a, b| (&a.campaign, &a.name).cmp(&(&b.campaign, &b.name)));
+            let name = if zipped { dir.file_stem()? } else { dir.file_name()? }.to_string_lossy().to_string();
+            Some(ModProject { name, dir, zipped, maps })
+        })
+        .collect();
+    projects.sort_by_key(|p| p.name.to_lowercase());
+    debug!("Found {} mods with maps in {}", projects.len(), mods_dir.display());
+    projects
+}
+
+/// The mod folder under `mods_dir` a map at `path` belongs to, if it sits in that mod's `Maps`.
+pub fn mod_of_map(mods_dir: &Path, path: &Path) -> Option<String> {
+    let mut components = path.strip_prefix(mods_dir).ok()?.components();
+    let mod_name = components.next()?.as_os_str().to_string_lossy().to_string();
+    (components.next()?.as_os_str() == "Maps").then_some(mod_name)
+}
+
+/// Copy a zipped map out to the temp dir, where Cairn can read it. Returns the copy's path.
+pub fn extract_zipped_map(map: &ModMap) -> Result<PathBuf, String> {
+    let entry = map.zip_entry.as_deref().ok_or("The map is not in a zip")?;
+    let file = File::open(&map.path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+    let mut zipped = archive.by_name(entry).map_err(|e| e.to_string())?;
+    let relative = zipped.enclosed_name().ok_or_else(|| format!("Unsafe path in zip: {}", entry))?.to_path_buf();
+    let zip_name = map.path.file_stem().unwrap_or_default();
+    let target = std::env::temp_dir().join("summit_zipped_maps").join(zip_name).join(relative);
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut out = File::create(&target).map_err(|e| e.to_string())?;
+    std::io::copy(&mut zipped, &mut out).map_err(|e| e.to_string())?;
+    Ok(target)
+}
@@ -0,0 +1,107 @@
+use eframe::egui::Color32;
+use serde_json::Value;
+
+/// A `parallax` background from the map's `Style` element.
+#[derive(Clone, Debug)]
+pub struct Parallax {
+    pub texture: String,
+    pub x: f32,
+    pub y: f32,
+    pub scroll_x: f32,
+    pub scroll_y: f32,
+    /// Color with `alpha` already applied.
+    pub color: Color32,
+    pub loop_x: bool,
+    pub loop_y: bool,
+    only: String,
+    exclude: String,
+}
+
+/// Parse a `RRGGBB` hex color, as stylegrounds store it. Invalid strings read as white.
+fn parse_hex_color(hex: &str) -> Color32 {
+    let hex = hex.trim_start_matches('#');
+    let channel = |i: usize| hex.get(i..i + 2).and_then(|c| u8::from_str_radix(c, 16).ok());
+    match (channel(0), channel(2), channel(4)) {
+        (Some(r), Some(g), Some(b)) => Color32::from_rgb(r, g, b),
+        _ => Color32::WHITE,
+    }
+}
+
+/// Celeste room filters: comma-separated names where `*` matches any run of characters.
+fn matches_room_filter(filter: &str, room: &str) -> bool {
+    filter.split(',').map(str::trim).filter(|p| !p.is_empty()).any(|pattern| {
+        let mut parts = pattern.split('*');
+        let first = parts.next().unwrap_or("");
+        let Some(mut rest) = room.strip_prefix(first) else { return false };
+        let parts: Vec<&str> = parts.collect();
+        if parts.is_empty() {
+            return rest.is_empty();
+        }
+        for (i, part) in parts.iter().enumerate() {
+            if i == parts.len() - 1 {
+                return rest.ends_with(part);
+            }
+            match rest.find(part) {
+                Some(at) => rest = &rest[at + part.len()..],
+                None => return false,
+            }
+        }
+        true
+    })
+}
+
+impl Parallax {
+    /// Read a parallax element; attributes it lacks fall back to its `apply` group.
+    fn from_element(el: &Value, group: &Value) -> Option<Parallax> {
+        let attr = |key: &str| if el[key].is_null() { &group[key] } else { &el[key] };
+        let num = |key: &str, default: f64| attr(key).as_f64().unwrap_or(default) as f32;
+        let flag = |key: &str| attr(key).as_bool().unwrap_or(true);
+        let text = |key: &str| attr(key).as_str().unwrap_or("").to_string();
+
+        let texture = text("texture");
+        if texture.is_empty() { return None; }
+        let alpha = num("alpha", 1.0).clamp(0.0, 1.0);
+        let color = parse_hex_color(attr("color").as_str().unwrap_or("FFFFFF"));
+        Some(Parallax {
+            texture,
+            x: num("x", 0.0),
+            y: num("y", 0.0),
+            scroll_x: num("scrollx", 0.0),
+            scroll_y: num("scrolly", 0.0),
+            color: Color32::from_rgba_unmultiplied(color.r(), color.g(), color.b(), (alpha * 255.0) as u8),
+            loop_x: flag("loopx"),
+            loop_y: flag("loopy"),
+            only: attr("only").as_str().unwrap_or("*").to_string(),
+            exclude: text("exclude"),
+        })
+    }
+
+    /// Whether the `only` / `exclude` filters show this layer in room `name`.
+    pub fn applies_to(&self, name: &str) -> bool {
+        matches_room_filter(&self.only, name) && !matches_room_filter(&self.exclude, name)
+    }
+}
+
+fn is(el: &Value, name: &str) -> bool {
+    el["__name"].as_str().map_or(false, |n| n.eq_ignore_ascii_case(name))
+}
+
+/// Parallax backgrounds of a map, back to front. Effects (snow, stars...) are skipped.
+pub fn parse_parallax(map: &Value) -> Vec<Parallax> {
+    let backgrounds = map["__children"].as_array().into_iter().flatten()
+        .filter(|c| is(c, "Style"))
+        .flat_map(|c| c["__children"].as_array().into_iter().flatten())
+        .filter(|c| is(c, "Backgrounds"))
+        .flat_map(|c| c["__children"].as_array().into_iter().flatten());
+    let mut layers = Vec::new();
+    for el in backgrounds {
+        if is(el, "parallax") {
+            layers.extend(Parallax::from_element(el, &Value::Null));
+        } else if is(el, "apply") {
+            for child in el["__children"].as_array().into_iter().flatten().filter(|c| is(c, "parallax")) {
+                layers.extend(Parallax::from_element(child, el));
+            }
+        }
+    }
+    layers
+}
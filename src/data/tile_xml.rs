@@ -5,8 +5,9 @@ use once_cell::sync::OnceCell;
 use quick_xml::events::Event;
 use quick_xml::Reader;
 use crate::app::CelesteMapEditor;
+use crate::data::assets::CelesteAssets;
 use log::debug;
-use std::path::PathBuf;
+use std::path::Path;
 
 /// Loads a mapping from tile id (char) to tileset path from a ForegroundTiles.xml or BackgroundTiles.xml file.
 pub fn load_tileset_id_path_map(xml_path: &str) -> HashMap<char, String> {
@@ -81,81 +82,44 @@ pub fn get_tileset_path_for_id(map: &HashMap<char, String>, id: char) -> Option<
 pub static TILESET_ID_PATH_MAP_FG: OnceCell<HashMap<char, String>> = OnceCell::new();
 pub static TILESET_ID_PATH_MAP_BG: OnceCell<HashMap<char, String>> = OnceCell::new();
 
-/// Ensures the tileset id/path maps are loaded for both foreground and background, using the Celeste install path.
-pub fn ensure_tileset_id_path_map_loaded_from_celeste(editor: &CelesteMapEditor) {
-    // Load foreground tileset map
-    if TILESET_ID_PATH_MAP_FG.get().is_none() {
-        if let Some(ref celeste_dir) = editor.celeste_assets.celeste_dir {
-            let mut xml_path = PathBuf::from(celeste_dir);
-            #[cfg(target_os = "macos")]
-            {
-                if !xml_path.ends_with("Celeste.app") {
-                    xml_path = xml_path.join("Celeste.app");
-                }
-                xml_path = xml_path.join("Contents/Resources/Content/Graphics/ForegroundTiles.xml");
-            }
-            #[cfg(not(target_os = "macos"))]
-            {
-                xml_path = xml_path.join("Content/Graphics/ForegroundTiles.xml");
-            }
-            #[cfg(debug_assertions)]
-            debug!("[TILE XML] Loading ForegroundTiles.xml from: {}", xml_path.display());
-            if xml_path.exists() {
-                let map = load_tileset_id_path_map(xml_path.to_str().unwrap());
-                #[cfg(debug_assertions)]
-                debug!("[TILE XML] Loaded {} foreground entries:", map.len());
-                for (id, path) in &map {
-                    #[cfg(debug_assertions)]
-                    debug!("[TILE XML] id='{}' path='{}'", id, path);
-                }
-                let _ = TILESET_ID_PATH_MAP_FG.set(map);
-            } else {
-                #[cfg(debug_assertions)]
-                debug!("[TILE XML] ForegroundTiles.xml not found at {}", xml_path.display());
-            }
-        } else {
-            #[cfg(debug_assertions)]
-            debug!("[TILE XML] celeste_dir is None!");
-        }
-    }
+/// Path of a tiles XML (`ForegroundTiles.xml` or `BackgroundTiles.xml`) in a Celeste install.
+pub fn tiles_xml_path(celeste_dir: &Path, file_name: &str) -> String {
+    CelesteAssets::game_root(celeste_dir).join("Content").join("Graphics").join(file_name).to_string_lossy().to_string()
+}
 
-    // Load background tileset map
-    if TILESET_ID_PATH_MAP_BG.get().is_none() {
-        if let Some(ref celeste_dir) = editor.celeste_assets.celeste_dir {
-            let mut xml_path = PathBuf::from(celeste_dir);
-            #[cfg(target_os = "macos")]
-            {
-                if !xml_path.ends_with("Celeste.app") {
-                    xml_path = xml_path.join("Celeste.app");
-                }
-                xml_path = xml_path.join("Contents/Resources/Content/Graphics/BackgroundTiles.xml");
-            }
-            #[cfg(not(target_os = "macos"))]
-            {
-                xml_path = xml_path.join("Content/Graphics/BackgroundTiles.xml");
-            }
-            #[cfg(debug_assertions)]
-            debug!("[TILE XML] Loading BackgroundTiles.xml from: {}", xml_path.display());
-            if xml_path.exists() {
-                let map = load_tileset_id_path_map(xml_path.to_str().unwrap());
-                #[cfg(debug_assertions)]
-                debug!("[TILE XML] Loaded {} background entries:", map.len());
-                for (id, path) in &map {
-                    #[cfg(debug_assertions)]
-                    debug!("[TILE XML] id='{}' path='{}'", id, path);
-                }
-                let _ = TILESET_ID_PATH_MAP_BG.set(map);
-            } else {
-                #[cfg(debug_assertions)]
-                debug!("[TILE XML] BackgroundTiles.xml not found at {}", xml_path.display());
-            }
-        } else {
+/// Load one tileset id/path map from `xml_path` unless it is already loaded.
+fn ensure_tileset_id_path_map_loaded(cell: &OnceCell<HashMap<char, String>>, xml_path: &str, label: &str) {
+    if cell.get().is_some() {
+        return;
+    }
+    if xml_path.is_empty() {
+        #[cfg(debug_assertions)]
+        debug!("[TILE XML] celeste_dir is None!");
+        return;
+    }
+    #[cfg(debug_assertions)]
+    debug!("[TILE XML] Loading {} tiles XML from: {}", label, xml_path);
+    if Path::new(xml_path).exists() {
+        let map = load_tileset_id_path_map(xml_path);
+        #[cfg(debug_assertions)]
+        debug!("[TILE XML] Loaded {} {} entries:", map.len(), label);
+        for (id, path) in &map {
             #[cfg(debug_assertions)]
-            debug!("[TILE XML] celeste_dir is None!");
+            debug!("[TILE XML] id='{}' path='{}'", id, path);
         }
+        let _ = cell.set(map);
+    } else {
+        #[cfg(debug_assertions)]
+        debug!("[TILE XML] {} tiles XML not found at {}", label, xml_path);
     }
 }
 
+/// Ensures the tileset id/path maps are loaded for both foreground and background, using the Celeste install path.
+pub fn ensure_tileset_id_path_map_loaded_from_celeste(editor: &CelesteMapEditor) {
+    ensure_tileset_id_path_map_loaded(&TILESET_ID_PATH_MAP_FG, &editor.fg_xml_path, "foreground");
+    ensure_tileset_id_path_map_loaded(&TILESET_ID_PATH_MAP_BG, &editor.bg_xml_path, "background");
+}
+
 // --- AUTOTILING DATA STRUCTURES ---
 static TILESET_RULES: OnceCell<HashMap<char, Tileset>> = OnceCell::new();
 
@@ -0,0 +1,46 @@
+use crate::data::map_model::AttributeValue;
+
+/// Vanilla triggers offered when a new trigger is drawn, by `__name`. Modded ones are typed in.
+pub const TRIGGER_NAMES: [&str; 16] = [
+    "altMusicTrigger",
+    "ambienceParamTrigger",
+    "bloomFadeTrigger",
+    "cameraOffsetTrigger",
+    "changeRespawnTrigger",
+    "checkpointBlockerTrigger",
+    "creditsTrigger",
+    "eventTrigger",
+    "goldenBerryCollectTrigger",
+    "lightFadeTrigger",
+    "minitextboxTrigger",
+    "musicFadeTrigger",
+    "musicTrigger",
+    "noRefillTrigger",
+    "stopBoostTrigger",
+    "windTrigger",
+];
+
+/// Attributes a new trigger starts with besides its position and size, with the values the game
+/// falls back to. Unknown names start with none.
+pub fn trigger_defaults(name: &str) -> Vec<(&'static str, AttributeValue)> {
+    match name {
+        "altMusicTrigger" => vec![("track", "".into()), ("resetOnLeave", true.into())],
+        "ambienceParamTrigger" => vec![
+            ("parameter", "".into()), ("from", 0.0.into()), ("to", 0.0.into()), ("direction", "NoEffect".into()),
+        ],
+        "bloomFadeTrigger" => vec![("bloomAddFrom", 0.0.into()), ("bloomAddTo", 0.0.into()), ("positionMode", "NoEffect".into())],
+        "cameraOffsetTrigger" => vec![("cameraX", 0.0.into()), ("cameraY", 0.0.into())],
+        "creditsTrigger" | "eventTrigger" => vec![("event", "".into())],
+        "lightFadeTrigger" => vec![("lightAddFrom", 0.0.into()), ("lightAddTo", 0.0.into()), ("positionMode", "NoEffect".into())],
+        "minitextboxTrigger" => vec![
+            ("dialog_id", "".into()), ("mode", "OnPlayerEnter".into()), ("only_once", true.into()), ("death_count", (-1i64).into()),
+        ],
+        "musicFadeTrigger" => vec![
+            ("direction", "leftToRight".into()), ("fadeA", 0.0.into()), ("fadeB", 1.0.into()), ("parameter", "".into()),
+        ],
+        "musicTrigger" => vec![("track", "".into()), ("resetOnLeave", true.into()), ("progress", 0i64.into())],
+        "noRefillTrigger" => vec![("state", true.into())],
+        "windTrigger" => vec![("pattern", "None".into())],
+        _ => Vec::new(),
+    }
+}
@@ -5,6 +5,7 @@ use std::io::{self, Read, Seek};
 use std::fs::File;
 use std::path::Path;
 use image::RgbaImage;
+use log::warn;
 
 use crate::data::binary_reader::BinaryReader;
 
@@ -88,18 +89,77 @@ impl From<i32> for TextureFormat {
     }
 }
 
+// Newest XNB format version: XNA 4.0, also written by MonoGame and FNA
+const MAX_XNB_VERSION: u8 = 5;
+
+/// Target platform byte of an XNB header. XNA defined the first three, MonoGame added the rest.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Platform {
+    Windows,
+    WindowsPhone,
+    Xbox360,
+    DesktopGl,
+    MacOsx,
+    Ios,
+    Android,
+    WindowsStore,
+    NativeClient,
+    WindowsPhone8,
+    RaspberryPi,
+    PlayStation4,
+    PlayStationVita,
+    XboxOne,
+    Switch,
+}
+
+impl Platform {
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            b'w' => Some(Platform::Windows),
+            b'm' => Some(Platform::WindowsPhone),
+            b'x' => Some(Platform::Xbox360),
+            b'd' => Some(Platform::DesktopGl),
+            b'X' => Some(Platform::MacOsx),
+            b'i' => Some(Platform::Ios),
+            b'a' => Some(Platform::Android),
+            b'W' => Some(Platform::WindowsStore),
+            b'n' => Some(Platform::NativeClient),
+            b'M' => Some(Platform::WindowsPhone8),
+            b'r' => Some(Platform::RaspberryPi),
+            b'P' => Some(Platform::PlayStation4),
+            b'5' => Some(Platform::PlayStationVita),
+            b'O' => Some(Platform::XboxOne),
+            b'S' => Some(Platform::Switch),
+            _ => None,
+        }
+    }
+}
+
 /// Reads XNB file format used by XNA/MonoGame (and Celeste)
 pub struct XnbReader<R: Read + Seek> {
     reader: BinaryReader<R>,
+    /// Header fields, set once the header is parsed.
+    platform: Option<Platform>,
+    version: u8,
 }
 
 impl<R: Read + Seek> XnbReader<R> {
     pub fn new(reader: R) -> Self {
         Self {
             reader: BinaryReader::new(reader),
+            platform: None,
+            version: 0,
         }
     }
 
+    pub fn platform(&self) -> Option<Platform> {
+        self.platform
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
     /// Read an XNB texture file and extract the image data
     pub fn read_texture(&mut self) -> Result<RgbaImage> {
         // Parse the XNB header
@@ -123,9 +183,21 @@ impl<R: Read + Seek> XnbReader<R> {
         }
 
         // Platform, version and flags
-        let _platform = self.reader.read_byte()?;
-        let _version = self.reader.read_byte()?;
-        let flag = self.reader.read_byte()? as u8;
+        let platform_byte = self.reader.read_ubyte()?;
+        let Some(platform) = Platform::from_byte(platform_byte) else {
+            return Err(XnbError::UnsupportedFeature(format!("Unknown XNB platform '{}'", platform_byte.escape_ascii())));
+        };
+        let version = self.reader.read_ubyte()?;
+        if version > MAX_XNB_VERSION {
+            return Err(XnbError::UnsupportedFeature(format!("XNB format version {} is newer than {}", version, MAX_XNB_VERSION)));
+        }
+        // Celeste ships version 5 Windows / DesktopGL content; other variants may still read fine
+        if version != MAX_XNB_VERSION || !matches!(platform, Platform::Windows | Platform::DesktopGl) {
+            warn!("Untested XNB variant: platform {:?}, version {}", platform, version);
+        }
+        self.platform = Some(platform);
+        self.version = version;
+        let flag = self.reader.read_ubyte()?;
 
         // File size (including header)
         let _file_size = self.reader.read_ulong()?;
@@ -3,3 +3,5 @@ pub mod config;
 pub mod data;
 pub mod map;
 pub mod ui;
+
+pub use map::loader::{load_map_file, save_map_file, LoadError, LoadedMap, SaveError};
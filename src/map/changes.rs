@@ -0,0 +1,192 @@
+use std::collections::{HashMap, HashSet};
+
+use serde_json::Value;
+
+use crate::app::CelesteMapEditor;
+use crate::data::map_model::{self, CelestePixels};
+
+// Level children whose objects are matched by texture and position; entities and triggers go by id
+const DECAL_LAYERS: [&str; 2] = ["fgdecals", "bgdecals"];
+
+/// A room as it was when the map was loaded or saved. Only what Review Changes compares is kept,
+/// so a snapshot costs about the size of the solids text rather than a copy of the room's JSON.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoomSnapshot {
+    pub name: String,
+    /// Room position in Celeste pixels; a room whose name changed is matched by it.
+    pub position: (i64, i64),
+    pub solids: String,
+    /// Ids of the room's entities and triggers.
+    pub object_ids: HashSet<i64>,
+    /// Decals as "texture x y", with how many of each.
+    pub decals: HashMap<String, usize>,
+}
+
+/// Every room of a map, in level order.
+pub type MapSnapshot = Vec<RoomSnapshot>;
+
+/// How a room compares with the snapshot.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RoomStatus {
+    Added,
+    Removed,
+    Renamed { from: String },
+    Edited,
+}
+
+/// One line of Review Changes.
+#[derive(Clone, Debug)]
+pub struct RoomChange {
+    /// Index of the room in the current map; None for a removed room.
+    pub room_idx: Option<usize>,
+    pub name: String,
+    pub status: RoomStatus,
+    /// Solids cells that differ, as (column, row). Empty for a removed room.
+    pub changed_tiles: Vec<(usize, usize)>,
+    pub entities_added: usize,
+    pub entities_removed: usize,
+    pub decals_added: usize,
+    pub decals_removed: usize,
+}
+
+impl RoomChange {
+    /// Counts for the list, e.g. "12 tiles, +2 -1 entities".
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.changed_tiles.is_empty() {
+            parts.push(format!("{} tiles", self.changed_tiles.len()));
+        }
+        for (added, removed, what) in [
+            (self.entities_added, self.entities_removed, "entities"),
+            (self.decals_added, self.decals_removed, "decals"),
+        ] {
+            if added + removed > 0 {
+                parts.push(format!("+{} -{} {}", added, removed, what));
+            }
+        }
+        parts.join(", ")
+    }
+}
+
+fn layer_children<'a>(level: &'a Value, layers: &'a [&str]) -> impl Iterator<Item = &'a Value> {
+    level["__children"].as_array().into_iter().flatten()
+        .filter(move |c| c["__name"].as_str().map_or(false, |n| layers.contains(&n)))
+        .flat_map(|c| c["__children"].as_array().into_iter().flatten())
+}
+
+/// Snapshot of one level node.
+pub fn snapshot_room(level: &Value) -> RoomSnapshot {
+    let solids = level["__children"].as_array().into_iter().flatten()
+        .find(|c| c["__name"] == "solids")
+        .and_then(|c| c["innerText"].as_str())
+        .unwrap_or("")
+        .to_string();
+    let mut decals = HashMap::new();
+    for decal in layer_children(level, &DECAL_LAYERS) {
+        let key = format!("{} {} {}",
+            decal["texture"].as_str().unwrap_or(""), CelestePixels::attr(&decal["x"]).0, CelestePixels::attr(&decal["y"]).0);
+        *decals.entry(key).or_insert(0) += 1;
+    }
+    RoomSnapshot {
+        name: level["name"].as_str().unwrap_or("").to_string(),
+        position: (CelestePixels::attr(&level["x"]).0.round() as i64, CelestePixels::attr(&level["y"]).0.round() as i64),
+        solids,
+        object_ids: map_model::level_entity_ids(level).collect(),
+        decals,
+    }
+}
+
+/// Snapshot of every room of `map`.
+pub fn snapshot_map(map: &Value) -> MapSnapshot {
+    map_model::levels(map).into_iter().flatten().map(snapshot_room).collect()
+}
+
+/// Solids cells that differ between two grids, as (column, row). Cells past the end of a row or
+/// of the grid count as air.
+pub fn changed_cells(before: &str, after: &str) -> Vec<(usize, usize)> {
+    let rows = |text: &str| -> Vec<Vec<char>> {
+        if text.is_empty() { Vec::new() } else { text.split('\n').map(|r| r.trim_end_matches('\r').chars().collect()).collect() }
+    };
+    let (before, after) = (rows(before), rows(after));
+    let cell = |grid: &[Vec<char>], x: usize, y: usize| grid.get(y).and_then(|r| r.get(x)).copied().unwrap_or('0');
+    let mut cells = Vec::new();
+    for y in 0..before.len().max(after.len()) {
+        let width = before.get(y).map_or(0, Vec::len).max(after.get(y).map_or(0, Vec::len));
+        cells.extend((0..width).filter(|&x| cell(&before, x, y) != cell(&after, x, y)).map(|x| (x, y)));
+    }
+    cells
+}
+
+/// (added, removed) between two decal multisets.
+fn decal_delta(before: &HashMap<String, usize>, after: &HashMap<String, usize>) -> (usize, usize) {
+    let more = |a: &HashMap<String, usize>, b: &HashMap<String, usize>| {
+        a.iter().map(|(key, n)| n.saturating_sub(b.get(key).copied().unwrap_or(0))).sum()
+    };
+    (more(after, before), more(before, after))
+}
+
+fn compare(room_idx: Option<usize>, status: RoomStatus, before: &RoomSnapshot, after: &RoomSnapshot) -> RoomChange {
+    let (decals_added, decals_removed) = decal_delta(&before.decals, &after.decals);
+    RoomChange {
+        room_idx,
+        name: if room_idx.is_some() { after.name.clone() } else { before.name.clone() },
+        status,
+        changed_tiles: if room_idx.is_some() { changed_cells(&before.solids, &after.solids) } else { Vec::new() },
+        entities_added: after.object_ids.difference(&before.object_ids).count(),
+        entities_removed: before.object_ids.difference(&after.object_ids).count(),
+        decals_added,
+        decals_removed,
+    }
+}
+
+/// Rooms of `map` that differ from `snapshot`, in level order, then the rooms removed since.
+/// Rooms are matched by name; an unmatched room at the position of an unmatched removed one
+/// counts as renamed.
+pub fn diff_map(snapshot: &MapSnapshot, map: &Value) -> Vec<RoomChange> {
+    let current = snapshot_map(map);
+    let empty = RoomSnapshot {
+        name: String::new(),
+        position: (0, 0),
+        solids: String::new(),
+        object_ids: HashSet::new(),
+        decals: HashMap::new(),
+    };
+    let names: HashSet<&str> = current.iter().map(|r| r.name.as_str()).collect();
+    let saved: HashMap<&str, &RoomSnapshot> = snapshot.iter().rev().map(|r| (r.name.as_str(), r)).collect();
+    let mut unmatched: Vec<&RoomSnapshot> = snapshot.iter().filter(|r| !names.contains(r.name.as_str())).collect();
+    let mut changes = Vec::new();
+    for (idx, room) in current.iter().enumerate() {
+        let change = match saved.get(room.name.as_str()) {
+            Some(before) => compare(Some(idx), RoomStatus::Edited, before, room),
+            None => match unmatched.iter().position(|r| r.position == room.position) {
+                Some(i) => {
+                    let before = unmatched.remove(i);
+                    compare(Some(idx), RoomStatus::Renamed { from: before.name.clone() }, before, room)
+                }
+                None => compare(Some(idx), RoomStatus::Added, &empty, room),
+            },
+        };
+        let untouched = change.status == RoomStatus::Edited
+            && change.changed_tiles.is_empty()
+            && change.entities_added + change.entities_removed + change.decals_added + change.decals_removed == 0;
+        if !untouched {
+            changes.push(change);
+        }
+    }
+    changes.extend(unmatched.into_iter().map(|before| compare(None, RoomStatus::Removed, before, &empty)));
+    changes
+}
+
+/// Remember the open map as the state Review Changes compares against. Call after a load or save.
+pub fn take_snapshot(editor: &mut CelesteMapEditor) {
+    editor.saved_snapshot = editor.map_data.as_ref().map(snapshot_map);
+    editor.map_changes.clear();
+}
+
+/// Compare the open map with its snapshot again.
+pub fn refresh_changes(editor: &mut CelesteMapEditor) {
+    editor.map_changes = match (&editor.saved_snapshot, &editor.map_data) {
+        (Some(snapshot), Some(map)) => diff_map(snapshot, map),
+        _ => Vec::new(),
+    };
+}
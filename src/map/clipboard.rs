@@ -0,0 +1,196 @@
+use eframe::egui::Pos2;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use log::info;
+
+use crate::app::CelesteMapEditor;
+use crate::data::map_model::{self, level_size_tiles, CelestePixels, LevelNode, TileCoord, ID_LAYERS};
+use crate::map::editor::{find_room_at, room_local_tile, get_solids_offset};
+use crate::map::rooms::edit_room;
+
+// Room children that can be carried along with copied tiles
+const OBJECT_LAYERS: [&str; 3] = ["entities", "fgdecals", "bgdecals"];
+// Room children whose elements get fresh ids when pasted
+
+/// A rectangular tile selection inside one room, in room-local tile coordinates (inclusive).
+#[derive(Clone, Debug)]
+pub struct TileSelection {
+    pub room: usize,
+    pub start: (i32, i32),
+    pub end: (i32, i32),
+}
+
+impl TileSelection {
+    /// Returns (min_x, min_y, max_x, max_y).
+    pub fn bounds(&self) -> (i32, i32, i32, i32) {
+        (
+            self.start.0.min(self.end.0),
+            self.start.1.min(self.end.1),
+            self.start.0.max(self.end.0),
+            self.start.1.max(self.end.1),
+        )
+    }
+}
+
+/// An entity or decal copied with a selection, positioned relative to the selection origin.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClipboardObject {
+    /// Name of the room child it belongs to ("entities", "triggers", "fgdecals" or "bgdecals").
+    pub layer: String,
+    pub data: Value,
+}
+
+/// Copied tiles (one string per row) plus any objects inside the selection.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TileClipboard {
+    #[serde(default)]
+    pub tiles: Vec<String>,
+    #[serde(default)]
+    pub objects: Vec<ClipboardObject>,
+}
+
+impl TileClipboard {
+    pub fn width(&self) -> usize {
+        self.tiles.iter().map(|r| r.chars().count()).max().unwrap_or(0)
+    }
+
+    pub fn height(&self) -> usize {
+        self.tiles.len()
+    }
+}
+
+pub fn begin_selection(editor: &mut CelesteMapEditor, pos: Pos2) {
+    if editor.show_all_rooms {
+        match find_room_at(editor, pos) {
+            Some(i) => editor.current_level_index = i,
+            None => return,
+        }
+    }
+    if let Some(tile) = room_local_tile(editor, pos) {
+        editor.selection = Some(TileSelection {
+            room: editor.current_level_index,
+            start: tile,
+            end: tile,
+        });
+    }
+}
+
+pub fn update_selection(editor: &mut CelesteMapEditor, pos: Pos2) {
+    let Some(tile) = room_local_tile(editor, pos) else { return };
+    if let Some(sel) = &mut editor.selection {
+        if sel.room == editor.current_level_index {
+            sel.end = tile;
+        }
+    }
+}
+
+/// Copy the selected tiles and the objects they contain into the clipboard.
+pub fn copy_selection(editor: &mut CelesteMapEditor) {
+    let Some(sel) = editor.selection.clone() else { return };
+    editor.current_level_index = sel.room;
+    let Some(level) = editor.get_current_level_json() else { return };
+    let (min_x, min_y, max_x, max_y) = sel.bounds();
+    let solids = editor.get_solids_data().unwrap_or_default();
+    let rows: Vec<Vec<char>> = solids.split('\n').map(|r| r.chars().collect()).collect();
+
+    let mut tiles = Vec::new();
+    for y in min_y..=max_y {
+        let mut row = String::new();
+        for x in min_x..=max_x {
+            let c = if x < 0 || y < 0 {
+                '0'
+            } else {
+                rows.get(y as usize).and_then(|r| r.get(x as usize)).copied().unwrap_or('0')
+            };
+            row.push(c);
+        }
+        tiles.push(row);
+    }
+
+    let (offset_x, offset_y) = get_solids_offset(level);
+    let CelestePixels(origin_x) = CelestePixels(offset_x as f64) + TileCoord(min_x).to_pixels();
+    let CelestePixels(origin_y) = CelestePixels(offset_y as f64) + TileCoord(min_y).to_pixels();
+    let CelestePixels(limit_x) = CelestePixels(offset_x as f64) + TileCoord(max_x + 1).to_pixels();
+    let CelestePixels(limit_y) = CelestePixels(offset_y as f64) + TileCoord(max_y + 1).to_pixels();
+
+    let mut objects = Vec::new();
+    if let Some(children) = level["__children"].as_array() {
+        for child in children {
+            let Some(layer) = child["__name"].as_str() else { continue };
+            if !OBJECT_LAYERS.contains(&layer) { continue; }
+            for obj in child["__children"].as_array().into_iter().flatten() {
+                let x = obj["x"].as_f64().unwrap_or(0.0);
+                let y = obj["y"].as_f64().unwrap_or(0.0);
+                if x >= origin_x && x < limit_x && y >= origin_y && y < limit_y {
+                    let mut data = obj.clone();
+                    data["x"] = serde_json::json!(x - origin_x);
+                    data["y"] = serde_json::json!(y - origin_y);
+                    objects.push(ClipboardObject { layer: layer.to_string(), data });
+                }
+            }
+        }
+    }
+
+    info!("Copied {}x{} tiles and {} objects", max_x - min_x + 1, max_y - min_y + 1, objects.len());
+    editor.clipboard = Some(TileClipboard { tiles, objects });
+}
+
+/// Paste the clipboard with its top-left corner on the tile under `pos`.
+pub fn paste_clipboard(editor: &mut CelesteMapEditor, pos: Pos2) {
+    let Some(clip) = editor.clipboard.clone() else { return };
+    if editor.show_all_rooms {
+        match find_room_at(editor, pos) {
+            Some(i) => editor.current_level_index = i,
+            None => return,
+        }
+    }
+    let Some((dest_x, dest_y)) = room_local_tile(editor, pos) else { return };
+    let Some(level) = editor.get_current_level_json() else { return };
+    let (TileCoord(room_w), TileCoord(room_h)) = level_size_tiles(level);
+    let (offset_x, offset_y) = get_solids_offset(level);
+
+    if !clip.objects.is_empty() {
+        let CelestePixels(origin_x) = CelestePixels(offset_x as f64) + TileCoord(dest_x).to_pixels();
+        let CelestePixels(origin_y) = CelestePixels(offset_y as f64) + TileCoord(dest_y).to_pixels();
+        let room_idx = editor.current_level_index;
+        let Some(mut map) = editor.map_data.as_ref().and_then(map_model::parse_map) else { return };
+        if map.level(room_idx).is_none() {
+            editor.notifications.warn(format!("Room {} could not be read, nothing pasted", room_idx));
+            return;
+        }
+        for obj in &clip.objects {
+            let mut data = obj.data.clone();
+            data["x"] = serde_json::json!(data["x"].as_f64().unwrap_or(0.0) + origin_x);
+            data["y"] = serde_json::json!(data["y"].as_f64().unwrap_or(0.0) + origin_y);
+            if ID_LAYERS.contains(&obj.layer.as_str()) {
+                map.add_element(room_idx, &obj.layer, data);
+            } else if let Some(level) = map.level_mut(room_idx) {
+                level.push_child(&obj.layer, data);
+            }
+        }
+        let Some(after) = map.level(room_idx).map(LevelNode::to_value) else { return };
+        edit_room(editor, room_idx, &format!("Paste {} objects", clip.objects.len()), |level| *level = after);
+    }
+
+    if clip.tiles.is_empty() { return; }
+    let solids = editor.get_solids_data().unwrap_or_default();
+    let mut rows: Vec<Vec<char>> = solids.split('\n').map(|r| r.chars().collect()).collect();
+    for (dy, src_row) in clip.tiles.iter().enumerate() {
+        let y = dest_y + dy as i32;
+        if y < 0 || y >= room_h { continue; }
+        while rows.len() <= y as usize {
+            rows.push(Vec::new());
+        }
+        for (dx, c) in src_row.chars().enumerate() {
+            let x = dest_x + dx as i32;
+            if x < 0 || x >= room_w { continue; }
+            let row = &mut rows[y as usize];
+            while row.len() <= x as usize {
+                row.push('0');
+            }
+            row[x as usize] = c;
+        }
+    }
+    let joined: Vec<String> = rows.into_iter().map(|r| r.into_iter().collect()).collect();
+    editor.update_solids_data(&joined.join("\n"));
+}
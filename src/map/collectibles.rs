@@ -0,0 +1,111 @@
+use eframe::egui::Color32;
+use serde_json::Value;
+
+use crate::app::CachedRoom;
+use crate::data::map_model::CelestePixels;
+
+/// Entities that count towards a chapter's completion.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollectibleKind {
+    Strawberry,
+    GoldenBerry,
+    /// The memorial text that unlocks once every berry is collected.
+    Memorial,
+    Cassette,
+    CrystalHeart,
+}
+
+impl CollectibleKind {
+    pub fn from_entity_name(name: &str) -> Option<Self> {
+        match name {
+            "strawberry" => Some(Self::Strawberry),
+            "goldenBerry" => Some(Self::GoldenBerry),
+            "memorialTextController" => Some(Self::Memorial),
+            "cassette" => Some(Self::Cassette),
+            "blackGem" => Some(Self::CrystalHeart),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Strawberry => "Strawberry",
+            Self::GoldenBerry => "Golden Berry",
+            Self::Memorial => "Memorial",
+            Self::Cassette => "Cassette",
+            Self::CrystalHeart => "Crystal Heart",
+        }
+    }
+
+    /// Short glyph drawn inside the overlay marker.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Strawberry => "S",
+            Self::GoldenBerry => "G",
+            Self::Memorial => "M",
+            Self::Cassette => "C",
+            Self::CrystalHeart => "H",
+        }
+    }
+
+    /// Sprites to draw the overlay icon with, in order of preference: the Gui atlas icon, then
+    /// the in-game sprite from Gameplay.
+    pub fn icon_paths(self) -> &'static [&'static str] {
+        match self {
+            Self::Strawberry => &["collectables/strawberry", "collectables/strawberry/normal00"],
+            Self::GoldenBerry => &["collectables/goldberry", "collectables/goldberry/idle00"],
+            Self::Memorial => &["scenery/memorial/memorial"],
+            Self::Cassette => &["collectables/cassette", "collectables/cassette/idle00"],
+            Self::CrystalHeart => &["collectables/heartgem/0/spin00", "collectables/heartGem/0/00"],
+        }
+    }
+
+    pub fn color(self) -> Color32 {
+        match self {
+            Self::Strawberry => Color32::from_rgb(230, 60, 80),
+            Self::GoldenBerry => Color32::from_rgb(240, 200, 60),
+            Self::Memorial => Color32::from_rgb(200, 200, 200),
+            Self::Cassette => Color32::from_rgb(220, 90, 220),
+            Self::CrystalHeart => Color32::from_rgb(80, 150, 255),
+        }
+    }
+}
+
+/// A collectible entity and where it is, in room-local Celeste pixels.
+#[derive(Clone, Debug)]
+pub struct Collectible {
+    pub room_idx: usize,
+    pub kind: CollectibleKind,
+    pub x: f64,
+    pub y: f64,
+    /// Strawberry `order` within its checkpoint; -1 or absent means unordered.
+    pub order: Option<i64>,
+    pub checkpoint_id: Option<i64>,
+}
+
+fn level_collectibles(room_idx: usize, level: &Value) -> impl Iterator<Item = Collectible> + '_ {
+    level["__children"].as_array().into_iter().flatten()
+        .filter(|c| c["__name"] == "entities")
+        .flat_map(|c| c["__children"].as_array().into_iter().flatten())
+        .filter_map(move |e| Some(Collectible {
+            room_idx,
+            kind: CollectibleKind::from_entity_name(e["__name"].as_str()?)?,
+            x: CelestePixels::attr(&e["x"]).0,
+            y: CelestePixels::attr(&e["y"]).0,
+            order: e["order"].as_i64(),
+            checkpoint_id: e["checkpointID"].as_i64(),
+        }))
+}
+
+/// Every collectible of the cached rooms in list order: strawberries by checkpoint then `order`,
+/// then everything else by room.
+pub fn find_collectibles(rooms: &[CachedRoom]) -> Vec<Collectible> {
+    let mut all: Vec<Collectible> = rooms.iter().enumerate()
+        .flat_map(|(i, room)| level_collectibles(i, &room.json).collect::<Vec<_>>())
+        .collect();
+    all.sort_by_key(|c| match c.kind {
+        CollectibleKind::Strawberry => (0, c.checkpoint_id.unwrap_or(i64::MAX), c.order.filter(|o| *o >= 0).unwrap_or(i64::MAX), c.room_idx),
+        _ => (1, 0, 0, c.room_idx),
+    });
+    all
+}
@@ -1,61 +1,77 @@
 use eframe::egui::Pos2;
+use serde_json::Value;
 use crate::app::CelesteMapEditor;
+use crate::data::map_model::{self, level_size_tiles, CelestePixels, TileCoord};
+use crate::map::history::EditCommand;
+use crate::ui::transform::{RoomGrid, ViewTransform};
 
-const CELESTE_TILE_PX: f32 = 8.0;
+/// What the place binding does on the canvas.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum EditorTool {
+    #[default]
+    Brush,
+    Eyedropper,
+    FloodFill,
+    Line,
+    RectSelect,
+    Move,
+    /// Works on entities and decals at pixel resolution.
+    Entities,
+    /// Draws new triggers and resizes existing ones.
+    Triggers,
+}
 
-pub fn place_block(editor: &mut CelesteMapEditor, pos: Pos2) {
-    if editor.show_all_rooms {
-        match find_room_at(editor, pos) {
-            Some(i) => editor.current_level_index = i,
-            None => return,
+impl EditorTool {
+    pub fn label(self) -> &'static str {
+        match self {
+            EditorTool::Brush => "Brush",
+            EditorTool::Eyedropper => "Eyedropper",
+            EditorTool::FloodFill => "Fill",
+            EditorTool::Line => "Line",
+            EditorTool::RectSelect => "Select",
+            EditorTool::Move => "Move",
+            EditorTool::Entities => "Entities",
+            EditorTool::Triggers => "Triggers",
         }
     }
-    modify_tile(editor, pos, '9');
 }
 
-pub fn remove_block(editor: &mut CelesteMapEditor, pos: Pos2) {
-    if editor.show_all_rooms {
-        match find_room_at(editor, pos) {
-            Some(i) => editor.current_level_index = i,
-            None => return,
-        }
+/// Pixel snap choices for entity editing; None places freely.
+pub const SNAP_OPTIONS: [Option<u32>; 5] = [None, Some(1), Some(2), Some(4), Some(8)];
+/// Decal snap grids in Celeste pixels; 0 places freely.
+pub const DECAL_SNAP_OPTIONS: [u8; 4] = [0, 2, 4, 8];
+
+/// Round a Celeste pixel coordinate to the snap step. Tile tools don't use this; they always snap to whole tiles.
+pub fn snap_pixels(value: CelestePixels, snap: Option<u32>) -> CelestePixels {
+    match snap {
+        Some(step) if step > 1 => CelestePixels((value.0 / step as f64).round() * step as f64),
+        Some(_) => CelestePixels(value.0.round()),
+        None => value,
     }
-    modify_tile(editor, pos, '0');
 }
 
-fn find_room_at(editor: &CelesteMapEditor, pos: Pos2) -> Option<usize> {
-    let scale = crate::ui::render::TILE_SIZE / CELESTE_TILE_PX * editor.zoom_level;
+pub(crate) fn find_room_at(editor: &CelesteMapEditor, pos: Pos2) -> Option<usize> {
+    let world = ViewTransform::of(editor).screen_to_world(pos);
+    let (px, py) = (CelestePixels(world.x as f64), CelestePixels(world.y as f64));
     let map = editor.map_data.as_ref()?;
-    let levels = find_levels(map)?;
+    let levels = map_model::levels(map)?;
 
     for (i, level) in levels.iter().enumerate() {
         if level["__name"] != "level" { continue; }
 
-        let rx = level["x"].as_f64()? as f32;
-        let ry = level["y"].as_f64()? as f32;
-        let rw = level["width"].as_f64().unwrap_or(320.0) as f32;
-        let rh = level["height"].as_f64().unwrap_or(184.0) as f32;
+        let rx = CelestePixels(level["x"].as_f64()?);
+        let ry = CelestePixels(level["y"].as_f64()?);
+        let rw = CelestePixels(level["width"].as_f64().unwrap_or(320.0));
+        let rh = CelestePixels(level["height"].as_f64().unwrap_or(184.0));
 
-        let screen_x = rx * scale - editor.camera_pos.x;
-        let screen_y = ry * scale - editor.camera_pos.y;
-
-        if pos.x >= screen_x && pos.x < screen_x + rw * scale
-            && pos.y >= screen_y && pos.y < screen_y + rh * scale
-        {
+        if px >= rx && px < rx + rw && py >= ry && py < ry + rh {
             return Some(i);
         }
     }
     None
 }
 
-fn find_levels(map: &serde_json::Value) -> Option<&Vec<serde_json::Value>> {
-    map["__children"].as_array()?
-        .iter()
-        .find(|c| c["__name"] == "levels")?
-        ["__children"].as_array()
-}
-
-fn get_solids_offset(level: &serde_json::Value) -> (i32, i32) {
+pub(crate) fn get_solids_offset(level: &serde_json::Value) -> (i32, i32) {
     level["__children"].as_array()
         .and_then(|children| children.iter().find(|c| c["__name"] == "solids"))
         .map(|s| (
@@ -65,45 +81,187 @@ fn get_solids_offset(level: &serde_json::Value) -> (i32, i32) {
         .unwrap_or((0, 0))
 }
 
-fn modify_tile(editor: &mut CelesteMapEditor, pos: Pos2, tile_char: char) {
-    let (abs_x, abs_y) = editor.screen_to_map(pos);
-
-    let Some(level) = editor.get_current_level() else { return };
-    let room_x = level["x"].as_f64().unwrap_or(0.0) as f32;
-    let room_y = level["y"].as_f64().unwrap_or(0.0) as f32;
-    let room_w = (level["width"].as_f64().unwrap_or(0.0) / CELESTE_TILE_PX as f64) as i32;
-    let room_h = (level["height"].as_f64().unwrap_or(0.0) / CELESTE_TILE_PX as f64) as i32;
-    let (offset_x, offset_y) = get_solids_offset(level);
-
-    let origin_x = ((room_x + offset_x as f32) / CELESTE_TILE_PX).floor() as i32;
-    let origin_y = ((room_y + offset_y as f32) / CELESTE_TILE_PX).floor() as i32;
-    let local_x = abs_x - origin_x;
-    let local_y = abs_y - origin_y;
-
-    if local_x < 0 || local_y < 0 || local_x >= room_w || local_y >= room_h { return; }
-
-    let Some(solids) = editor.get_solids_data() else { return };
-    let mut rows: Vec<String> = solids.split('\n').map(|s| s.to_string()).collect();
-
-    if tile_char == '0' {
-        if local_y as usize >= rows.len() { return; }
-        let row = &rows[local_y as usize];
-        if local_x as usize >= row.len() { return; }
-        let mut new_row = row.clone();
-        new_row.replace_range(local_x as usize..local_x as usize + 1, "0");
-        rows[local_y as usize] = new_row;
-    } else {
-        while rows.len() <= local_y as usize {
-            rows.push(String::new());
+/// Convert a screen position to tile coordinates local to the current room's solids grid.
+/// The result is not bounds-checked against the room size.
+pub(crate) fn room_local_tile(editor: &CelesteMapEditor, pos: Pos2) -> Option<(i32, i32)> {
+    Some(level_local_tile(editor, editor.get_current_level_json()?, pos))
+}
+
+/// The room under `pos` as tile edits see it: the hovered room when showing all rooms,
+/// otherwise the current one.
+pub(crate) fn hovered_room(editor: &CelesteMapEditor, pos: Pos2) -> Option<usize> {
+    if editor.show_all_rooms { find_room_at(editor, pos) } else { Some(editor.current_level_index) }
+}
+
+/// Tile under `pos` relative to the tile grid origin of `level`.
+pub(crate) fn level_local_tile(editor: &CelesteMapEditor, level: &serde_json::Value, pos: Pos2) -> (i32, i32) {
+    ViewTransform::of(editor).screen_to_room_tile(RoomGrid::of_level(level), pos)
+}
+
+/// Focus the room under `pos` when all rooms are shown. Returns false if there is none.
+fn focus_room_at(editor: &mut CelesteMapEditor, pos: Pos2) -> bool {
+    if editor.show_all_rooms {
+        match find_room_at(editor, pos) {
+            Some(i) => editor.current_level_index = i,
+            None => return false,
         }
-        let row = &mut rows[local_y as usize];
-        while row.len() <= local_x as usize {
-            row.push('0');
+    }
+    true
+}
+
+/// Current room's solids as a grid padded to the room size with air.
+fn padded_solids(editor: &CelesteMapEditor) -> Option<(Vec<Vec<char>>, i32, i32)> {
+    let (TileCoord(room_w), TileCoord(room_h)) = level_size_tiles(editor.get_current_level_json()?);
+    let solids = editor.get_solids_data().unwrap_or_default();
+    let mut rows: Vec<Vec<char>> = solids.split('\n').map(|r| r.chars().collect()).collect();
+    rows.resize(rows.len().max(room_h as usize), Vec::new());
+    for row in rows.iter_mut().take(room_h as usize) {
+        if row.len() < room_w as usize {
+            row.resize(room_w as usize, '0');
+        }
+    }
+    Some((rows, room_w, room_h))
+}
+
+fn store_solids(editor: &mut CelesteMapEditor, rows: Vec<Vec<char>>) {
+    let joined: Vec<String> = rows.into_iter().map(|r| r.into_iter().collect()).collect();
+    editor.update_solids_data(&joined.join("\n"));
+}
+
+/// Set the brush tile to the tile under `pos` and switch back to the brush.
+pub fn pick_tile(editor: &mut CelesteMapEditor, pos: Pos2) {
+    if !focus_room_at(editor, pos) { return; }
+    let Some((x, y)) = room_local_tile(editor, pos) else { return };
+    let Some((rows, w, h)) = padded_solids(editor) else { return };
+    if x < 0 || y < 0 || x >= w || y >= h { return; }
+    editor.brush_tile = rows[y as usize][x as usize];
+    editor.active_tool = EditorTool::Brush;
+}
+
+/// Replace the 4-connected region of identical tiles under `pos` with the brush tile.
+pub fn flood_fill(editor: &mut CelesteMapEditor, pos: Pos2) {
+    if !focus_room_at(editor, pos) { return; }
+    let Some((x, y)) = room_local_tile(editor, pos) else { return };
+    let Some((mut rows, w, h)) = padded_solids(editor) else { return };
+    if x < 0 || y < 0 || x >= w || y >= h { return; }
+    let target = rows[y as usize][x as usize];
+    let fill = editor.brush_tile;
+    if target == fill { return; }
+
+    let mut stack = vec![(x, y)];
+    while let Some((cx, cy)) = stack.pop() {
+        if cx < 0 || cy < 0 || cx >= w || cy >= h { continue; }
+        let cell = &mut rows[cy as usize][cx as usize];
+        if *cell != target { continue; }
+        *cell = fill;
+        stack.extend([(cx + 1, cy), (cx - 1, cy), (cx, cy + 1), (cx, cy - 1)]);
+    }
+    store_solids(editor, rows);
+}
+
+/// First click anchors the line, second click paints it with the brush tile.
+pub fn line_click(editor: &mut CelesteMapEditor, pos: Pos2) {
+    if !focus_room_at(editor, pos) { return; }
+    let Some(end) = room_local_tile(editor, pos) else { return };
+    match editor.line_start.take() {
+        Some((room, start)) if room == editor.current_level_index => draw_line(editor, start, end),
+        _ => editor.line_start = Some((editor.current_level_index, end)),
+    }
+}
+
+/// Paint a Bresenham line between two room-local tiles with the brush tile, clipped to the room.
+pub fn draw_line(editor: &mut CelesteMapEditor, start: (i32, i32), end: (i32, i32)) {
+    if let Some(rows) = line_rows(editor, start, end, editor.brush_tile) {
+        store_solids(editor, rows);
+    }
+}
+
+/// Paint a line during a stroke. Nothing is recorded; `end_stroke` records the whole stroke.
+fn stroke_line(editor: &mut CelesteMapEditor, start: (i32, i32), end: (i32, i32), tile: char) {
+    if let Some(rows) = line_rows(editor, start, end, tile) {
+        let joined: Vec<String> = rows.into_iter().map(|r| r.into_iter().collect()).collect();
+        editor.set_solids_data(&joined.join("\n"));
+    }
+}
+
+/// The current room's solids with a Bresenham line of `tile` between two tiles, clipped to the room.
+fn line_rows(editor: &CelesteMapEditor, start: (i32, i32), end: (i32, i32), tile: char) -> Option<Vec<Vec<char>>> {
+    let (mut rows, w, h) = padded_solids(editor)?;
+    let (mut x, mut y) = start;
+    let dx = (end.0 - x).abs();
+    let dy = -(end.1 - y).abs();
+    let sx = if x < end.0 { 1 } else { -1 };
+    let sy = if y < end.1 { 1 } else { -1 };
+    let mut err = dx + dy;
+    loop {
+        if x >= 0 && y >= 0 && x < w && y < h {
+            rows[y as usize][x as usize] = tile;
         }
-        let mut new_row = row.clone();
-        new_row.replace_range(local_x as usize..local_x as usize + 1, &tile_char.to_string());
-        rows[local_y as usize] = new_row;
+        if (x, y) == end { break; }
+        let e2 = 2 * err;
+        if e2 >= dy { err += dy; x += sx; }
+        if e2 <= dx { err += dx; y += sy; }
     }
+    Some(rows)
+}
+
+/// A brush or eraser drag. It stays in the room it started in, so it never paints across rooms.
+#[derive(Clone, Debug)]
+pub struct PaintStroke {
+    pub room_idx: usize,
+    /// Painted with the remove binding.
+    pub erase: bool,
+    /// First tile of the stroke; the Shift axis lock keeps to its row or column.
+    origin: (i32, i32),
+    last: (i32, i32),
+    /// The room before the stroke; the whole stroke becomes one undo entry when it ends.
+    before: Value,
+}
+
+/// Start a stroke at `pos`. With `from_last`, first draw a straight line from the last painted tile.
+pub fn begin_stroke(editor: &mut CelesteMapEditor, pos: Pos2, erase: bool, from_last: bool) {
+    if !focus_room_at(editor, pos) { return; }
+    let Some(tile) = room_local_tile(editor, pos) else { return };
+    let room_idx = editor.current_level_index;
+    let start = match editor.last_painted {
+        Some((room, last)) if from_last && room == room_idx => last,
+        _ => tile,
+    };
+    let Some(before) = editor.get_current_level_json().cloned() else { return };
+    stroke_line(editor, start, tile, if erase { '0' } else { editor.brush_tile });
+    editor.last_painted = Some((room_idx, tile));
+    editor.paint_stroke = Some(PaintStroke { room_idx, erase, origin: tile, last: tile, before });
+}
+
+/// Extend the stroke to `pos`, filling the gap since the last frame. `axis_lock` keeps the
+/// stroke on the dominant axis of the drag so far.
+pub fn continue_stroke(editor: &mut CelesteMapEditor, pos: Pos2, axis_lock: bool) {
+    let Some(stroke) = editor.paint_stroke.clone() else { return };
+    editor.current_level_index = stroke.room_idx;
+    let Some(level) = editor.get_current_level_json() else { return };
+    let (mut x, mut y) = level_local_tile(editor, level, pos);
+    if axis_lock {
+        let (ox, oy) = stroke.origin;
+        if (x - ox).abs() >= (y - oy).abs() { y = oy } else { x = ox }
+    }
+    if (x, y) == stroke.last { return; }
+    stroke_line(editor, stroke.last, (x, y), if stroke.erase { '0' } else { editor.brush_tile });
+    editor.last_painted = Some((stroke.room_idx, (x, y)));
+    if let Some(s) = &mut editor.paint_stroke {
+        s.last = (x, y);
+    }
+}
 
-    editor.update_solids_data(&rows.join("\n"));
+/// Finish the stroke and record it as one undo entry.
+pub fn end_stroke(editor: &mut CelesteMapEditor) {
+    let Some(stroke) = editor.paint_stroke.take() else { return };
+    let Some(after) = editor.get_level_mut(stroke.room_idx).map(|l| l.clone()) else { return };
+    if after == stroke.before { return; }
+    let description = if stroke.erase { "Erase tiles" } else { "Paint tiles" };
+    editor.history.push(EditCommand::LevelEdit {
+        room_idx: stroke.room_idx,
+        description: description.to_string(),
+        before: stroke.before,
+        after,
+    });
 }
@@ -0,0 +1,169 @@
+use eframe::egui::{Pos2, Rect, Vec2};
+use serde_json::Value;
+
+use crate::app::CelesteMapEditor;
+use crate::data::map_model::{CelestePixels, PIXELS_PER_TILE};
+use crate::map::editor::snap_pixels;
+use crate::map::history::{find_entity_mut, EditCommand};
+use crate::ui::transform::ViewTransform;
+
+// How far from an entity, in screen points, a click still picks it
+const PICK_DISTANCE: f32 = 10.0;
+
+/// An entity being dragged with the Entities tool.
+#[derive(Clone, Debug)]
+pub struct EntityDragState {
+    pub room_idx: usize,
+    pub entity_id: i64,
+    pub original_x: f64,
+    pub original_y: f64,
+    /// Offset from the pointer to the entity origin, in Celeste pixels, so it doesn't jump to the cursor.
+    grab_dx: f64,
+    grab_dy: f64,
+    /// Set when the far end of a spike strip or jumpthru was grabbed: the length attribute and its
+    /// value before the drag. The entity is stretched instead of moved.
+    resize: Option<(&'static str, f64)>,
+}
+
+/// The attribute spikes and jumpthrus are sized by; they only stretch along one axis.
+pub fn length_attribute(name: &str) -> Option<&'static str> {
+    match name {
+        "spikesUp" | "spikesDown" | "jumpThru" => Some("width"),
+        "spikesLeft" | "spikesRight" => Some("height"),
+        _ => None,
+    }
+}
+
+/// Entities of a level, as drawn by the entity layer.
+fn level_entities(level: &Value) -> impl Iterator<Item = &Value> {
+    level["__children"].as_array().into_iter().flatten()
+        .filter(|c| c["__name"] == "entities")
+        .flat_map(|c| c["__children"].as_array().into_iter().flatten())
+}
+
+/// The visible entity closest to `pos` within PICK_DISTANCE: (room index, entity id, x, y).
+pub fn find_entity_at(editor: &CelesteMapEditor, pos: Pos2) -> Option<(usize, i64, f64, f64)> {
+    let transform = ViewTransform::of(editor);
+    let scale = transform.scale();
+    let rooms: Vec<usize> = if editor.show_all_rooms {
+        (0..editor.cached_rooms.len()).collect()
+    } else {
+        vec![editor.current_level_index]
+    };
+    let mut best: Option<(f32, (usize, i64, f64, f64))> = None;
+    for idx in rooms {
+        let Some(room) = editor.cached_rooms.get(idx) else { continue };
+        let ld = &room.level_data;
+        for e in level_entities(&room.json) {
+            let Some(id) = e["id"].as_i64() else { continue };
+            let x = CelestePixels::attr(&e["x"]).0;
+            let y = CelestePixels::attr(&e["y"]).0;
+            let w = e["width"].as_f64().unwrap_or(8.0).max(1.0) as f32;
+            let h = e["height"].as_f64().unwrap_or(8.0).max(1.0) as f32;
+            let min = transform.world_to_screen(Pos2::new(ld.x + x as f32, ld.y + y as f32));
+            let rect = Rect::from_min_size(min, Vec2::new(w * scale, h * scale));
+            let dist = rect.distance_to_pos(pos);
+            if dist <= PICK_DISTANCE && best.map_or(true, |(d, _)| dist < d) {
+                best = Some((dist, (idx, id, x, y)));
+            }
+        }
+    }
+    best.map(|(_, hit)| hit)
+}
+
+pub fn begin_entity_drag(editor: &mut CelesteMapEditor, pos: Pos2) {
+    let Some((room_idx, entity_id, x, y)) = find_entity_at(editor, pos) else { return };
+    let Some((px, py)) = editor.cached_rooms.get(room_idx).map(|r| ViewTransform::of(editor).screen_to_room_pixels(&r.level_data, pos)) else { return };
+    let scale = ViewTransform::of(editor).scale() as f64;
+    let resize = editor.cached_rooms.get(room_idx)
+        .and_then(|r| level_entities(&r.json).find(|e| e["id"].as_i64() == Some(entity_id)))
+        .and_then(|e| {
+            let key = length_attribute(e["__name"].as_str()?)?;
+            let length = e[key].as_f64().unwrap_or(PIXELS_PER_TILE as f64);
+            let (start, pointer) = if key == "width" { (x, px) } else { (y, py) };
+            // Only the far end stretches; the rest of the entity still moves it
+            (((start + length - pointer).abs() * scale) <= PICK_DISTANCE as f64).then_some((key, length))
+        });
+    editor.current_level_index = room_idx;
+    editor.entity_drag = Some(EntityDragState {
+        room_idx,
+        entity_id,
+        original_x: x,
+        original_y: y,
+        grab_dx: x - px,
+        grab_dy: y - py,
+        resize,
+    });
+}
+
+/// Write entity attributes to both the map and the cached room used for drawing.
+fn write_attributes(editor: &mut CelesteMapEditor, room_idx: usize, entity_id: i64, attrs: &[(&str, i64)]) {
+    let set = |entity: &mut Value| {
+        for (key, value) in attrs {
+            entity[*key] = serde_json::json!(value);
+        }
+    };
+    if let Some(entity) = editor.get_level_mut(room_idx).and_then(|l| find_entity_mut(l, entity_id)) {
+        set(entity);
+    }
+    // Updating the cached copy too avoids recaching every room on each frame of the drag
+    if let Some(entity) = editor.cached_rooms.get_mut(room_idx).and_then(|r| find_entity_mut(&mut r.json, entity_id)) {
+        set(entity);
+    }
+}
+
+/// Map positions are whole pixels; written as integers so the attribute type survives a save.
+fn pixel_value(v: CelestePixels) -> i64 {
+    v.0.round() as i64
+}
+
+pub fn update_entity_drag(editor: &mut CelesteMapEditor, pos: Pos2) {
+    let Some(drag) = editor.entity_drag.clone() else { return };
+    let Some((px, py)) = editor.cached_rooms.get(drag.room_idx).map(|r| ViewTransform::of(editor).screen_to_room_pixels(&r.level_data, pos)) else { return };
+    if let Some((key, _)) = drag.resize {
+        // Lengths stay whole tiles, as the game tiles these in 8 px pieces
+        let (start, pointer) = if key == "width" { (drag.original_x, px) } else { (drag.original_y, py) };
+        let step = PIXELS_PER_TILE as f64;
+        let length = (((pointer - start) / step).round() * step).max(step);
+        write_attributes(editor, drag.room_idx, drag.entity_id, &[(key, length as i64)]);
+        return;
+    }
+    let x = pixel_value(snap_pixels(CelestePixels(px + drag.grab_dx), editor.entity_snap));
+    let y = pixel_value(snap_pixels(CelestePixels(py + drag.grab_dy), editor.entity_snap));
+    write_attributes(editor, drag.room_idx, drag.entity_id, &[("x", x), ("y", y)]);
+}
+
+/// Finish the drag and record it for undo if the entity moved.
+pub fn end_entity_drag(editor: &mut CelesteMapEditor) {
+    let Some(drag) = editor.entity_drag.take() else { return };
+    let Some(entity) = editor.cached_rooms.get(drag.room_idx)
+        .and_then(|r| level_entities(&r.json).find(|e| e["id"].as_i64() == Some(drag.entity_id)))
+    else { return };
+    let to = (CelestePixels::attr(&entity["x"]).0, CelestePixels::attr(&entity["y"]).0);
+    let entity_name = entity["__name"].as_str().unwrap_or("entity").to_string();
+    if let Some((key, original)) = drag.resize {
+        let new_val = entity[key].clone();
+        if new_val.as_f64() == Some(original) { return; }
+        editor.history.push(EditCommand::EntityPropertyEdit {
+            room_idx: drag.room_idx,
+            entity_id: drag.entity_id,
+            entity_name,
+            key: key.to_string(),
+            old_val: serde_json::json!(original as i64),
+            new_val,
+        });
+        editor.is_dirty = true;
+        editor.cache_rooms();
+        return;
+    }
+    if to == (drag.original_x, drag.original_y) { return; }
+    editor.history.push(EditCommand::EntityMove {
+        room_idx: drag.room_idx,
+        entity_id: drag.entity_id,
+        entity_name,
+        from: (drag.original_x, drag.original_y),
+        to,
+    });
+    editor.is_dirty = true;
+    editor.cache_rooms();
+}
@@ -0,0 +1,299 @@
+use eframe::egui::{Pos2, Rect, Vec2};
+use serde_json::Value;
+
+use crate::app::CelesteMapEditor;
+use crate::data::map_model::{CelestePixels, ID_LAYERS, PIXELS_PER_TILE};
+use crate::map::clipboard::{ClipboardObject, TileClipboard};
+use crate::map::editor::snap_pixels;
+use crate::map::entities::{begin_entity_drag, find_entity_at};
+use crate::map::history::EditCommand;
+use crate::map::rooms::edit_room;
+use crate::ui::render::{normalize_decal_path, LAYER_BG_DECALS, LAYER_ENTITIES, LAYER_FG_DECALS};
+use crate::ui::transform::ViewTransform;
+
+// Size assumed for a decal whose sprite isn't loaded, in Celeste pixels
+const DECAL_FALLBACK_SIZE: f64 = 16.0;
+
+/// An entity, trigger or decal, by room child name and position in that child's list.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SelectedObject {
+    pub layer: String,
+    pub index: usize,
+}
+
+/// Objects picked with the Entities tool. Always within one room.
+#[derive(Clone, Debug)]
+pub struct ObjectSelection {
+    pub room_idx: usize,
+    pub objects: Vec<SelectedObject>,
+}
+
+/// A selection being dragged: the pointer where it started and each object's start position,
+/// all in room-local Celeste pixels, plus the room before the drag for undo.
+#[derive(Clone, Debug)]
+pub struct GroupDrag {
+    grab: (f64, f64),
+    origins: Vec<(f64, f64)>,
+    before: Value,
+}
+
+/// Layers the rubber band picks from: the visible object layers.
+fn selectable_layers(editor: &CelesteMapEditor) -> Vec<&'static str> {
+    let mut layers = Vec::new();
+    if editor.layer_visibility[LAYER_ENTITIES] {
+        layers.extend(ID_LAYERS);
+    }
+    if editor.layer_visibility[LAYER_FG_DECALS] && editor.show_fgdecals {
+        layers.push("fgdecals");
+    }
+    if editor.layer_visibility[LAYER_BG_DECALS] {
+        layers.push("bgdecals");
+    }
+    layers
+}
+
+fn layer_objects<'a>(level: &'a Value, layer: &'a str) -> impl Iterator<Item = &'a Value> {
+    level["__children"].as_array().into_iter().flatten()
+        .filter(move |c| c["__name"] == layer)
+        .flat_map(|c| c["__children"].as_array().into_iter().flatten())
+}
+
+fn layer_objects_mut<'a>(level: &'a mut Value, layer: &'a str) -> Option<&'a mut Vec<Value>> {
+    level["__children"].as_array_mut()?
+        .iter_mut()
+        .find(|c| c["__name"] == layer)?["__children"]
+        .as_array_mut()
+}
+
+/// Room-local bounds of an object in Celeste pixels. Decals are centred on their position.
+fn object_bounds(editor: &CelesteMapEditor, layer: &str, obj: &Value) -> (f64, f64, f64, f64) {
+    let x = CelestePixels::attr(&obj["x"]).0;
+    let y = CelestePixels::attr(&obj["y"]).0;
+    if layer.ends_with("decals") {
+        let path = normalize_decal_path(obj["texture"].as_str().unwrap_or(""));
+        let (w, h) = editor.atlas_manager.as_ref()
+            .and_then(|am| am.get_sprite("Gameplay", &path))
+            .map(|s| (s.metadata.width as f64, s.metadata.height as f64))
+            .unwrap_or((DECAL_FALLBACK_SIZE, DECAL_FALLBACK_SIZE));
+        let w = w * obj["scaleX"].as_f64().unwrap_or(1.0).abs();
+        let h = h * obj["scaleY"].as_f64().unwrap_or(1.0).abs();
+        return (x - w / 2.0, y - h / 2.0, w, h);
+    }
+    let w = obj["width"].as_f64().unwrap_or(8.0).max(1.0);
+    let h = obj["height"].as_f64().unwrap_or(8.0).max(1.0);
+    (x, y, w, h)
+}
+
+/// Screen rectangle of each selected object, for drawing.
+pub fn selection_rects(editor: &CelesteMapEditor) -> Vec<Rect> {
+    let Some(sel) = &editor.object_selection else { return Vec::new() };
+    let Some(room) = editor.cached_rooms.get(sel.room_idx) else { return Vec::new() };
+    let transform = ViewTransform::of(editor);
+    let ld = &room.level_data;
+    sel.objects.iter().filter_map(|o| {
+        let obj = layer_objects(&room.json, &o.layer).nth(o.index)?;
+        let (x, y, w, h) = object_bounds(editor, &o.layer, obj);
+        let min = transform.world_to_screen(Pos2::new(ld.x + x as f32, ld.y + y as f32));
+        Some(Rect::from_min_size(min, Vec2::new(w as f32, h as f32) * transform.scale()))
+    }).collect()
+}
+
+/// Press with the Entities tool: drag the selection if the press is on it, otherwise drag the
+/// entity under the pointer, otherwise start a rubber band.
+pub fn begin_object_press(editor: &mut CelesteMapEditor, pos: Pos2) {
+    let hit = find_entity_at(editor, pos);
+    let count = editor.object_selection.as_ref().map_or(0, |s| s.objects.len());
+    // A lone selected entity goes through the single drag so its far end can still be stretched
+    let on_selection = (count > 1 || (count == 1 && hit.is_none()))
+        && selection_rects(editor).iter().any(|r| r.expand(2.0).contains(pos));
+    if on_selection {
+        begin_group_drag(editor, pos);
+        return;
+    }
+    editor.object_selection = None;
+    let Some((room_idx, entity_id, _, _)) = hit else {
+        editor.selection_band = Some((pos, pos));
+        return;
+    };
+    begin_entity_drag(editor, pos);
+    let index = editor.cached_rooms.get(room_idx)
+        .and_then(|r| layer_objects(&r.json, "entities").position(|e| e["id"].as_i64() == Some(entity_id)));
+    if let Some(index) = index {
+        editor.object_selection = Some(ObjectSelection {
+            room_idx,
+            objects: vec![SelectedObject { layer: "entities".to_string(), index }],
+        });
+    }
+}
+
+fn begin_group_drag(editor: &mut CelesteMapEditor, pos: Pos2) {
+    let Some(sel) = editor.object_selection.clone() else { return };
+    let Some(grab) = editor.cached_rooms.get(sel.room_idx).map(|r| ViewTransform::of(editor).screen_to_room_pixels(&r.level_data, pos)) else { return };
+    let Some(level) = editor.cached_rooms.get(sel.room_idx).map(|r| r.json.clone()) else { return };
+    let origins = sel.objects.iter().map(|o| {
+        layer_objects(&level, &o.layer).nth(o.index)
+            .map_or((0.0, 0.0), |obj| (CelestePixels::attr(&obj["x"]).0, CelestePixels::attr(&obj["y"]).0))
+    }).collect();
+    let Some(before) = editor.get_level_mut(sel.room_idx).map(|l| l.clone()) else { return };
+    editor.current_level_index = sel.room_idx;
+    editor.group_drag = Some(GroupDrag { grab, origins, before });
+}
+
+/// Write object positions into both the map and the cached room used for drawing.
+fn write_positions(editor: &mut CelesteMapEditor, room_idx: usize, objects: &[SelectedObject], positions: &[(f64, f64)]) {
+    let set = |level: &mut Value| {
+        for (o, (x, y)) in objects.iter().zip(positions) {
+            let Some(obj) = layer_objects_mut(level, &o.layer).and_then(|v| v.get_mut(o.index)) else { continue };
+            obj["x"] = pixel_json(*x);
+            obj["y"] = pixel_json(*y);
+        }
+    };
+    if let Some(level) = editor.get_level_mut(room_idx) {
+        set(level);
+    }
+    if let Some(room) = editor.cached_rooms.get_mut(room_idx) {
+        set(&mut room.json);
+    }
+}
+
+fn pixel_json(value: f64) -> Value {
+    if value.fract() == 0.0 { serde_json::json!(value as i64) } else { serde_json::json!(value) }
+}
+
+/// Drag with the Entities tool held: move the group or stretch the rubber band.
+pub fn update_object_drag(editor: &mut CelesteMapEditor, pos: Pos2) {
+    if let Some((start, _)) = editor.selection_band {
+        editor.selection_band = Some((start, pos));
+        return;
+    }
+    let (Some(drag), Some(sel)) = (editor.group_drag.clone(), editor.object_selection.clone()) else { return };
+    let Some((px, py)) = editor.cached_rooms.get(sel.room_idx).map(|r| ViewTransform::of(editor).screen_to_room_pixels(&r.level_data, pos)) else { return };
+    // Snap the offset, not each object, so the group keeps its shape
+    let (dx, dy) = if sel.objects.iter().all(|o| o.layer.ends_with("decals")) {
+        // Decals land on the decal grid: the first one snaps and the rest follow it
+        let grid = (editor.decal_snap_grid > 0).then_some(editor.decal_snap_grid as u32);
+        let (ox, oy) = drag.origins.first().copied().unwrap_or_default();
+        (
+            snap_pixels(CelestePixels(ox + px - drag.grab.0), grid).0.round() - ox,
+            snap_pixels(CelestePixels(oy + py - drag.grab.1), grid).0.round() - oy,
+        )
+    } else {
+        (
+            snap_pixels(CelestePixels(px - drag.grab.0), editor.entity_snap).0.round(),
+            snap_pixels(CelestePixels(py - drag.grab.1), editor.entity_snap).0.round(),
+        )
+    };
+    let positions: Vec<(f64, f64)> = drag.origins.iter().map(|(x, y)| (x + dx, y + dy)).collect();
+    write_positions(editor, sel.room_idx, &sel.objects, &positions);
+}
+
+/// Release: finish a group move as one undo entry, or select what the rubber band covers.
+pub fn end_object_drag(editor: &mut CelesteMapEditor) {
+    if let Some(drag) = editor.group_drag.take() {
+        let Some(sel) = &editor.object_selection else { return };
+        let room_idx = sel.room_idx;
+        let count = sel.objects.len();
+        let Some(after) = editor.get_level_mut(room_idx).map(|l| l.clone()) else { return };
+        if after != drag.before {
+            editor.history.push(EditCommand::LevelEdit {
+                room_idx,
+                description: format!("Move {} objects", count),
+                before: drag.before,
+                after,
+            });
+            editor.is_dirty = true;
+            editor.cache_rooms();
+        }
+        return;
+    }
+    let Some((start, end)) = editor.selection_band.take() else { return };
+    select_in_band(editor, Rect::from_two_pos(start, end));
+}
+
+fn select_in_band(editor: &mut CelesteMapEditor, band: Rect) {
+    let transform = ViewTransform::of(editor);
+    let layers = selectable_layers(editor);
+    let rooms: Vec<usize> = if editor.show_all_rooms {
+        (0..editor.cached_rooms.len()).collect()
+    } else {
+        vec![editor.current_level_index]
+    };
+    let mut found: Vec<(usize, SelectedObject)> = Vec::new();
+    for idx in rooms {
+        let Some(room) = editor.cached_rooms.get(idx) else { continue };
+        let ld = &room.level_data;
+        for layer in &layers {
+            for (index, obj) in layer_objects(&room.json, layer).enumerate() {
+                let (x, y, w, h) = object_bounds(editor, layer, obj);
+                let min = transform.world_to_screen(Pos2::new(ld.x + x as f32, ld.y + y as f32));
+                if band.intersects(Rect::from_min_size(min, Vec2::new(w as f32, h as f32) * transform.scale())) {
+                    found.push((idx, SelectedObject { layer: layer.to_string(), index }));
+                }
+            }
+        }
+    }
+    let Some(&(room_idx, _)) = found.first() else { return };
+    if found.iter().any(|(idx, _)| *idx != room_idx) {
+        editor.notifications.warn("The selection spans several rooms; select objects in one room at a time");
+        return;
+    }
+    editor.current_level_index = room_idx;
+    editor.object_selection = Some(ObjectSelection { room_idx, objects: found.into_iter().map(|(_, o)| o).collect() });
+}
+
+/// Move the selection by whole pixels, as one undo entry.
+pub fn nudge_selection(editor: &mut CelesteMapEditor, dx: f64, dy: f64) {
+    let Some(sel) = editor.object_selection.clone() else { return };
+    edit_room(editor, sel.room_idx, "Nudge objects", |level| {
+        for o in &sel.objects {
+            let Some(obj) = layer_objects_mut(level, &o.layer).and_then(|v| v.get_mut(o.index)) else { continue };
+            let x = CelestePixels::attr(&obj["x"]).0 + dx;
+            let y = CelestePixels::attr(&obj["y"]).0 + dy;
+            obj["x"] = pixel_json(x);
+            obj["y"] = pixel_json(y);
+        }
+    });
+}
+
+pub fn delete_selection(editor: &mut CelesteMapEditor) {
+    let Some(sel) = editor.object_selection.take() else { return };
+    let mut objects = sel.objects.clone();
+    // Highest index first so earlier removals don't shift later ones
+    objects.sort_by(|a, b| b.index.cmp(&a.index));
+    edit_room(editor, sel.room_idx, &format!("Delete {} objects", objects.len()), |level| {
+        for o in &objects {
+            if let Some(list) = layer_objects_mut(level, &o.layer) {
+                if o.index < list.len() {
+                    list.remove(o.index);
+                }
+            }
+        }
+    });
+}
+
+/// Copy the selection into the clipboard, positioned relative to the tile holding its top-left object.
+pub fn copy_object_selection(editor: &mut CelesteMapEditor) {
+    let Some(sel) = &editor.object_selection else { return };
+    let Some(room) = editor.cached_rooms.get(sel.room_idx) else { return };
+    let picked: Vec<(&str, &Value)> = sel.objects.iter()
+        .filter_map(|o| Some((o.layer.as_str(), layer_objects(&room.json, &o.layer).nth(o.index)?)))
+        .collect();
+    let step = PIXELS_PER_TILE as f64;
+    let min = |key: &str| picked.iter().map(|(_, o)| CelestePixels::attr(&o[key]).0).fold(f64::INFINITY, f64::min);
+    let (origin_x, origin_y) = ((min("x") / step).floor() * step, (min("y") / step).floor() * step);
+    let objects = picked.iter().map(|(layer, obj)| {
+        let mut data = (*obj).clone();
+        data["x"] = pixel_json(CelestePixels::attr(&obj["x"]).0 - origin_x);
+        data["y"] = pixel_json(CelestePixels::attr(&obj["y"]).0 - origin_y);
+        ClipboardObject { layer: layer.to_string(), data }
+    }).collect::<Vec<_>>();
+    editor.notifications.info(format!("Copied {} objects", objects.len()));
+    editor.clipboard = Some(TileClipboard { tiles: Vec::new(), objects });
+}
+
+/// Forget the selection. Its indices go stale whenever the room's object lists change elsewhere.
+pub fn clear_object_selection(editor: &mut CelesteMapEditor) {
+    editor.object_selection = None;
+    editor.group_drag = None;
+    editor.selection_band = None;
+}
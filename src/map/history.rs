@@ -0,0 +1,248 @@
+use serde_json::Value;
+use std::time::Instant;
+
+use crate::app::CelesteMapEditor;
+use crate::data::map_model;
+use crate::map::entity_selection::clear_object_selection;
+
+// Oldest entries are dropped past this many
+const MAX_HISTORY: usize = 200;
+
+/// One undoable edit.
+#[derive(Clone, Debug)]
+pub enum EditCommand {
+    /// A change anywhere in a room, stored as the level JSON before and after.
+    LevelEdit {
+        room_idx: usize,
+        description: String,
+        before: Value,
+        after: Value,
+    },
+    /// A single entity or trigger attribute change.
+    EntityPropertyEdit {
+        room_idx: usize,
+        entity_id: i64,
+        entity_name: String,
+        key: String,
+        old_val: Value,
+        new_val: Value,
+    },
+    /// An entity dragged to a new position, in room-local Celeste pixels.
+    EntityMove {
+        room_idx: usize,
+        entity_id: i64,
+        entity_name: String,
+        from: (f64, f64),
+        to: (f64, f64),
+    },
+    /// A room inserted at `room_idx` of the level list; undoing removes it again.
+    RoomAdd {
+        room_idx: usize,
+        description: String,
+        level: Value,
+    },
+}
+
+/// Strings without quotes, everything else as JSON.
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => "nothing".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Whole pixel values as JSON integers, so attribute types survive a save.
+fn pixel_json(value: f64) -> Value {
+    if value.fract() == 0.0 { serde_json::json!(value as i64) } else { serde_json::json!(value) }
+}
+
+impl EditCommand {
+    pub fn room_idx(&self) -> usize {
+        match self {
+            EditCommand::LevelEdit { room_idx, .. }
+            | EditCommand::EntityPropertyEdit { room_idx, .. }
+            | EditCommand::EntityMove { room_idx, .. }
+            | EditCommand::RoomAdd { room_idx, .. } => *room_idx,
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            EditCommand::LevelEdit { description, .. } | EditCommand::RoomAdd { description, .. } => description.clone(),
+            EditCommand::EntityPropertyEdit { entity_name, key, old_val, new_val, .. } => format!(
+                "Changed {}.{} from {} to {}",
+                entity_name, key, display_value(old_val), display_value(new_val)
+            ),
+            EditCommand::EntityMove { entity_name, from, to, .. } => format!(
+                "Moved {} from ({}, {}) to ({}, {})",
+                entity_name, from.0, from.1, to.0, to.1
+            ),
+        }
+    }
+
+    /// Write the after state (`redo`) or the before state (undo) into the map, without
+    /// refreshing the cached rooms; see `applied`.
+    fn write(&self, editor: &mut CelesteMapEditor, redo: bool) {
+        if let EditCommand::RoomAdd { room_idx, level, .. } = self {
+            let Some(levels) = editor.map_data.as_mut().and_then(map_model::levels_mut) else { return };
+            if redo {
+                levels.insert((*room_idx).min(levels.len()), level.clone());
+            } else if *room_idx < levels.len() {
+                levels.remove(*room_idx);
+            }
+            return;
+        }
+        let Some(level) = editor.get_level_mut(self.room_idx()) else { return };
+        match self {
+            EditCommand::LevelEdit { before, after, .. } => {
+                *level = if redo { after.clone() } else { before.clone() };
+            }
+            EditCommand::EntityPropertyEdit { entity_id, key, old_val, new_val, .. } => {
+                if let Some(entity) = find_entity_mut(level, *entity_id) {
+                    entity[key.as_str()] = if redo { new_val.clone() } else { old_val.clone() };
+                }
+            }
+            EditCommand::EntityMove { entity_id, from, to, .. } => {
+                if let Some(entity) = find_entity_mut(level, *entity_id) {
+                    let (x, y) = if redo { *to } else { *from };
+                    entity["x"] = pixel_json(x);
+                    entity["y"] = pixel_json(y);
+                }
+            }
+            EditCommand::RoomAdd { .. } => {}
+        }
+    }
+}
+
+/// Refresh the editor after commands were written, showing room `room_idx`, the last one touched.
+/// `rooms_changed` when rooms were added or removed, so the names are read again.
+fn applied(editor: &mut CelesteMapEditor, room_idx: usize, rooms_changed: bool) {
+    if rooms_changed {
+        editor.extract_level_names();
+    }
+    // An undone room addition leaves its index past the end
+    editor.current_level_index = room_idx.min(editor.level_names.len().saturating_sub(1));
+    clear_object_selection(editor);
+    editor.cache_rooms();
+    editor.is_dirty = true;
+}
+
+/// Entity or trigger with the given id in a level.
+pub fn find_entity_mut(level: &mut Value, id: i64) -> Option<&mut Value> {
+    level["__children"].as_array_mut()?
+        .iter_mut()
+        .filter(|c| c["__name"].as_str().map_or(false, |n| map_model::ID_LAYERS.contains(&n)))
+        .flat_map(|c| c["__children"].as_array_mut().into_iter().flatten())
+        .find(|e| e["id"].as_i64() == Some(id))
+}
+
+/// A command in the history and when it was last changed.
+#[derive(Clone, Debug)]
+pub struct HistoryEntry {
+    pub command: EditCommand,
+    pub time: Instant,
+}
+
+/// Undo stack; entries before `position` are applied, the rest can be redone.
+#[derive(Default)]
+pub struct EditHistory {
+    pub entries: Vec<HistoryEntry>,
+    pub position: usize,
+}
+
+impl EditHistory {
+    pub fn push(&mut self, command: EditCommand) {
+        self.entries.truncate(self.position);
+        // Typing into a field or dragging a value edits the same attribute every frame; keep one entry
+        if let Some(last) = self.entries.last_mut() {
+            if let (
+                EditCommand::EntityPropertyEdit { room_idx, entity_id, key, new_val, .. },
+                EditCommand::EntityPropertyEdit { room_idx: r, entity_id: e, key: k, new_val: v, .. },
+            ) = (&mut last.command, &command) {
+                if room_idx == r && entity_id == e && key == k {
+                    *new_val = v.clone();
+                    last.time = Instant::now();
+                    return;
+                }
+            }
+        }
+        self.entries.push(HistoryEntry { command, time: Instant::now() });
+        if self.entries.len() > MAX_HISTORY {
+            self.entries.remove(0);
+        }
+        self.position = self.entries.len();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.position > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.position < self.entries.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.position = 0;
+    }
+}
+
+pub fn undo(editor: &mut CelesteMapEditor) {
+    if editor.history.can_undo() {
+        jump_to(editor, editor.history.position - 1);
+    }
+}
+
+pub fn redo(editor: &mut CelesteMapEditor) {
+    if editor.history.can_redo() {
+        jump_to(editor, editor.history.position + 1);
+    }
+}
+
+/// Undo or redo until `position` entries are applied. The rooms are re-cached once at the end,
+/// not per step, so jumping far back stays quick.
+pub fn jump_to(editor: &mut CelesteMapEditor, position: usize) {
+    let position = position.min(editor.history.entries.len());
+    let from = editor.history.position;
+    if position == from { return; }
+    // Writing commands never touches the history, so it can be moved out instead of cloned
+    let entries = std::mem::take(&mut editor.history.entries);
+    let crossed = &entries[position.min(from)..position.max(from)];
+    let rooms_changed = crossed.iter().any(|e| matches!(e.command, EditCommand::RoomAdd { .. }));
+    if position < from {
+        for entry in entries[position..from].iter().rev() {
+            entry.command.write(editor, false);
+        }
+    } else {
+        for entry in &entries[from..position] {
+            entry.command.write(editor, true);
+        }
+    }
+    // The room of the step crossed last
+    let last = if position < from { position } else { position - 1 };
+    let room_idx = entries[last].command.room_idx();
+    editor.history.entries = entries;
+    editor.history.position = position;
+    applied(editor, room_idx, rooms_changed);
+}
+
+/// Set an attribute on an entity of room `room_idx` and record it for undo.
+pub fn set_entity_property(editor: &mut CelesteMapEditor, room_idx: usize, entity_id: i64, key: &str, value: Value) {
+    let Some(level) = editor.get_level_mut(room_idx) else { return };
+    let Some(entity) = find_entity_mut(level, entity_id) else { return };
+    let old_val = entity[key].clone();
+    if old_val == value { return; }
+    let entity_name = entity["__name"].as_str().unwrap_or("entity").to_string();
+    entity[key] = value.clone();
+    editor.history.push(EditCommand::EntityPropertyEdit {
+        room_idx,
+        entity_id,
+        entity_name,
+        key: key.to_string(),
+        old_val,
+        new_val: value,
+    });
+    editor.cache_rooms();
+    editor.is_dirty = true;
+}
@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+
+use image::RgbaImage;
+use serde_json::json;
+
+use crate::app::CelesteMapEditor;
+use crate::data::map_model::PIXELS_PER_TILE;
+use crate::map::loader::blank_room_json;
+use crate::map::rooms::{add_room, edit_room};
+use crate::map::validation::trim_level;
+
+/// Largest room an image is turned into, in tiles per side; bigger images are cropped.
+pub const MAX_IMPORT_TILES: u32 = 300;
+/// Image pixels per tile offered in the import dialog.
+pub const IMPORT_SCALES: [u32; 4] = [1, 2, 4, 8];
+pub const DEFAULT_IMPORT_THRESHOLD: u8 = 128;
+
+/// An image being turned into room solids, waiting in the import dialog.
+pub struct ImageImport {
+    pub path: PathBuf,
+    image: RgbaImage,
+    /// Tiles whose pixels average darker than this are solid.
+    pub threshold: u8,
+    /// Image pixels per tile side.
+    pub scale: u32,
+    /// Replace the current room's solids instead of adding a room.
+    pub replace_current: bool,
+    pub room_name: String,
+    /// Solids for the current settings, rebuilt by `refresh`.
+    pub solids: Vec<String>,
+    /// The image was larger than `MAX_IMPORT_TILES` and got cropped.
+    pub cropped: bool,
+}
+
+impl ImageImport {
+    /// Read the image at `path`, placing `tile` for dark cells.
+    pub fn open(path: &Path, tile: char) -> Result<Self, String> {
+        let image = image::open(path).map_err(|e| e.to_string())?.to_rgba8();
+        let room_name = path.file_stem().map_or("imported".to_string(), |s| s.to_string_lossy().to_string());
+        let mut import = Self {
+            path: path.to_path_buf(),
+            image,
+            threshold: DEFAULT_IMPORT_THRESHOLD,
+            scale: 1,
+            replace_current: false,
+            room_name,
+            solids: Vec::new(),
+            cropped: false,
+        };
+        import.refresh(tile);
+        Ok(import)
+    }
+
+    /// Rebuild `solids` after a setting changed, placing `tile` for dark cells.
+    pub fn refresh(&mut self, tile: char) {
+        let (solids, cropped) = image_to_solids(&self.image, self.scale, self.threshold, tile);
+        self.solids = solids;
+        self.cropped = cropped;
+    }
+
+    /// Room size in tiles.
+    pub fn size(&self) -> (u32, u32) {
+        (self.solids.first().map_or(0, |r| r.chars().count()) as u32, self.solids.len() as u32)
+    }
+}
+
+/// Perceived brightness of a pixel; transparent pixels count as white.
+fn brightness(pixel: &image::Rgba<u8>) -> f32 {
+    let [r, g, b, a] = pixel.0;
+    let lum = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+    let alpha = a as f32 / 255.0;
+    lum * alpha + 255.0 * (1.0 - alpha)
+}
+
+/// Solids rows for `image`: each `scale` x `scale` block of pixels becomes one tile, `tile` when
+/// its average brightness is below `threshold` and air otherwise. Returns the rows and whether
+/// the image was cropped to `MAX_IMPORT_TILES`.
+pub fn image_to_solids(image: &RgbaImage, scale: u32, threshold: u8, tile: char) -> (Vec<String>, bool) {
+    let scale = scale.max(1);
+    let full = ((image.width() + scale - 1) / scale, (image.height() + scale - 1) / scale);
+    let (cols, rows) = (full.0.min(MAX_IMPORT_TILES), full.1.min(MAX_IMPORT_TILES));
+    let solids = (0..rows).map(|ty| (0..cols).map(|tx| {
+        let (x0, y0) = (tx * scale, ty * scale);
+        let (x1, y1) = ((x0 + scale).min(image.width()), (y0 + scale).min(image.height()));
+        let mut sum = 0.0;
+        for y in y0..y1 {
+            for x in x0..x1 {
+                sum += brightness(image.get_pixel(x, y));
+            }
+        }
+        let average = sum / ((x1 - x0) * (y1 - y0)) as f32;
+        if average < threshold as f32 { tile } else { '0' }
+    }).collect()).collect();
+    (solids, (cols, rows) != full)
+}
+
+/// Commit the import as one undoable edit: a new room, or the current room's solids and size.
+pub fn apply_image_import(editor: &mut CelesteMapEditor, import: &ImageImport) -> Result<String, String> {
+    let (width, height) = import.size();
+    if width == 0 || height == 0 {
+        return Err("The image is empty".to_string());
+    }
+    let text = import.solids.join("\n");
+    if import.replace_current {
+        let idx = editor.current_level_index;
+        let name = editor.level_names.get(idx).cloned().ok_or("No room is selected")?;
+        edit_room(editor, idx, "Import solids from image", |level| {
+            level["width"] = json!(width as i64 * PIXELS_PER_TILE as i64);
+            level["height"] = json!(height as i64 * PIXELS_PER_TILE as i64);
+            if let Some(solids) = level["__children"].as_array_mut().and_then(|c| c.iter_mut().find(|c| c["__name"] == "solids")) {
+                solids["innerText"] = json!(text);
+            }
+            // Other grids may now reach past a smaller room
+            trim_level(level);
+        });
+        return Ok(name);
+    }
+    let mut level = blank_room_json(import.room_name.trim(), width, height);
+    if let Some(solids) = level["__children"].as_array_mut().and_then(|c| c.iter_mut().find(|c| c["__name"] == "solids")) {
+        solids["innerText"] = json!(text);
+    }
+    let idx = add_room(editor, &level, "Import room from image")?;
+    Ok(editor.level_names.get(idx).cloned().unwrap_or_default())
+}
+
+/// Ask for an image and open the import dialog on it.
+pub fn pick_import_image(editor: &mut CelesteMapEditor) {
+    if editor.map_data.is_none() { return; }
+    let Some(path) = rfd::FileDialog::new()
+        .set_title("Import Room from Image")
+        .add_filter("PNG image", &["png"])
+        .pick_file() else { return };
+    match ImageImport::open(&path, editor.brush_tile) {
+        Ok(import) => editor.image_import = Some(import),
+        Err(e) => editor.notifications.error(format!("Could not read {}: {}", path.display(), e)),
+    }
+}
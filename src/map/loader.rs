@@ -1,13 +1,41 @@
 use cairn::{bin_to_json, json_to_bin};
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{self, BufReader};
 use std::path::Path;
-use std::io::Write;
+use std::time::SystemTime;
 use eframe::egui::Vec2;
+use serde_json::{json, Value};
+use thiserror::Error;
 use rfd;
-use log::{debug, info, warn};
+use log::info;
 
 use crate::app::CelesteMapEditor;
+use crate::app::tabs::remember_view;
+use crate::config::recent::{add_recent_map, save_last_mod};
+use crate::data::map_model::Entity;
+use crate::data::mods::{extract_zipped_map, mod_of_map, ModMap};
+use crate::map::changes::take_snapshot;
+use crate::map::entity_selection::clear_object_selection;
+use crate::map::meta::load_meta;
+use crate::ui::input::fit_map_in_view;
+use crate::ui::transform::{MAX_ZOOM, MIN_ZOOM};
+
+const CELESTE_TILE_PX: u32 = 8;
+/// Name and size (in tiles) of the room created for a new map; one vanilla screen.
+pub const NEW_ROOM_NAME: &str = "lvl_a-00";
+pub const NEW_ROOM_WIDTH: u32 = 40;
+pub const NEW_ROOM_HEIGHT: u32 = 23;
+
+/// Pick up the map's `.meta.yaml` sidecar, if it has one.
+fn load_map_meta(editor: &mut CelesteMapEditor, bin_path: &str) {
+    editor.map_meta = match load_meta(bin_path) {
+        Ok(meta) => meta,
+        Err(e) => {
+            editor.notifications.warn(e);
+            None
+        }
+    };
+}
 
 /// Get a temporary JSON path for a given binary map file
 pub fn get_temp_json_path(bin_path: &str) -> String {
@@ -17,113 +45,279 @@ pub fn get_temp_json_path(bin_path: &str) -> String {
     temp_dir.join(format!("{}_temp.json", stem)).to_string_lossy().to_string()
 }
 
-pub fn load_map(editor: &mut CelesteMapEditor, bin_path: &str) {
-    let temp_json_path = get_temp_json_path(bin_path);
+/// Why a .bin could not be read.
+#[derive(Debug, Error)]
+pub enum LoadError {
+    #[error("Cairn failed: {0}")]
+    Cairn(String),
+    #[error("Failed to open converted JSON file: {0}")]
+    Io(#[from] io::Error),
+    #[error("Failed to parse JSON: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// Why a map could not be written.
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error("Failed to serialize map data: {0}")]
+    Serialize(#[from] serde_json::Error),
+    #[error("Failed to write temporary JSON file: {0}")]
+    Io(#[from] io::Error),
+    #[error("Failed to convert JSON to BIN: {0}")]
+    Cairn(String),
+}
+
+/// A map read from disk, as Cairn JSON.
+#[derive(Clone, Debug)]
+pub struct LoadedMap {
+    pub data: Value,
+    /// Last-modified time of the .bin when it was read.
+    pub modified: Option<SystemTime>,
+}
+
+/// Read a .bin map through Cairn. Needs no editor, so tools and tests can use it directly.
+pub fn load_map_file(bin_path: impl AsRef<Path>) -> Result<LoadedMap, LoadError> {
+    let bin_path = bin_path.as_ref().to_string_lossy().to_string();
+    let temp_json_path = get_temp_json_path(&bin_path);
     info!("Loading map: {}", bin_path);
     info!("Temp JSON path: {}", temp_json_path);
+    bin_to_json(&bin_path, &temp_json_path).map_err(|e| LoadError::Cairn(e.to_string()))?;
+    let data = serde_json::from_reader(BufReader::new(File::open(&temp_json_path)?))?;
+    Ok(LoadedMap { data, modified: bin_modified(&bin_path) })
+}
 
-    // Convert BIN to JSON using Cairn library
-    match bin_to_json(bin_path, &temp_json_path) {
-        Ok(_) => {
-            info!("Successfully converted bin to json");
-            if let Ok(file) = File::open(&temp_json_path) {
-                let reader = BufReader::new(file);
-                match serde_json::from_reader(reader) {
-                    Ok(data) => {
-                        info!("Successfully parsed JSON data");
-                        editor.map_data = Some(data);
-                        editor.extract_level_names();
-                        editor.cache_rooms();
-                        editor.static_dirty = true;
-                        editor.bin_path = Some(bin_path.to_string());
-                        editor.temp_json_path = Some(temp_json_path);
-
-                        // Debug the map structure
-                        editor.debug_map_structure();
-
-                        // Reset current level to the first one
-                        editor.current_level_index = 0;
-
-                        // Reset camera position
-                        editor.camera_pos = Vec2::new(0.0, 0.0);
-
-                        info!("Map loaded successfully with {} levels", editor.level_names.len());
-                        editor.error_message = None;
-                    }
-                    Err(e) => {
-                        warn!("Failed to parse JSON: {}", e);
-                        editor.error_message = Some(format!("Failed to parse JSON: {}", e));
-                    }
-                }
-            } else {
-                warn!("Failed to open converted JSON file");
-                editor.error_message = Some("Failed to open converted JSON file.".to_string());
-            }
-        }
-        Err(e) => {
-            warn!("Cairn conversion failed: {}", e);
-            editor.error_message = Some(format!("Cairn failed: {}", e));
+/// Write Cairn JSON `map` to a .bin, replacing the file.
+pub fn save_map_file(map: &Value, bin_path: impl AsRef<Path>) -> Result<(), SaveError> {
+    let bin_path = bin_path.as_ref().to_string_lossy().to_string();
+    let temp_json_path = get_temp_json_path(&bin_path);
+    std::fs::write(&temp_json_path, serde_json::to_string_pretty(map)?)?;
+    json_to_bin(&temp_json_path, &bin_path).map_err(|e| SaveError::Cairn(e.to_string()))
+}
+
+pub fn load_map(editor: &mut CelesteMapEditor, bin_path: &str) {
+    match load_map_file(bin_path) {
+        Ok(loaded) => show_loaded_map(editor, loaded, Some(bin_path)),
+        Err(e) => editor.notifications.error(e.to_string()),
+    }
+}
+
+/// Open a map from the Open dialog's mod list. A zipped map can't be written back into its zip,
+/// so it opens without a file, like a new map; Save As gives it one.
+pub fn load_mod_map(editor: &mut CelesteMapEditor, mod_name: &str, map: &ModMap) {
+    if map.zip_entry.is_none() {
+        load_map(editor, &map.path.display().to_string());
+        return;
+    }
+    let loaded = extract_zipped_map(map).and_then(|copy| load_map_file(copy).map_err(|e| e.to_string()));
+    match loaded {
+        Ok(loaded) => {
+            show_loaded_map(editor, LoadedMap { modified: None, ..loaded }, None);
+            remember_mod(editor, mod_name);
+            editor.notifications.info(format!("{} is zipped and opened read-only; use Save As to keep changes", map.name));
         }
+        Err(e) => editor.notifications.error(format!("Could not open {} from {}: {}", map.name, map.path.display(), e)),
     }
 }
 
-pub fn save_map(editor: &CelesteMapEditor) {
-    if let (Some(map_data), Some(bin_path), Some(temp_json_path)) = (&editor.map_data, &editor.bin_path, &editor.temp_json_path) {
-        // Save the JSON to a temporary file
-        match serde_json::to_string_pretty(map_data) {
-            Ok(json_str) => {
-                if let Err(e) = File::create(&temp_json_path).and_then(|mut file| file.write_all(json_str.as_bytes())) {
-                    if cfg!(debug_assertions) {
-                        debug!("Failed to write temporary JSON file: {}", e);
-                    }
-                    return;
-                }
-
-                // Convert JSON to BIN using Cairn Rust library
-                match json_to_bin(&temp_json_path, &bin_path) {
-                    Ok(_) => info!("Map saved successfully to {}", bin_path),
-                    Err(e) => {
-                        if cfg!(debug_assertions) {
-                            debug!("Failed to convert JSON to BIN: {}", e);
-                        }
-                    },
-                }
-            }
-            Err(e) => {
-                if cfg!(debug_assertions) {
-                    debug!("Failed to serialize map data: {}", e);
-                }
+/// Remember `mod_name` as the mod a map was last opened from.
+fn remember_mod(editor: &mut CelesteMapEditor, mod_name: &str) {
+    if editor.last_mod.as_deref() == Some(mod_name) { return; }
+    save_last_mod(mod_name);
+    editor.last_mod = Some(mod_name.to_string());
+}
+
+/// Make a freshly read map the open one. `bin_path` is None for a map that has no file to save to.
+fn show_loaded_map(editor: &mut CelesteMapEditor, loaded: LoadedMap, bin_path: Option<&str>) {
+    remember_view(editor);
+    editor.map_data = Some(loaded.data);
+    editor.history.clear();
+    editor.is_dirty = false;
+    take_snapshot(editor);
+    editor.bin_modified = loaded.modified;
+    match bin_path {
+        Some(path) => load_map_meta(editor, path),
+        None => editor.map_meta = None,
+    }
+    editor.extract_level_names();
+    editor.cache_rooms();
+    editor.bin_path = bin_path.map(str::to_string);
+
+    // Debug the map structure
+    editor.debug_map_structure();
+
+    // Come back to where this map was left, or frame it if it was never opened
+    editor.camera_animation = None;
+    match bin_path.and_then(|path| editor.last_views.get(path).copied()) {
+        Some(view) => {
+            // The remembered room may have been deleted or renamed away since
+            editor.current_level_index = if view.room < editor.level_names.len() { view.room } else { 0 };
+            editor.camera_pos = Vec2::new(view.camera_x, view.camera_y);
+            editor.zoom_level = view.zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+            editor.show_all_rooms = view.show_all_rooms;
+        }
+        None => {
+            editor.current_level_index = 0;
+            editor.camera_pos = Vec2::new(0.0, 0.0);
+            if let Some(canvas) = editor.canvas_rect {
+                fit_map_in_view(editor, canvas);
             }
         }
     }
+    editor.last_painted = None;
+    clear_object_selection(editor);
+
+    info!("Map loaded successfully with {} levels", editor.level_names.len());
+    let Some(bin_path) = bin_path else { return };
+    add_recent_map(&mut editor.recent_maps, bin_path);
+    // Maps opened with Browse or from Recent count toward the last used mod too
+    if let Some(mod_name) = editor.celeste_assets.mods_dir().and_then(|dir| mod_of_map(&dir, Path::new(bin_path))) {
+        remember_mod(editor, &mod_name);
+    }
 }
 
-// Restore save_map_as for Save As functionality
-pub fn save_map_as(editor: &mut CelesteMapEditor) {
-    if let Some(map_data) = &editor.map_data {
-        if let Some(new_bin_path) = rfd::FileDialog::new()
-            .add_filter("Celeste Map", &["bin"])
-            .save_file()
-        {
-            let new_bin_path_str = new_bin_path.display().to_string();
-            // For minimal version, just save JSON for now
-            match serde_json::to_string_pretty(map_data) {
-                Ok(json_str) => {
-                    if let Err(e) = File::create(&new_bin_path_str).and_then(|mut file| file.write_all(json_str.as_bytes())) {
-                        if cfg!(debug_assertions) {
-                            debug!("Failed to write file: {}", e);
-                        }
-                        return;
-                    }
-                    info!("Map saved successfully to {}", new_bin_path_str);
-                    editor.bin_path = Some(new_bin_path_str);
-                }
-                Err(e) => {
-                    if cfg!(debug_assertions) {
-                        debug!("Failed to serialize map data: {}", e);
-                    }
-                }
-            }
+/// Version of the linked cairn crate, with the git revision when it comes from git, e.g. "0.1.0 (51460af)".
+/// Set by build.rs from Cargo.lock; None when the build had no lock file.
+pub fn cairn_version() -> Option<String> {
+    option_env!("SUMMIT_CAIRN_VERSION").map(str::to_string)
+}
+
+/// Last-modified time of the open .bin, if it can be read.
+pub fn bin_modified(bin_path: &str) -> Option<SystemTime> {
+    std::fs::metadata(bin_path).ok()?.modified().ok()
+}
+
+/// Build the JSON of an empty room at the origin, with every child Cairn writes for a level.
+pub fn blank_room_json(room_name: &str, width_tiles: u32, height_tiles: u32) -> Value {
+    let tiles = |name: &str| json!({ "__name": name, "offsetX": 0, "offsetY": 0, "innerText": "" });
+    json!({
+        "__name": "level",
+        "name": room_name,
+        "x": 0, "y": 0,
+        "width": width_tiles * CELESTE_TILE_PX, "height": height_tiles * CELESTE_TILE_PX,
+        "c": 0,
+        "music": "", "alt_music": "", "ambience": "",
+        "musicLayer1": true, "musicLayer2": true, "musicLayer3": true, "musicLayer4": true,
+        "musicProgress": "", "ambienceProgress": "",
+        "dark": false, "space": false, "underwater": false, "whisper": false,
+        "disableDownTransition": false, "delayAltMusicFade": false,
+        "windPattern": "None",
+        "cameraOffsetX": 0, "cameraOffsetY": 0,
+        "__children": [
+            { "__name": "entities", "__children": [] },
+            { "__name": "triggers", "__children": [] },
+            { "__name": "fgtiles", "tileset": "Scenery" },
+            { "__name": "bgtiles", "tileset": "Scenery" },
+            { "__name": "fgdecals", "tileset": "scenery", "__children": [] },
+            { "__name": "bgdecals", "tileset": "scenery", "__children": [] },
+            tiles("solids"),
+            tiles("bg"),
+            { "__name": "objtiles", "tileset": "scenery", "innerText": "" }
+        ]
+    })
+}
+
+/// Build the JSON of a map holding one empty room with a player spawn, in the layout Cairn reads and writes.
+pub fn blank_map_json(package: &str, room_name: &str, width_tiles: u32, height_tiles: u32) -> Value {
+    let height = height_tiles * CELESTE_TILE_PX;
+    let mut room = blank_room_json(room_name, width_tiles, height_tiles);
+    room["__children"][0]["__children"] = json!([
+        Entity::new("player", (4 * CELESTE_TILE_PX) as f64, (height - 2 * CELESTE_TILE_PX) as f64).to_value()
+    ]);
+    json!({
+        "__name": "Map",
+        "package": package,
+        "__children": [
+            { "__name": "Filler", "__children": [] },
+            { "__name": "levels", "__children": [room] },
+            { "__name": "Style", "__children": [
+                { "__name": "Foregrounds", "__children": [] },
+                { "__name": "Backgrounds", "__children": [] }
+            ] }
+        ]
+    })
+}
+
+/// Replace the open map with a new unsaved one. Use Save As to give it a file.
+pub fn new_map(editor: &mut CelesteMapEditor, room_name: &str, width_tiles: u32, height_tiles: u32) {
+    editor.map_data = Some(blank_map_json("untitled", room_name, width_tiles, height_tiles));
+    editor.bin_path = None;
+    editor.map_meta = None;
+    editor.bin_modified = None;
+    editor.history.clear();
+    editor.is_dirty = false;
+    take_snapshot(editor);
+    editor.extract_level_names();
+    editor.cache_rooms();
+    editor.current_level_index = 0;
+    editor.camera_pos = Vec2::new(0.0, 0.0);
+    editor.camera_animation = None;
+    editor.last_painted = None;
+    clear_object_selection(editor);
+    info!("Created new map with room {} ({}x{} tiles)", room_name, width_tiles, height_tiles);
+}
+
+pub fn save_map(editor: &mut CelesteMapEditor) {
+    // New maps have no file yet
+    if editor.map_data.is_some() && editor.bin_path.is_none() {
+        save_map_as(editor);
+        return;
+    }
+    let Some(bin_path) = editor.bin_path.clone() else { return };
+    write_map(editor, &bin_path);
+}
+
+/// Write the open map to `bin_path`. Returns whether it was saved; failures are reported.
+fn write_map(editor: &mut CelesteMapEditor, bin_path: &str) -> bool {
+    let Some(map_data) = &editor.map_data else { return false };
+    match save_map_file(map_data, bin_path) {
+        Ok(_) => {
+            let msg = format!("Map saved to {}", bin_path);
+            editor.notifications.info(msg);
+            editor.is_dirty = false;
+            take_snapshot(editor);
+            editor.bin_modified = bin_modified(bin_path);
+            true
         }
+        Err(e) => {
+            editor.notifications.error(e.to_string());
+            false
+        }
+    }
+}
+
+/// Load the open map's file again, keeping the current room, camera and zoom.
+/// Unsaved changes are discarded; callers confirm first when `is_dirty`.
+pub fn reload_map(editor: &mut CelesteMapEditor) {
+    let Some(bin_path) = editor.bin_path.clone() else { return };
+    let (room, camera_pos, zoom) = (editor.current_level_index, editor.camera_pos, editor.zoom_level);
+    load_map(editor, &bin_path);
+    if editor.map_data.is_none() { return; }
+    editor.current_level_index = room.min(editor.level_names.len().saturating_sub(1));
+    editor.camera_pos = camera_pos;
+    editor.zoom_level = zoom;
+}
+
+/// Reload, asking first if there are unsaved changes.
+pub fn request_reload(editor: &mut CelesteMapEditor) {
+    if editor.bin_path.is_none() { return; }
+    if editor.is_dirty {
+        editor.show_reload_confirm = true;
+    } else {
+        reload_map(editor);
     }
 }
+
+/// Ask for a new .bin path, then save there through Cairn like a normal save.
+pub fn save_map_as(editor: &mut CelesteMapEditor) {
+    if editor.map_data.is_none() { return; }
+    let Some(new_bin_path) = rfd::FileDialog::new()
+        .add_filter("Celeste Map", &["bin"])
+        .save_file() else { return };
+    let new_bin_path_str = new_bin_path.display().to_string();
+    // A failed save keeps the map on its old file
+    if !write_map(editor, &new_bin_path_str) { return; }
+    editor.bin_path = Some(new_bin_path_str.clone());
+    add_recent_map(&mut editor.recent_maps, &new_bin_path_str);
+    // The sidecar belongs to the file name, so the new path has its own (usually none)
+    load_map_meta(editor, &new_bin_path_str);
+}
@@ -0,0 +1,82 @@
+use std::path::{Path, PathBuf};
+use serde_yaml::{Mapping, Value};
+
+/// `IntroType` values Everest accepts, in the order the game declares them.
+pub const INTRO_TYPES: [&str; 10] = [
+    "Transition", "Respawn", "WalkInRight", "WalkInLeft", "Jump",
+    "WakeUp", "Fall", "TempleMirrorVoid", "None", "ThinkForABit",
+];
+
+/// Everest's `mapname.meta.yaml` sidecar, kept as a YAML tree so keys the editor doesn't
+/// know about are written back unchanged. Comments are not preserved.
+#[derive(Clone, Debug)]
+pub struct MapMeta {
+    pub path: PathBuf,
+    pub root: Value,
+}
+
+/// `Maps/foo.bin` -> `Maps/foo.meta.yaml`
+pub fn meta_path(bin_path: &str) -> PathBuf {
+    Path::new(bin_path).with_extension("meta.yaml")
+}
+
+/// Read the sidecar of a map. `Ok(None)` if the map has none.
+pub fn load_meta(bin_path: &str) -> Result<Option<MapMeta>, String> {
+    let path = meta_path(bin_path);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    // An empty file parses as null; treat it as an empty mapping
+    let root = match serde_yaml::from_str(&text) {
+        Ok(Value::Null) => Value::Mapping(Mapping::new()),
+        Ok(root) => root,
+        Err(e) => return Err(format!("Failed to parse {}: {}", path.display(), e)),
+    };
+    Ok(Some(MapMeta { path, root }))
+}
+
+impl MapMeta {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.root.get(key)
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key)?.as_str()
+    }
+
+    /// Set a top-level key, or remove it when `value` is None.
+    pub fn set(&mut self, key: &str, value: Option<Value>) {
+        let Value::Mapping(map) = &mut self.root else { return };
+        match value {
+            Some(v) => { map.insert(Value::String(key.to_string()), v); }
+            None => { map.remove(key); }
+        }
+    }
+
+    /// Top-level entries as (key, short display value) for read-only listing.
+    pub fn summary(&self) -> Vec<(String, String)> {
+        let Value::Mapping(map) = &self.root else { return Vec::new() };
+        map.iter().map(|(k, v)| {
+            let key = k.as_str().map(str::to_string).unwrap_or_else(|| format!("{:?}", k));
+            let value = match v {
+                Value::Mapping(m) => format!("{{{} keys}}", m.len()),
+                Value::Sequence(s) => format!("[{} items]", s.len()),
+                Value::String(s) => s.clone(),
+                Value::Bool(b) => b.to_string(),
+                Value::Number(n) => n.to_string(),
+                Value::Null => "~".to_string(),
+                Value::Tagged(t) => format!("{}", t.tag),
+            };
+            (key, value)
+        }).collect()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let text = serde_yaml::to_string(&self.root)
+            .map_err(|e| format!("Failed to serialize {}: {}", self.path.display(), e))?;
+        std::fs::write(&self.path, text)
+            .map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))
+    }
+}
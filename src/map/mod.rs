@@ -1,2 +1,18 @@
+pub mod changes;
+pub mod clipboard;
+pub mod collectibles;
 pub mod editor;
-pub mod loader;
\ No newline at end of file
+pub mod entities;
+pub mod entity_selection;
+pub mod history;
+pub mod image_import;
+pub mod loader;
+pub mod meta;
+pub mod playtest;
+pub mod resize;
+pub mod rooms;
+pub mod search;
+pub mod stamps;
+pub mod summary;
+pub mod triggers;
+pub mod validation;
\ No newline at end of file
@@ -0,0 +1,47 @@
+use std::path::{Path, PathBuf};
+use log::info;
+
+use crate::app::CelesteMapEditor;
+use crate::map::loader::save_map;
+
+/// Where a map outside every mod is copied so Everest loads it: `Mods/<mod>/Maps/<mod>/<file>.bin`.
+fn mod_map_path(mods_dir: &Path, mod_name: &str, bin_path: &Path) -> Option<PathBuf> {
+    Some(mods_dir.join(mod_name).join("Maps").join(mod_name).join(bin_path.file_name()?))
+}
+
+/// Save the map, make sure Everest can see it, and start Celeste.
+pub fn test_in_celeste(editor: &mut CelesteMapEditor) {
+    save_map(editor);
+    // Nothing to test if the save was cancelled or failed
+    if editor.is_dirty { return; }
+    let Some(bin_path) = editor.bin_path.clone().map(PathBuf::from) else { return };
+    let Some(mods_dir) = editor.celeste_assets.mods_dir() else {
+        editor.notifications.error("Set the Celeste path before testing a map");
+        return;
+    };
+
+    // Maps already inside a mod folder are loaded from there
+    if !bin_path.starts_with(&mods_dir) {
+        let mod_name = editor.launch_settings.mod_name.trim();
+        if mod_name.is_empty() {
+            editor.notifications.error("Set a test mod name under File > Test in Celeste");
+            return;
+        }
+        let Some(target) = mod_map_path(&mods_dir, mod_name, &bin_path) else { return };
+        let copied = target.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::copy(&bin_path, &target));
+        if let Err(e) = copied {
+            editor.notifications.error(format!("Failed to copy map to {}: {}", target.display(), e));
+            return;
+        }
+        info!("Copied {} to {}", bin_path.display(), target.display());
+    }
+
+    let spawned = editor.celeste_assets.launch_command(editor.launch_settings.console)
+        .and_then(|mut command| command.spawn().map_err(|e| format!("Failed to start Celeste: {}", e)));
+    match spawned {
+        Ok(_) => editor.notifications.info("Starting Celeste..."),
+        Err(e) => editor.notifications.error(e),
+    }
+}
@@ -0,0 +1,130 @@
+use eframe::egui::{Pos2, Rect, Vec2};
+
+use crate::app::CelesteMapEditor;
+use crate::map::rooms::{room_rect, set_room_bounds, snap_to_tile};
+use crate::ui::transform::ViewTransform;
+
+/// Edge of a resize handle, in screen points
+pub const HANDLE_SIZE: f32 = 8.0;
+// Smallest room edge a handle drag can leave, in Celeste pixels
+const MIN_ROOM_SIZE: i64 = 40;
+
+/// One of the eight grips on the current room's border.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoomResizeHandle {
+    TopLeft,
+    Top,
+    TopRight,
+    Right,
+    BottomRight,
+    Bottom,
+    BottomLeft,
+    Left,
+}
+
+impl RoomResizeHandle {
+    pub const ALL: [RoomResizeHandle; 8] = [
+        Self::TopLeft, Self::Top, Self::TopRight, Self::Right,
+        Self::BottomRight, Self::Bottom, Self::BottomLeft, Self::Left,
+    ];
+
+    /// Which edges the handle moves: -1 for left/top, 1 for right/bottom, 0 for neither.
+    pub(crate) fn edges(self) -> (i32, i32) {
+        match self {
+            Self::TopLeft => (-1, -1),
+            Self::Top => (0, -1),
+            Self::TopRight => (1, -1),
+            Self::Right => (1, 0),
+            Self::BottomRight => (1, 1),
+            Self::Bottom => (0, 1),
+            Self::BottomLeft => (-1, 1),
+            Self::Left => (-1, 0),
+        }
+    }
+}
+
+/// A handle being dragged. The cached room shows the new bounds until the drag ends,
+/// when they are written to the map as one undo entry.
+#[derive(Clone, Debug)]
+pub struct RoomResizeDrag {
+    pub room_idx: usize,
+    pub handle: RoomResizeHandle,
+    /// Room bounds when the drag began: x, y, width, height in Celeste pixels.
+    original: (i64, i64, i64, i64),
+    bounds: (i64, i64, i64, i64),
+}
+
+/// Screen rectangles of the handles around the cached bounds of room `idx`.
+pub fn handle_rects(editor: &CelesteMapEditor, idx: usize) -> Vec<(RoomResizeHandle, Rect)> {
+    let Some(room) = editor.cached_rooms.get(idx) else { return Vec::new() };
+    let room = ViewTransform::of(editor).room_rect(&room.level_data);
+    RoomResizeHandle::ALL.iter().map(|&handle| {
+        let (ex, ey) = handle.edges();
+        let pick = |edge: i32, min: f32, max: f32| match edge {
+            -1 => min,
+            1 => max,
+            _ => (min + max) / 2.0,
+        };
+        let center = Pos2::new(pick(ex, room.left(), room.right()), pick(ey, room.top(), room.bottom()));
+        (handle, Rect::from_center_size(center, Vec2::splat(HANDLE_SIZE)))
+    }).collect()
+}
+
+/// The current room's handle under `pos`, if any.
+pub fn handle_at(editor: &CelesteMapEditor, pos: Pos2) -> Option<RoomResizeHandle> {
+    handle_rects(editor, editor.current_level_index).into_iter()
+        .find(|(_, rect)| rect.expand(2.0).contains(pos))
+        .map(|(handle, _)| handle)
+}
+
+/// Start dragging the current room's handle under `pos`. Returns false if there is none.
+pub fn begin_room_resize(editor: &mut CelesteMapEditor, pos: Pos2) -> bool {
+    let Some(handle) = handle_at(editor, pos) else { return false };
+    let Some(level) = editor.get_current_level_json() else { return false };
+    let original = room_rect(level);
+    editor.room_resize = Some(RoomResizeDrag { room_idx: editor.current_level_index, handle, original, bounds: original });
+    true
+}
+
+/// Move the dragged edges to the pointer, on whole tiles and no smaller than MIN_ROOM_SIZE.
+pub fn update_room_resize(editor: &mut CelesteMapEditor, pos: Pos2) {
+    let Some(drag) = editor.room_resize.clone() else { return };
+    let world = ViewTransform::of(editor).screen_to_world(pos);
+    let px = snap_to_tile(world.x as i64);
+    let py = snap_to_tile(world.y as i64);
+    let (x, y, w, h) = drag.original;
+    let (ex, ey) = drag.handle.edges();
+    let (left, right) = match ex {
+        -1 => (px.min(x + w - MIN_ROOM_SIZE), x + w),
+        1 => (x, px.max(x + MIN_ROOM_SIZE)),
+        _ => (x, x + w),
+    };
+    let (top, bottom) = match ey {
+        -1 => (py.min(y + h - MIN_ROOM_SIZE), y + h),
+        1 => (y, py.max(y + MIN_ROOM_SIZE)),
+        _ => (y, y + h),
+    };
+    let bounds = (left, top, right - left, bottom - top);
+    if bounds == drag.bounds { return; }
+    if let Some(room) = editor.cached_rooms.get_mut(drag.room_idx) {
+        let ld = &mut room.level_data;
+        ld.x = bounds.0 as f32;
+        ld.y = bounds.1 as f32;
+        ld.width = bounds.2 as f32;
+        ld.height = bounds.3 as f32;
+    }
+    if let Some(d) = &mut editor.room_resize {
+        d.bounds = bounds;
+    }
+}
+
+/// Apply the new bounds to the map, or just redraw the room if they didn't change.
+pub fn end_room_resize(editor: &mut CelesteMapEditor) {
+    let Some(drag) = editor.room_resize.take() else { return };
+    if drag.bounds == drag.original {
+        editor.cache_rooms();
+        return;
+    }
+    let (x, y, w, h) = drag.bounds;
+    set_room_bounds(editor, drag.room_idx, x, y, w, h);
+}
@@ -0,0 +1,317 @@
+use std::collections::HashSet;
+use serde_json::Value;
+
+use crate::app::CelesteMapEditor;
+use crate::data::map_model::{self, CelestePixels, PIXELS_PER_TILE};
+use crate::map::entity_selection::clear_object_selection;
+use crate::map::history::EditCommand;
+use crate::map::validation::trim_level;
+
+// Gap left between a copied room and the rightmost room of the target map
+const FREE_SPACE_GAP: f64 = 64.0;
+// Room children positioned relative to the room origin
+const POSITIONED_LAYERS: [&str; 4] = ["entities", "triggers", "fgdecals", "bgdecals"];
+// Character tile grids, shifted a column or row at a time
+const CHAR_GRIDS: [&str; 2] = ["solids", "bg"];
+// Object tile grid: comma-separated tile indices, -1 where there is none
+const OBJ_TILE_GRID: &str = "objtiles";
+
+fn room_names(levels: &[Value]) -> HashSet<String> {
+    levels.iter().filter_map(|l| l["name"].as_str().map(str::to_string)).collect()
+}
+
+/// `name` if free, otherwise the first free `name-copy`, `name-copy2`, ...
+fn unique_room_name(name: &str, taken: &HashSet<String>) -> String {
+    if !taken.contains(name) {
+        return name.to_string();
+    }
+    let mut candidate = format!("{}-copy", name);
+    let mut n = 2;
+    while taken.contains(&candidate) {
+        candidate = format!("{}-copy{}", name, n);
+        n += 1;
+    }
+    candidate
+}
+
+fn overlaps(a: &Value, b: &Value) -> bool {
+    let px = |v: &Value, k: &str| CelestePixels::attr(&v[k]).0;
+    px(a, "x") < px(b, "x") + px(b, "width") && px(b, "x") < px(a, "x") + px(a, "width")
+        && px(a, "y") < px(b, "y") + px(b, "height") && px(b, "y") < px(a, "y") + px(a, "height")
+}
+
+/// Deep-copy `room` into the `levels` of `target`.
+/// The room is renamed if its name is taken, entities and triggers whose ids already exist in the
+/// target get fresh ones, and with `find_free_space` a room overlapping another is moved right of
+/// every existing room. Returns the name the copy ended up with.
+pub fn copy_room_into(room: &Value, target: &mut Value, find_free_space: bool) -> Result<String, String> {
+    let levels = map_model::levels(target).ok_or("Target map has no levels")?;
+    let mut copy = room.clone();
+
+    let name = unique_room_name(room["name"].as_str().unwrap_or("room"), &room_names(levels));
+    copy["name"] = serde_json::json!(name);
+
+    if find_free_space && levels.iter().any(|l| overlaps(l, &copy)) {
+        let right = levels.iter()
+            .map(|l| CelestePixels::attr(&l["x"]).0 + CelestePixels::attr(&l["width"]).0)
+            .fold(0.0, f64::max);
+        copy["x"] = serde_json::json!((right + FREE_SPACE_GAP) as i64);
+    }
+
+    let mut taken: HashSet<i64> = levels.iter().flat_map(map_model::level_entity_ids).collect();
+    let mut next_id = map_model::next_entity_id(target);
+    if let Some(children) = copy["__children"].as_array_mut() {
+        for child in children {
+            if !child["__name"].as_str().map_or(false, |n| map_model::ID_LAYERS.contains(&n)) { continue; }
+            for e in child["__children"].as_array_mut().into_iter().flatten() {
+                let Some(id) = e["id"].as_i64() else { continue };
+                if taken.contains(&id) {
+                    e["id"] = serde_json::json!(next_id);
+                    taken.insert(next_id);
+                    next_id += 1;
+                } else {
+                    taken.insert(id);
+                    next_id = next_id.max(id + 1);
+                }
+            }
+        }
+    }
+
+    map_model::levels_mut(target).ok_or("Target map has no levels")?.push(copy);
+    Ok(name)
+}
+
+/// Room-local position of the first `checkpoint` entity in a level.
+pub fn checkpoint_position(level: &Value) -> Option<(f64, f64)> {
+    level["__children"].as_array()?
+        .iter()
+        .filter(|c| c["__name"] == "entities")
+        .flat_map(|c| c["__children"].as_array().into_iter().flatten())
+        .find(|e| e["__name"] == "checkpoint")
+        .map(|e| (CelestePixels::attr(&e["x"]).0, CelestePixels::attr(&e["y"]).0))
+}
+
+/// Positions of the room's player spawns, in room pixels (the player's feet).
+pub fn spawn_positions(level: &Value) -> Vec<(f64, f64)> {
+    level["__children"].as_array().into_iter().flatten()
+        .filter(|c| c["__name"] == "entities")
+        .flat_map(|c| c["__children"].as_array().into_iter().flatten())
+        .filter(|e| e["__name"] == "player")
+        .map(|e| (CelestePixels::attr(&e["x"]).0, CelestePixels::attr(&e["y"]).0))
+        .collect()
+}
+
+/// Camera offset in pixels from the room's `cameraOffsetX/Y`; the game scales them by 48 and 32.
+pub fn camera_offset(level: &Value) -> (f64, f64) {
+    (
+        48.0 * level["cameraOffsetX"].as_f64().unwrap_or(0.0),
+        32.0 * level["cameraOffsetY"].as_f64().unwrap_or(0.0),
+    )
+}
+
+/// The room's `music` and `alt_music` event paths, if set.
+pub fn room_music(level: &Value) -> (Option<&str>, Option<&str>) {
+    let get = |key: &str| level[key].as_str().filter(|s| !s.is_empty());
+    (get("music"), get("alt_music"))
+}
+
+/// Readable track name from a music event path: `event:/music/lvl1/main` becomes `lvl1/main`.
+pub fn music_track_name(path: &str) -> &str {
+    path.strip_prefix("event:/music/")
+        .or_else(|| path.strip_prefix("event:/"))
+        .unwrap_or(path)
+}
+
+/// Move room `from` to position `to` in the `levels` array. Only the order changes;
+/// the current room stays selected.
+pub fn move_room(editor: &mut CelesteMapEditor, from: usize, to: usize) {
+    if from == to { return; }
+    let current = editor.current_level_index;
+    let Some(levels) = editor.map_data.as_mut().and_then(map_model::levels_mut) else { return };
+    if from >= levels.len() || to >= levels.len() { return; }
+    let room = levels.remove(from);
+    levels.insert(to, room);
+
+    editor.current_level_index = if current == from {
+        to
+    } else if from < current && to >= current {
+        current - 1
+    } else if from > current && to <= current {
+        current + 1
+    } else {
+        current
+    };
+    // These refer to rooms by index
+    editor.selection = None;
+    editor.line_start = None;
+    editor.last_painted = None;
+    clear_object_selection(editor);
+    editor.history.clear();
+    editor.extract_level_names();
+    editor.cache_rooms();
+    editor.is_dirty = true;
+}
+
+/// Room bounds in Celeste pixels: (x, y, width, height).
+pub fn room_rect(level: &Value) -> (i64, i64, i64, i64) {
+    let px = |k: &str| CelestePixels::attr(&level[k]).0 as i64;
+    (px("x"), px("y"), px("width"), px("height"))
+}
+
+/// Apply `edit` to room `index` and record it for undo as `description`.
+pub(crate) fn edit_room(editor: &mut CelesteMapEditor, index: usize, description: &str, edit: impl FnOnce(&mut Value)) {
+    let Some(level) = editor.get_level_mut(index) else { return };
+    let before = level.clone();
+    edit(level);
+    if *level == before { return; }
+    let after = level.clone();
+    editor.history.push(EditCommand::LevelEdit { room_idx: index, description: description.to_string(), before, after });
+    editor.cache_rooms();
+    editor.is_dirty = true;
+}
+
+/// Add `level` to the open map as one undoable edit, renamed if its name is taken and moved
+/// right of every room if it overlaps one. Returns the new room's index.
+pub fn add_room(editor: &mut CelesteMapEditor, level: &Value, description: &str) -> Result<usize, String> {
+    let map = editor.map_data.as_mut().ok_or("No map is open")?;
+    copy_room_into(level, map, true)?;
+    let levels = map_model::levels(map).ok_or("The map has no levels")?;
+    let room_idx = levels.len() - 1;
+    let level = levels[room_idx].clone();
+    editor.history.push(EditCommand::RoomAdd { room_idx, description: description.to_string(), level });
+    editor.extract_level_names();
+    editor.cache_rooms();
+    editor.current_level_index = room_idx;
+    editor.is_dirty = true;
+    Ok(room_idx)
+}
+
+/// Move a room. Tiles, entities and decals are stored relative to the room, so nothing else changes.
+pub fn set_room_position(editor: &mut CelesteMapEditor, index: usize, x: i64, y: i64) {
+    edit_room(editor, index, "Move room", |level| {
+        level["x"] = serde_json::json!(x);
+        level["y"] = serde_json::json!(y);
+    });
+}
+
+/// Set the room's colour index `c`, which the game and other editors use to colour-code rooms.
+pub fn set_room_color(editor: &mut CelesteMapEditor, index: usize, color: i64) {
+    edit_room(editor, index, "Change room color", |level| {
+        level["c"] = serde_json::json!(color);
+    });
+}
+
+/// Move and resize a room as one edit. Contents move with the room, as in `set_room_position`,
+/// and tile grids are trimmed to the new size. Growing needs no grid change since missing tiles
+/// read as air.
+pub fn set_room_rect(editor: &mut CelesteMapEditor, index: usize, x: i64, y: i64, width: i64, height: i64) {
+    let Some((old_x, old_y, old_width, old_height)) = editor.get_level_mut(index).map(|l| room_rect(l)) else { return };
+    let moved = (x, y) != (old_x, old_y);
+    let resized = (width, height) != (old_width, old_height);
+    let description = match (moved, resized) {
+        (true, true) => "Move and resize room",
+        (true, false) => "Move room",
+        _ => "Resize room",
+    };
+    edit_room(editor, index, description, |level| {
+        level["x"] = serde_json::json!(x);
+        level["y"] = serde_json::json!(y);
+        level["width"] = serde_json::json!(width);
+        level["height"] = serde_json::json!(height);
+        trim_level(level);
+    });
+}
+
+/// Move a room's contents by whole tiles, as when its left or top edge moves the other way:
+/// tile grids gain or lose leading columns and rows, and entities, triggers and decals move along.
+fn shift_contents(level: &mut Value, dx: i64, dy: i64) {
+    let step = PIXELS_PER_TILE as f64;
+    let Some(children) = level["__children"].as_array_mut() else { return };
+    for child in children {
+        let name = child["__name"].as_str().unwrap_or("");
+        if CHAR_GRIDS.contains(&name) {
+            let Some(text) = child["innerText"].as_str() else { continue };
+            let shifted = shift_grid_rows(text, dy, |row| if dx >= 0 {
+                "0".repeat(dx as usize) + row
+            } else {
+                row.chars().skip((-dx) as usize).collect()
+            });
+            child["innerText"] = serde_json::json!(shifted);
+        } else if name == OBJ_TILE_GRID {
+            let Some(text) = child["innerText"].as_str() else { continue };
+            let shifted = shift_grid_rows(text, dy, |row| if dx >= 0 {
+                "-1,".repeat(dx as usize) + row
+            } else {
+                row.split(',').skip((-dx) as usize).collect::<Vec<_>>().join(",")
+            });
+            child["innerText"] = serde_json::json!(shifted);
+        } else if POSITIONED_LAYERS.contains(&name) {
+            for e in child["__children"].as_array_mut().into_iter().flatten() {
+                if let Some(nodes) = e["__children"].as_array_mut() {
+                    for node in nodes.iter_mut().filter(|n| n["__name"] == "node") {
+                        shift_position(node, dx as f64 * step, dy as f64 * step);
+                    }
+                }
+                shift_position(e, dx as f64 * step, dy as f64 * step);
+            }
+        }
+    }
+}
+
+/// Grid text with `dy` blank rows added at the top, or `-dy` rows removed, and each non-empty
+/// row passed through `shift_row`.
+fn shift_grid_rows(text: &str, dy: i64, shift_row: impl Fn(&str) -> String) -> String {
+    let mut rows: Vec<String> = text.split('\n')
+        .map(|row| if row.is_empty() { String::new() } else { shift_row(row) })
+        .collect();
+    if dy >= 0 {
+        rows.splice(0..0, std::iter::repeat(String::new()).take(dy as usize));
+    } else {
+        rows.drain(0..((-dy) as usize).min(rows.len()));
+    }
+    rows.join("\n")
+}
+
+fn shift_position(item: &mut Value, dx: f64, dy: f64) {
+    for (key, delta) in [("x", dx), ("y", dy)] {
+        let moved = CelestePixels::attr(&item[key]).0 + delta;
+        item[key] = if moved.fract() == 0.0 { serde_json::json!(moved as i64) } else { serde_json::json!(moved) };
+    }
+}
+
+/// Set all four room bounds at once. Contents stay where they are in the world, so moving the left
+/// or top edge shifts them within the room; tiles left outside are trimmed.
+pub fn set_room_bounds(editor: &mut CelesteMapEditor, index: usize, x: i64, y: i64, width: i64, height: i64) {
+    edit_room(editor, index, "Resize room", |level| {
+        let (old_x, old_y, _, _) = room_rect(level);
+        let step = PIXELS_PER_TILE as i64;
+        shift_contents(level, (old_x - x) / step, (old_y - y) / step);
+        level["x"] = serde_json::json!(x);
+        level["y"] = serde_json::json!(y);
+        level["width"] = serde_json::json!(width);
+        level["height"] = serde_json::json!(height);
+        trim_level(level);
+    });
+}
+
+/// Index of the room closest to room `index`, by the gap between their bounds.
+pub fn nearest_room(editor: &CelesteMapEditor, index: usize) -> Option<usize> {
+    let levels = map_model::levels(editor.map_data.as_ref()?)?;
+    let (x, y, w, h) = room_rect(levels.get(index)?);
+    levels.iter().enumerate()
+        .filter(|(i, l)| *i != index && l["__name"] == "level")
+        .map(|(i, l)| {
+            let (ox, oy, ow, oh) = room_rect(l);
+            let gap_x = (ox - (x + w)).max(x - (ox + ow)).max(0);
+            let gap_y = (oy - (y + h)).max(y - (oy + oh)).max(0);
+            (gap_x * gap_x + gap_y * gap_y, i)
+        })
+        .min()
+        .map(|(_, i)| i)
+}
+
+/// Round a pixel position to the tile grid.
+pub fn snap_to_tile(value: i64) -> i64 {
+    let step = PIXELS_PER_TILE as i64;
+    (value as f64 / step as f64).round() as i64 * step
+}
@@ -0,0 +1,82 @@
+use serde_json::Value;
+
+use crate::app::CelesteMapEditor;
+use crate::data::map_model::CelestePixels;
+
+// Level children searched, with the label shown for each
+const SEARCH_LAYERS: [(&str, &str); 4] = [
+    ("entities", "entity"),
+    ("triggers", "trigger"),
+    ("fgdecals", "fg decal"),
+    ("bgdecals", "bg decal"),
+];
+
+/// An entity, trigger or decal matching a Find query.
+#[derive(Clone, Debug)]
+pub struct SearchHit {
+    pub room_idx: usize,
+    /// "entity", "trigger", "fg decal" or "bg decal".
+    pub kind: &'static str,
+    /// `__name` for entities and triggers, texture path for decals.
+    pub name: String,
+    /// What matched: "name", or `key = value` for an attribute.
+    pub matched: String,
+    /// Position relative to the room, in Celeste pixels.
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Attribute value as text, so numbers and booleans can be searched too.
+fn value_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// First thing about `object` containing `query` (lowercase): its name, then its attributes in key order.
+fn match_object(object: &Value, name: &str, query: &str) -> Option<String> {
+    if name.to_lowercase().contains(query) {
+        return Some("name".to_string());
+    }
+    let attrs = object.as_object()?;
+    let mut keys: Vec<&String> = attrs.keys().filter(|k| !k.starts_with("__")).collect();
+    keys.sort();
+    keys.into_iter().find_map(|key| {
+        let text = value_text(&attrs[key])?;
+        text.to_lowercase().contains(query).then(|| format!("{} = {}", key, text))
+    })
+}
+
+/// Entities, triggers and decals in every room whose name or an attribute value contains `query`,
+/// ignoring case. Decals are named by their texture path.
+pub fn find_objects(editor: &CelesteMapEditor, query: &str) -> Vec<SearchHit> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let mut hits = Vec::new();
+    for (room_idx, room) in editor.cached_rooms.iter().enumerate() {
+        for (layer, kind) in SEARCH_LAYERS {
+            let objects = room.json["__children"].as_array().into_iter().flatten()
+                .filter(|c| c["__name"] == layer)
+                .flat_map(|c| c["__children"].as_array().into_iter().flatten());
+            for object in objects {
+                let name_key = if layer.ends_with("decals") { "texture" } else { "__name" };
+                let name = object[name_key].as_str().unwrap_or("").to_string();
+                let Some(matched) = match_object(object, &name, &query) else { continue };
+                hits.push(SearchHit {
+                    room_idx,
+                    kind,
+                    name,
+                    matched,
+                    x: CelestePixels::attr(&object["x"]).0,
+                    y: CelestePixels::attr(&object["y"]).0,
+                });
+            }
+        }
+    }
+    hits
+}
@@ -0,0 +1,108 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use log::{debug, warn};
+
+use crate::map::clipboard::{ClipboardObject, TileClipboard};
+
+/// Bumped whenever the on-disk stamp layout changes incompatibly.
+pub const STAMP_FORMAT_VERSION: u32 = 1;
+
+/// A named clipboard persisted under the config dir so it can be reused and shared.
+/// Unknown fields are ignored so stamps written by newer versions still load.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Stamp {
+    #[serde(default = "default_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub tiles: Vec<String>,
+    #[serde(default)]
+    pub objects: Vec<ClipboardObject>,
+}
+
+fn default_version() -> u32 {
+    STAMP_FORMAT_VERSION
+}
+
+impl Stamp {
+    pub fn from_clipboard(name: &str, clipboard: &TileClipboard) -> Self {
+        Self {
+            version: STAMP_FORMAT_VERSION,
+            name: name.to_string(),
+            tiles: clipboard.tiles.clone(),
+            objects: clipboard.objects.clone(),
+        }
+    }
+
+    pub fn to_clipboard(&self) -> TileClipboard {
+        TileClipboard {
+            tiles: self.tiles.clone(),
+            objects: self.objects.clone(),
+        }
+    }
+}
+
+/// Directory holding one JSON file per stamp.
+pub fn stamps_dir() -> PathBuf {
+    let config_dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+    config_dir.join("summit_stamps")
+}
+
+/// Turn a stamp name into a safe file name, without the extension.
+fn stamp_file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+/// File for the stamp named `name` in `dir`: the one it was saved to before, or a free one.
+/// Names that clean up to the same file name get `_2`, `_3`, ... so one never overwrites the other.
+fn stamp_path(dir: &Path, name: &str) -> PathBuf {
+    let stem = stamp_file_stem(name);
+    (1..).map(|n| if n == 1 { dir.join(format!("{}.json", stem)) } else { dir.join(format!("{}_{}.json", stem, n)) })
+        .find(|path| {
+            let saved = std::fs::read_to_string(path).ok().and_then(|s| serde_json::from_str::<Stamp>(&s).ok());
+            match saved {
+                Some(saved) => saved.name == name,
+                None => !path.exists(),
+            }
+        })
+        .unwrap_or_else(|| dir.join(format!("{}.json", stem)))
+}
+
+pub fn save_stamp(stamp: &Stamp) -> io::Result<()> {
+    let dir = stamps_dir();
+    std::fs::create_dir_all(&dir)?;
+    let json = serde_json::to_string_pretty(stamp)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    std::fs::write(stamp_path(&dir, &stamp.name), json)
+}
+
+/// Load every readable stamp, sorted by name. Broken files are skipped.
+pub fn load_stamps() -> Vec<Stamp> {
+    let mut stamps = Vec::new();
+    let Ok(entries) = std::fs::read_dir(stamps_dir()) else { return stamps };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().map_or(true, |ext| ext != "json") { continue; }
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| serde_json::from_str::<Stamp>(&s).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(mut stamp) => {
+                if stamp.version > STAMP_FORMAT_VERSION {
+                    warn!("Stamp {} uses newer format v{}, loading what we understand", path.display(), stamp.version);
+                }
+                if stamp.name.is_empty() {
+                    stamp.name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                }
+                stamps.push(stamp);
+            }
+            Err(e) => debug!("Skipping stamp {}: {}", path.display(), e),
+        }
+    }
+    stamps.sort_by(|a, b| a.name.cmp(&b.name));
+    stamps
+}
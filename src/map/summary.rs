@@ -0,0 +1,121 @@
+use std::path::Path;
+use serde_json::Value;
+
+use crate::app::CelesteMapEditor;
+use crate::map::rooms::checkpoint_position;
+
+/// Column names in file order. Scripts read these by position, so only ever append.
+pub const SUMMARY_COLUMNS: [&str; 10] = [
+    "name", "x", "y", "width", "height", "fg_tiles", "entities", "strawberries", "checkpoint", "spawn",
+];
+
+/// One row of the room summary, from the cached room and its statistics.
+pub struct RoomSummary {
+    pub name: String,
+    pub x: i64,
+    pub y: i64,
+    pub width: i64,
+    pub height: i64,
+    pub fg_tiles: usize,
+    pub entities: usize,
+    pub strawberries: usize,
+    pub checkpoint: bool,
+    pub spawn: bool,
+}
+
+impl RoomSummary {
+    fn cells(&self) -> [String; 10] {
+        [
+            self.name.clone(),
+            self.x.to_string(),
+            self.y.to_string(),
+            self.width.to_string(),
+            self.height.to_string(),
+            self.fg_tiles.to_string(),
+            self.entities.to_string(),
+            self.strawberries.to_string(),
+            self.checkpoint.to_string(),
+            self.spawn.to_string(),
+        ]
+    }
+}
+
+fn count_entities(level: &Value, name: &str) -> usize {
+    level["__children"].as_array().into_iter().flatten()
+        .filter(|c| c["__name"] == "entities")
+        .flat_map(|c| c["__children"].as_array().into_iter().flatten())
+        .filter(|e| e["__name"] == name)
+        .count()
+}
+
+pub fn room_summaries(editor: &CelesteMapEditor) -> Vec<RoomSummary> {
+    editor.cached_rooms.iter().map(|room| {
+        let json = &room.json;
+        let int = |key: &str| json[key].as_f64().unwrap_or(0.0) as i64;
+        RoomSummary {
+            name: room.level_data.name.clone(),
+            x: int("x"),
+            y: int("y"),
+            width: int("width"),
+            height: int("height"),
+            fg_tiles: room.stats.fg_tiles,
+            entities: room.stats.entities,
+            strawberries: count_entities(json, "strawberry"),
+            checkpoint: checkpoint_position(json).is_some(),
+            spawn: count_entities(json, "player") > 0,
+        }
+    }).collect()
+}
+
+/// Quote a CSV field when it holds a separator, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn summary_csv(rows: &[RoomSummary]) -> String {
+    let mut out = SUMMARY_COLUMNS.join(",");
+    out.push('\n');
+    for row in rows {
+        let cells: Vec<String> = row.cells().iter().map(|c| csv_field(c)).collect();
+        out.push_str(&cells.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+pub fn summary_markdown(rows: &[RoomSummary]) -> String {
+    let line = |cells: &[String]| format!("| {} |\n", cells.join(" | "));
+    let header: Vec<String> = SUMMARY_COLUMNS.iter().map(|c| c.to_string()).collect();
+    let mut out = line(&header);
+    out.push_str(&line(&vec!["---".to_string(); SUMMARY_COLUMNS.len()]));
+    for row in rows {
+        let cells: Vec<String> = row.cells().iter().map(|c| c.replace('|', "\\|")).collect();
+        out.push_str(&line(&cells));
+    }
+    out
+}
+
+/// Ask for a destination and write the summary there, as Markdown for `.md` and CSV otherwise.
+pub fn export_room_summary(editor: &mut CelesteMapEditor) {
+    if editor.cached_rooms.is_empty() { return; }
+    let Some(path) = rfd::FileDialog::new()
+        .set_title("Export Room Summary")
+        .add_filter("CSV", &["csv"])
+        .add_filter("Markdown", &["md"])
+        .save_file() else { return };
+    let rows = room_summaries(editor);
+    let markdown = path.extension().map_or(false, |e| e.eq_ignore_ascii_case("md"));
+    let text = if markdown { summary_markdown(&rows) } else { summary_csv(&rows) };
+    match std::fs::write(&path, text) {
+        Ok(()) => editor.notifications.info(format!("Exported {} rooms to {}", rows.len(), display_name(&path))),
+        Err(e) => editor.notifications.error(format!("Could not write {}: {}", path.display(), e)),
+    }
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned())
+}
@@ -0,0 +1,223 @@
+use eframe::egui::{Pos2, Rect, Vec2};
+use serde_json::Value;
+
+use crate::app::CelesteMapEditor;
+use crate::data::map_model::{self, CelestePixels, Entity, LevelNode, PIXELS_PER_TILE};
+use crate::data::trigger_catalogue::trigger_defaults;
+use crate::map::editor::hovered_room;
+use crate::map::history::{find_entity_mut, EditCommand};
+use crate::map::resize::{RoomResizeHandle, HANDLE_SIZE};
+use crate::map::rooms::edit_room;
+use crate::ui::transform::ViewTransform;
+
+const STEP: i64 = PIXELS_PER_TILE as i64;
+
+/// A trigger rectangle in room-local Celeste pixels: x, y, width, height.
+pub type TriggerBounds = (i64, i64, i64, i64);
+
+/// A rectangle being drawn with the Triggers tool, between two room-local tiles (inclusive).
+#[derive(Clone, Debug)]
+pub struct TriggerDraft {
+    pub room_idx: usize,
+    start: (i64, i64),
+    end: (i64, i64),
+}
+
+impl TriggerDraft {
+    /// The tiles covered, in room-local pixels.
+    pub fn bounds(&self) -> TriggerBounds {
+        let (x0, x1) = (self.start.0.min(self.end.0), self.start.0.max(self.end.0));
+        let (y0, y1) = (self.start.1.min(self.end.1), self.start.1.max(self.end.1));
+        (x0 * STEP, y0 * STEP, (x1 - x0 + 1) * STEP, (y1 - y0 + 1) * STEP)
+    }
+}
+
+/// A drawn rectangle waiting for the trigger name to be chosen.
+#[derive(Clone, Debug)]
+pub struct NewTrigger {
+    pub room_idx: usize,
+    pub bounds: TriggerBounds,
+    /// Name typed for a modded trigger.
+    pub custom_name: String,
+}
+
+/// A trigger edge being dragged. The map and the cached room follow the pointer; the release
+/// records the whole drag as one undo entry.
+#[derive(Clone, Debug)]
+pub struct TriggerResizeDrag {
+    pub room_idx: usize,
+    pub trigger_id: i64,
+    pub handle: RoomResizeHandle,
+    original: TriggerBounds,
+    before: Value,
+}
+
+/// Triggers of a level.
+pub(crate) fn level_triggers(level: &Value) -> impl Iterator<Item = &Value> {
+    level["__children"].as_array().into_iter().flatten()
+        .filter(|c| c["__name"] == "triggers")
+        .flat_map(|c| c["__children"].as_array().into_iter().flatten())
+}
+
+fn trigger_bounds(trigger: &Value) -> TriggerBounds {
+    (
+        CelestePixels::attr(&trigger["x"]).0.round() as i64,
+        CelestePixels::attr(&trigger["y"]).0.round() as i64,
+        trigger["width"].as_i64().unwrap_or(STEP),
+        trigger["height"].as_i64().unwrap_or(STEP),
+    )
+}
+
+/// Screen rectangle of room-local `bounds` in room `room_idx`.
+pub fn bounds_rect(editor: &CelesteMapEditor, room_idx: usize, bounds: TriggerBounds) -> Option<Rect> {
+    let ld = &editor.cached_rooms.get(room_idx)?.level_data;
+    let (x, y, w, h) = bounds;
+    let world = Rect::from_min_size(Pos2::new(ld.x + x as f32, ld.y + y as f32), Vec2::new(w as f32, h as f32));
+    Some(ViewTransform::of(editor).world_rect_to_screen(world))
+}
+
+/// Room-local tile under `pos` in room `room_idx`, on the 8 px grid of the room's position.
+fn local_tile(editor: &CelesteMapEditor, room_idx: usize, pos: Pos2) -> Option<(i64, i64)> {
+    let ld = &editor.cached_rooms.get(room_idx)?.level_data;
+    let world = ViewTransform::of(editor).screen_to_world(pos);
+    let step = STEP as f32;
+    Some((((world.x - ld.x) / step).floor() as i64, ((world.y - ld.y) / step).floor() as i64))
+}
+
+/// Resize handles of every trigger in the current room: (trigger id, handle, screen rectangle).
+pub fn trigger_handle_rects(editor: &CelesteMapEditor) -> Vec<(i64, RoomResizeHandle, Rect)> {
+    let idx = editor.current_level_index;
+    let Some(room) = editor.cached_rooms.get(idx) else { return Vec::new() };
+    let mut handles = Vec::new();
+    for trigger in level_triggers(&room.json) {
+        let Some(id) = trigger["id"].as_i64() else { continue };
+        let Some(rect) = bounds_rect(editor, idx, trigger_bounds(trigger)) else { continue };
+        for handle in RoomResizeHandle::ALL {
+            let (ex, ey) = handle.edges();
+            let pick = |edge: i32, min: f32, max: f32| match edge {
+                -1 => min,
+                1 => max,
+                _ => (min + max) / 2.0,
+            };
+            let center = Pos2::new(pick(ex, rect.left(), rect.right()), pick(ey, rect.top(), rect.bottom()));
+            handles.push((id, handle, Rect::from_center_size(center, Vec2::splat(HANDLE_SIZE))));
+        }
+    }
+    handles
+}
+
+/// The trigger under `pos` in the room tile edits would use: (room index, trigger id).
+pub fn trigger_at(editor: &CelesteMapEditor, pos: Pos2) -> Option<(usize, i64)> {
+    let room_idx = hovered_room(editor, pos)?;
+    let room = editor.cached_rooms.get(room_idx)?;
+    // Last drawn is on top
+    level_triggers(&room.json).collect::<Vec<_>>().into_iter().rev()
+        .filter_map(|t| Some((t["id"].as_i64()?, bounds_rect(editor, room_idx, trigger_bounds(t))?)))
+        .find(|(_, rect)| rect.contains(pos))
+        .map(|(id, _)| (room_idx, id))
+}
+
+/// Press with the Triggers tool: grab a trigger handle of the current room, or start drawing
+/// a new trigger in the room under the pointer. Presses on a trigger draw nothing, so the first
+/// click of a double click doesn't leave a rectangle behind.
+pub fn begin_trigger_press(editor: &mut CelesteMapEditor, pos: Pos2) {
+    let grabbed = trigger_handle_rects(editor).into_iter().find(|(_, _, rect)| rect.expand(2.0).contains(pos));
+    if let Some((trigger_id, handle, _)) = grabbed {
+        let room_idx = editor.current_level_index;
+        let Some(level) = editor.get_level_mut(room_idx).map(|l| l.clone()) else { return };
+        let Some(original) = level_triggers(&level).find(|t| t["id"].as_i64() == Some(trigger_id)).map(trigger_bounds) else { return };
+        editor.trigger_resize = Some(TriggerResizeDrag { room_idx, trigger_id, handle, original, before: level });
+        return;
+    }
+    if trigger_at(editor, pos).is_some() { return; }
+    let Some(room_idx) = hovered_room(editor, pos) else { return };
+    let Some(tile) = local_tile(editor, room_idx, pos) else { return };
+    editor.current_level_index = room_idx;
+    editor.trigger_draft = Some(TriggerDraft { room_idx, start: tile, end: tile });
+}
+
+/// Drag with the Triggers tool held: stretch the rectangle being drawn or the grabbed trigger edge.
+pub fn update_trigger_drag(editor: &mut CelesteMapEditor, pos: Pos2) {
+    if let Some(draft) = editor.trigger_draft.clone() {
+        if let Some(tile) = local_tile(editor, draft.room_idx, pos) {
+            editor.trigger_draft = Some(TriggerDraft { end: tile, ..draft });
+        }
+        return;
+    }
+    let Some(drag) = editor.trigger_resize.clone() else { return };
+    let Some(ld) = editor.cached_rooms.get(drag.room_idx).map(|r| &r.level_data) else { return };
+    // Edges land on the nearest grid line, not the one left of the pointer
+    let world = ViewTransform::of(editor).screen_to_world(pos);
+    let step = STEP as f32;
+    let px = ((world.x - ld.x) / step).round() as i64 * STEP;
+    let py = ((world.y - ld.y) / step).round() as i64 * STEP;
+    let (x, y, w, h) = drag.original;
+    let (ex, ey) = drag.handle.edges();
+    let (left, right) = match ex {
+        -1 => (px.min(x + w - STEP), x + w),
+        1 => (x, px.max(x + STEP)),
+        _ => (x, x + w),
+    };
+    let (top, bottom) = match ey {
+        -1 => (py.min(y + h - STEP), y + h),
+        1 => (y, py.max(y + STEP)),
+        _ => (y, y + h),
+    };
+    let set = |trigger: &mut Value| {
+        trigger["x"] = Value::from(left);
+        trigger["y"] = Value::from(top);
+        trigger["width"] = Value::from(right - left);
+        trigger["height"] = Value::from(bottom - top);
+    };
+    if let Some(trigger) = editor.get_level_mut(drag.room_idx).and_then(|l| find_entity_mut(l, drag.trigger_id)) {
+        set(trigger);
+    }
+    // The cached copy is drawn; updating it avoids recaching every room on each frame of the drag
+    if let Some(trigger) = editor.cached_rooms.get_mut(drag.room_idx).and_then(|r| find_entity_mut(&mut r.json, drag.trigger_id)) {
+        set(trigger);
+    }
+}
+
+/// Release: ask for the name of the drawn trigger, or record the resize for undo.
+pub fn end_trigger_drag(editor: &mut CelesteMapEditor) {
+    if let Some(draft) = editor.trigger_draft.take() {
+        editor.new_trigger = Some(NewTrigger { room_idx: draft.room_idx, bounds: draft.bounds(), custom_name: String::new() });
+        return;
+    }
+    let Some(drag) = editor.trigger_resize.take() else { return };
+    let Some(after) = editor.get_level_mut(drag.room_idx).map(|l| l.clone()) else { return };
+    if after == drag.before { return; }
+    editor.history.push(EditCommand::LevelEdit {
+        room_idx: drag.room_idx,
+        description: "Resize trigger".to_string(),
+        before: drag.before,
+        after,
+    });
+    editor.is_dirty = true;
+    editor.cache_rooms();
+}
+
+/// Add a trigger named `name` over `bounds` of room `room_idx`, with its default attributes.
+pub fn add_trigger(editor: &mut CelesteMapEditor, room_idx: usize, name: &str, bounds: TriggerBounds) {
+    let Some(mut map) = editor.map_data.as_ref().and_then(map_model::parse_map) else { return };
+    let (x, y, width, height) = bounds;
+    let trigger = trigger_defaults(name).into_iter()
+        .fold(Entity::new(name, x as f64, y as f64), |trigger, (key, value)| trigger.with_attribute(key, value))
+        .with_attribute("width", width)
+        .with_attribute("height", height);
+    if map.add_entity(room_idx, "triggers", trigger).is_none() {
+        editor.notifications.warn(format!("Room {} could not be read, trigger not added", room_idx));
+        return;
+    }
+    let Some(after) = map.level(room_idx).map(LevelNode::to_value) else { return };
+    edit_room(editor, room_idx, &format!("Add {}", name), |level| *level = after);
+}
+
+/// Open the attribute inspector on the trigger under `pos`. Returns false if there is none.
+pub fn inspect_trigger_at(editor: &mut CelesteMapEditor, pos: Pos2) -> bool {
+    let Some((room_idx, id)) = trigger_at(editor, pos) else { return false };
+    editor.current_level_index = room_idx;
+    editor.show_entity_properties = true;
+    editor.inspect_entity = Some(id);
+    true
+}
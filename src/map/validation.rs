@@ -0,0 +1,146 @@
+use serde_json::Value;
+use log::info;
+
+use crate::app::CelesteMapEditor;
+use crate::data::map_model::{level_size_tiles, TileCoord};
+use crate::map::history::EditCommand;
+use crate::map::rooms::edit_room;
+
+// Tile grids checked against the room bounds
+const TILE_GRIDS: [&str; 2] = ["solids", "bg"];
+// Undo entry of a trim
+const TRIM_DESCRIPTION: &str = "Trim to room bounds";
+
+/// Count tiles lying past `width` columns or `height` rows.
+pub fn count_overflow(grid: &[Vec<char>], width: usize, height: usize) -> usize {
+    grid.iter().enumerate().map(|(y, row)| {
+        if y >= height { row.len() } else { row.len().saturating_sub(width) }
+    }).sum()
+}
+
+fn is_air(c: char) -> bool {
+    c == '0' || c == ' '
+}
+
+/// Count cells where a bg tile lies under a fg solid, which the game never shows.
+pub fn count_hidden_bg(solids: &[Vec<char>], bg: &[Vec<char>]) -> usize {
+    bg.iter().zip(solids).map(|(bg_row, fg_row)| {
+        bg_row.iter().zip(fg_row).filter(|(b, f)| !is_air(**b) && !is_air(**f)).count()
+    }).sum()
+}
+
+/// Set every bg cell under a fg solid to air. Returns the new bg text and the number of cells cleared.
+pub fn clear_hidden_bg_text(solids: &str, bg: &str) -> (String, usize) {
+    let fg_rows: Vec<Vec<char>> = solids.split('\n').map(|r| r.chars().collect()).collect();
+    let mut cleared = 0;
+    let rows: Vec<String> = bg.split('\n').enumerate().map(|(y, line)| {
+        line.chars().enumerate().map(|(x, c)| {
+            let solid = fg_rows.get(y).and_then(|r| r.get(x)).map_or(false, |f| !is_air(*f));
+            if solid && !is_air(c) {
+                cleared += 1;
+                '0'
+            } else {
+                c
+            }
+        }).collect()
+    }).collect();
+    (rows.join("\n"), cleared)
+}
+
+/// Clear the bg tiles hidden under solids in room `index` as one undoable edit. Returns the number cleared.
+pub fn clear_hidden_bg(editor: &mut CelesteMapEditor, index: usize) -> usize {
+    let mut cleared = 0;
+    edit_room(editor, index, "Clear hidden bg tiles", |level| {
+        let grid_text = |name: &str| level["__children"].as_array()
+            .and_then(|c| c.iter().find(|c| c["__name"] == name))
+            .and_then(|c| c["innerText"].as_str())
+            .map(str::to_string);
+        let (Some(solids), Some(bg)) = (grid_text("solids"), grid_text("bg")) else { return };
+        let (text, n) = clear_hidden_bg_text(&solids, &bg);
+        if n == 0 { return; }
+        if let Some(node) = level["__children"].as_array_mut().and_then(|c| c.iter_mut().find(|c| c["__name"] == "bg")) {
+            node["innerText"] = serde_json::json!(text);
+            cleared = n;
+        }
+    });
+    if cleared > 0 {
+        info!("Cleared {} hidden bg tiles from room {}", cleared, index);
+    }
+    cleared
+}
+
+/// Truncate a tile grid's text to the given bounds. Returns the new text and the number of tiles removed.
+pub fn trim_tile_text(text: &str, width: usize, height: usize) -> (String, usize) {
+    let mut removed = 0;
+    let mut rows = Vec::new();
+    for (y, line) in text.split('\n').enumerate() {
+        let len = line.chars().count();
+        if y >= height {
+            removed += len;
+            continue;
+        }
+        if len > width {
+            removed += len - width;
+            rows.push(line.chars().take(width).collect::<String>());
+        } else {
+            rows.push(line.to_string());
+        }
+    }
+    (rows.join("\n"), removed)
+}
+
+/// Trim the solids and bg grids of one level JSON node. Returns the number of tiles removed.
+pub(crate) fn trim_level(level: &mut Value) -> usize {
+    let (TileCoord(width), TileCoord(height)) = level_size_tiles(level);
+    let (width, height) = (width.max(0) as usize, height.max(0) as usize);
+    let mut removed = 0;
+    if let Some(children) = level["__children"].as_array_mut() {
+        for child in children {
+            let is_grid = child["__name"].as_str().map_or(false, |n| TILE_GRIDS.contains(&n));
+            if !is_grid { continue; }
+            if let Some(text) = child["innerText"].as_str() {
+                let (trimmed, n) = trim_tile_text(text, width, height);
+                if n > 0 {
+                    child["innerText"] = serde_json::json!(trimmed);
+                    removed += n;
+                }
+            }
+        }
+    }
+    removed
+}
+
+/// Trim one room to its bounds as one undoable edit. Returns the number of tiles removed.
+pub fn trim_room_to_bounds(editor: &mut CelesteMapEditor, index: usize) -> usize {
+    let mut removed = 0;
+    edit_room(editor, index, TRIM_DESCRIPTION, |level| removed = trim_level(level));
+    if removed > 0 {
+        info!("Trimmed {} out-of-bounds tiles from room {}", removed, index);
+    }
+    removed
+}
+
+/// Trim every room to its bounds, recording one undo entry per room touched and caching the rooms
+/// once. Returns (rooms touched, tiles removed).
+pub fn trim_map_to_bounds(editor: &mut CelesteMapEditor) -> (usize, usize) {
+    let mut rooms = 0;
+    let mut removed = 0;
+    let mut index = 0;
+    while let Some(level) = editor.get_level_mut(index) {
+        let before = level.clone();
+        let n = trim_level(level);
+        if n > 0 {
+            let after = level.clone();
+            editor.history.push(EditCommand::LevelEdit { room_idx: index, description: TRIM_DESCRIPTION.to_string(), before, after });
+            rooms += 1;
+            removed += n;
+        }
+        index += 1;
+    }
+    if removed > 0 {
+        info!("Trimmed {} out-of-bounds tiles across {} rooms", removed, rooms);
+        editor.cache_rooms();
+        editor.is_dirty = true;
+    }
+    (rooms, removed)
+}
@@ -3,8 +3,36 @@
 use eframe::egui;
 
 use crate::app::CelesteMapEditor;
+use crate::app::tabs::{close_tab, tab_index, tab_title};
+use crate::data::assets::CelesteAssets;
+use crate::data::map_model;
+use crate::data::mods::scan_mods;
 use crate::config::keybindings::{BindingType, InputBinding, InputMode, KeyBindings};
-use crate::map::loader::load_map;
+use crate::map::changes::{refresh_changes, RoomStatus};
+use crate::map::history::{jump_to, set_entity_property, HistoryEntry};
+use crate::map::image_import::{apply_image_import, IMPORT_SCALES, MAX_IMPORT_TILES};
+use crate::map::loader::{load_map, load_mod_map, new_map, reload_map};
+use crate::map::stamps::{load_stamps, save_stamp, Stamp};
+use crate::map::meta::{meta_path, INTRO_TYPES};
+use crate::map::search::find_objects;
+use crate::map::triggers::{add_trigger, bounds_rect};
+use crate::data::trigger_catalogue::TRIGGER_NAMES;
+use crate::map::rooms::{music_track_name, nearest_room, room_music, room_rect, set_room_color, set_room_position, set_room_rect, snap_to_tile};
+use crate::map::validation::{clear_hidden_bg, count_hidden_bg, trim_map_to_bounds, trim_room_to_bounds};
+use crate::data::tile_xml::{self, ensure_tileset_id_path_map_loaded_from_celeste};
+use crate::config::room_colors::save_room_colors;
+use crate::config::view_settings::save_view_settings;
+use crate::ui::input::{fit_room_in_view, move_view_to};
+use crate::ui::render::{toggle_solo_layer, LayerId, ROOM_COLORS, SELECTION_COLOR, SOLID_TILE_COLOR};
+
+// Largest edge of a stamp preview, in points
+const STAMP_PREVIEW_SIZE: f32 = 64.0;
+// Edge of the Import Room from Image preview, in points
+const IMAGE_IMPORT_PREVIEW_SIZE: f32 = 240.0;
+// Height of the Mods list in the Open dialog, in points
+const MODS_TREE_HEIGHT: f32 = 240.0;
+// Edge of a room colour swatch, in points
+const COLOR_SWATCH_SIZE: f32 = 18.0;
 
 pub fn show_open_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
     egui::Window::new("Open Map File")
@@ -12,10 +40,9 @@ pub fn show_open_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
         .resizable(false)
         .show(ctx, |ui| {
             ui.horizontal(|ui| {
-                let mut path = editor.bin_path.clone().unwrap_or_default();
                 ui.label("File path:");
-                if ui.text_edit_singleline(&mut path).changed() {
-                    editor.bin_path = Some(path);
+                if ui.text_edit_singleline(&mut editor.open_path).changed() {
+                    editor.open_mod_map = None;
                 }
 
                 if ui.button("Browse...").clicked() {
@@ -46,7 +73,8 @@ pub fn show_open_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
                         }
                     }
                     if let Some(path) = dialog.pick_file() {
-                        editor.bin_path = Some(path.display().to_string());
+                        editor.open_path = path.display().to_string();
+                        editor.open_mod_map = None;
                     }
                 }
             });
@@ -71,15 +99,22 @@ pub fn show_open_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
                 }
             }
 
+            ui.add_space(5.0);
+            show_mods_tree(editor, ui);
+
             ui.horizontal(|ui| {
                 if ui.button("Cancel").clicked() {
                     editor.show_open_dialog = false;
                 }
 
                 if ui.button("Open").clicked() {
-                    let path_clone = editor.bin_path.clone();
-                    if let Some(path) = path_clone {
-                        load_map(editor, &path);
+                    match editor.open_mod_map.take() {
+                        Some((mod_name, map)) => load_mod_map(editor, &mod_name, &map),
+                        None if !editor.open_path.is_empty() => {
+                            let path = editor.open_path.clone();
+                            load_map(editor, &path);
+                        }
+                        None => {}
                     }
                     editor.show_open_dialog = false;
                 }
@@ -87,6 +122,149 @@ pub fn show_open_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
         });
 }
 
+/// Mods section of the Open dialog: mod, then campaign folder, then map. Click a map to pick it,
+/// double click to open it. Maps of zipped mods open read-only.
+fn show_mods_tree(editor: &mut CelesteMapEditor, ui: &mut egui::Ui) {
+    egui::CollapsingHeader::new("Mods").default_open(editor.last_mod.is_some()).show(ui, |ui| {
+        let Some(mods_dir) = editor.celeste_assets.mods_dir() else {
+            ui.label("Set the Celeste path to list the maps of installed mods.");
+            return;
+        };
+        if editor.mod_projects.is_none() || ui.small_button("Rescan").clicked() {
+            editor.mod_projects = Some(scan_mods(&mods_dir));
+        }
+        let projects = editor.mod_projects.as_deref().unwrap_or_default();
+        if projects.is_empty() {
+            ui.label(format!("No mods with maps in {}", mods_dir.display()));
+            return;
+        }
+        let selected = editor.open_mod_map.as_ref().map(|(_, map)| map);
+        let mut picked = None;
+        egui::ScrollArea::vertical().max_height(MODS_TREE_HEIGHT).show(ui, |ui| {
+            for project in projects {
+                let is_last = editor.last_mod.as_deref() == Some(project.name.as_str());
+                let title = if project.zipped { format!("{} (zip)", project.name) } else { project.name.clone() };
+                egui::CollapsingHeader::new(title).id_source(&project.dir).default_open(is_last).show(ui, |ui| {
+                    for (campaign, maps) in project.campaigns() {
+                        let mut show_maps = |ui: &mut egui::Ui| {
+                            for map in &maps {
+                                let hover = match &map.zip_entry {
+                                    Some(entry) => format!("{} in {}", entry, map.path.display()),
+                                    None => map.path.display().to_string(),
+                                };
+                                let response = ui.selectable_label(selected == Some(*map), &map.name).on_hover_text(hover);
+                                if response.clicked() || response.double_clicked() {
+                                    picked = Some((project.name.clone(), (*map).clone(), response.double_clicked()));
+                                }
+                            }
+                        };
+                        if campaign.is_empty() {
+                            show_maps(ui);
+                        } else {
+                            egui::CollapsingHeader::new(campaign).id_source((&project.dir, campaign)).default_open(is_last).show(ui, show_maps);
+                        }
+                    }
+                });
+            }
+        });
+        if let Some((mod_name, map, open)) = picked {
+            if open {
+                load_mod_map(editor, &mod_name, &map);
+                editor.open_mod_map = None;
+                editor.show_open_dialog = false;
+                return;
+            }
+            if map.zip_entry.is_none() {
+                editor.open_path = map.path.display().to_string();
+            }
+            editor.open_mod_map = Some((mod_name, map));
+        }
+    });
+}
+
+pub fn show_new_map_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    let mut open = true;
+    egui::Window::new("New Map")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label("Creates an unsaved map with one empty room and a player spawn.");
+            ui.add_space(5.0);
+            egui::Grid::new("new_map_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Room name:");
+                ui.text_edit_singleline(&mut editor.new_map_room_name);
+                ui.end_row();
+                ui.label("Width (tiles):");
+                ui.add(egui::DragValue::new(&mut editor.new_map_width).clamp_range(1..=1000));
+                ui.end_row();
+                ui.label("Height (tiles):");
+                ui.add(egui::DragValue::new(&mut editor.new_map_height).clamp_range(1..=1000));
+                ui.end_row();
+            });
+            if editor.map_data.is_some() {
+                ui.colored_label(egui::Color32::from_rgb(220, 160, 60), "Unsaved changes to the open map will be lost.");
+            }
+            ui.add_space(10.0);
+            let valid = !editor.new_map_room_name.trim().is_empty();
+            if ui.add_enabled(valid, egui::Button::new("Create")).clicked() {
+                let name = editor.new_map_room_name.trim().to_string();
+                new_map(editor, &name, editor.new_map_width, editor.new_map_height);
+                editor.notifications.info("New map created; use File > Save As... to write it to disk");
+                editor.show_new_map_dialog = false;
+            }
+        });
+    if !open {
+        editor.show_new_map_dialog = false;
+    }
+}
+
+pub fn show_reload_confirm_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    egui::Window::new("Reload Map?")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.label("The map has unsaved changes. Reloading it from disk will discard them.");
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                if ui.button("Reload").clicked() {
+                    editor.show_reload_confirm = false;
+                    reload_map(editor);
+                }
+                if ui.button("Cancel").clicked() {
+                    editor.show_reload_confirm = false;
+                }
+            });
+        });
+}
+
+pub fn show_close_tab_confirm_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    let Some(id) = editor.close_tab_confirm else { return };
+    // The tab may have been closed another way meanwhile
+    let Some(idx) = tab_index(editor, id) else {
+        editor.close_tab_confirm = None;
+        return;
+    };
+    egui::Window::new("Close Tab?")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.label(format!("{} has unsaved changes. Closing the tab will discard them.", tab_title(editor, idx)));
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                if ui.button("Close").clicked() {
+                    editor.close_tab_confirm = None;
+                    close_tab(editor, idx);
+                }
+                if ui.button("Cancel").clicked() {
+                    editor.close_tab_confirm = None;
+                }
+            });
+        });
+}
+
 pub fn show_key_bindings_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
     egui::Window::new("Key Bindings")
         .collapsible(false)
@@ -103,8 +281,26 @@ pub fn show_key_bindings_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Conte
             render_binding_selector(editor, ui, "Remove Block:", BindingType::RemoveBlock);
             render_binding_selector(editor, ui, "Zoom In:", BindingType::ZoomIn);
             render_binding_selector(editor, ui, "Zoom Out:", BindingType::ZoomOut);
+            render_binding_selector(editor, ui, "Reset View:", BindingType::ResetView);
             render_binding_selector(editor, ui, "Save (Ctrl+):", BindingType::Save);
             render_binding_selector(editor, ui, "Open (Ctrl+):", BindingType::Open);
+            render_binding_selector(editor, ui, "Go To Room # (Ctrl+Shift+):", BindingType::GoToRoom);
+
+            ui.add_space(10.0);
+            ui.label(egui::RichText::new("View toggles").strong());
+            render_binding_selector(editor, ui, "Toggle Grid:", BindingType::ToggleGrid);
+            render_binding_selector(editor, ui, "Toggle Labels:", BindingType::ToggleLabels);
+            render_binding_selector(editor, ui, "Toggle All Rooms:", BindingType::ToggleAllRooms);
+            render_binding_selector(editor, ui, "Toggle Textures:", BindingType::ToggleTextures);
+            render_binding_selector(editor, ui, "Toggle Fg Decals:", BindingType::ToggleFgDecals);
+
+            ui.add_space(10.0);
+            ui.label(egui::RichText::new("Tools").strong());
+            render_binding_selector(editor, ui, "Eyedropper:", BindingType::Eyedropper);
+            render_binding_selector(editor, ui, "Flood Fill:", BindingType::FloodFill);
+            render_binding_selector(editor, ui, "Line:", BindingType::LineTool);
+            render_binding_selector(editor, ui, "Rectangle Select (Alt+):", BindingType::RectSelect);
+            render_binding_selector(editor, ui, "Move:", BindingType::MoveTool);
             
             ui.add_space(20.0);
             
@@ -200,6 +396,20 @@ fn render_binding_selector(editor: &mut CelesteMapEditor, ui: &mut egui::Ui, lab
     });
 }
 
+/// A folder with a green check if it holds Celeste's ForegroundTiles.xml, or a red cross if not.
+fn celeste_path_row(ui: &mut egui::Ui, path: &std::path::Path) {
+    let xml = CelesteAssets::foreground_tiles_xml(path);
+    ui.horizontal(|ui| {
+        let mark = if xml.exists() {
+            ui.colored_label(egui::Color32::from_rgb(90, 200, 90), "✔")
+        } else {
+            ui.colored_label(egui::Color32::from_rgb(230, 90, 90), "✗")
+        };
+        mark.on_hover_text(xml.display().to_string());
+        ui.label(path.display().to_string());
+    });
+}
+
 pub fn show_celeste_path_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
     egui::Window::new("Celeste Installation Path")
         .collapsible(false)
@@ -215,9 +425,43 @@ pub fn show_celeste_path_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Conte
                 ui.label("This is needed to load textures for the map editor.");
             } else {
                 ui.label("Current Celeste installation path:");
-                ui.label(editor.celeste_assets.celeste_dir.as_ref().unwrap().display().to_string());
+                celeste_path_row(ui, editor.celeste_assets.celeste_dir.as_ref().unwrap());
                 ui.label("You can change the path if needed.");
             }
+            // A rejected pick isn't the current path, so show it too
+            if let Some(picked) = editor.celeste_path_picked.as_ref().filter(|p| editor.celeste_assets.celeste_dir.as_ref() != Some(*p)) {
+                ui.label("Selected folder:");
+                celeste_path_row(ui, picked);
+            }
+
+            match &editor.celeste_assets.game_version {
+                Some(version) => {
+                    ui.label(format!("Detected Celeste version: {}", version));
+                    if !CelesteAssets::is_version_supported(version) {
+                        ui.colored_label(
+                            egui::Color32::from_rgb(220, 180, 60),
+                            "⚠ This Celeste version is older than Summit supports; some features may not work correctly.",
+                        );
+                    }
+                }
+                None if editor.celeste_assets.celeste_dir.is_some() => {
+                    ui.label("Celeste version: unknown");
+                }
+                None => {}
+            }
+
+            if let Some(error) = &editor.celeste_path_error {
+                ui.colored_label(egui::Color32::from_rgb(230, 90, 90), error);
+            }
+            if !editor.atlas_loads.is_empty() {
+                ui.label("Loading textures...");
+            } else if !editor.asset_problems.is_empty() {
+                ui.add_space(5.0);
+                ui.colored_label(egui::Color32::from_rgb(220, 180, 60), "⚠ Some game files could not be read:");
+                for problem in &editor.asset_problems {
+                    ui.label(problem);
+                }
+            }
             
             ui.add_space(10.0);
                 
@@ -226,11 +470,19 @@ pub fn show_celeste_path_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Conte
                     if let Some(path) = rfd::FileDialog::new()
                         .set_title("Select Celeste Installation Folder")
                         .pick_folder() {
-                        if !editor.celeste_assets.set_celeste_dir(&path) {
-                            editor.error_message = Some("Invalid Celeste installation directory.".to_string());
+                        editor.celeste_path_picked = Some(path.clone());
+                        match editor.celeste_assets.set_celeste_dir(&path) {
+                            Ok(()) => {
+                                editor.celeste_path_error = None;
+                                editor.recheck_assets();
+                            }
+                            Err(e) => editor.celeste_path_error = Some(e),
                         }
                     }
                 }
+                if ui.add_enabled(editor.celeste_assets.celeste_dir.is_some(), egui::Button::new("Re-check Assets")).clicked() {
+                    editor.recheck_assets();
+                }
                 
                 ui.checkbox(&mut editor.use_textures, "Use textures when available");
             });
@@ -252,4 +504,1160 @@ pub fn show_celeste_path_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Conte
                 });
             });
         });
-}
\ No newline at end of file
+}
+
+pub fn show_stamps_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    let mut open = true;
+    egui::Window::new("Stamps")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .default_width(280.0)
+        .show(ctx, |ui| {
+            ui.label("Shift+drag to select tiles, Ctrl+C to copy, Ctrl+V to paste.");
+            ui.add_space(5.0);
+
+            ui.horizontal(|ui| {
+                ui.label("Name:");
+                ui.text_edit_singleline(&mut editor.new_stamp_name);
+            });
+            ui.horizontal(|ui| {
+                let can_save = editor.clipboard.is_some() && !editor.new_stamp_name.trim().is_empty();
+                if ui.add_enabled(can_save, egui::Button::new("Save Clipboard as Stamp")).clicked() {
+                    if let Some(clip) = &editor.clipboard {
+                        let stamp = Stamp::from_clipboard(editor.new_stamp_name.trim(), clip);
+                        match save_stamp(&stamp) {
+                            Ok(()) => {
+                                editor.new_stamp_name.clear();
+                                editor.stamps = load_stamps();
+                            }
+                            Err(e) => editor.notifications.error(format!("Failed to save stamp: {}", e)),
+                        }
+                    }
+                }
+                if ui.button("Refresh").clicked() {
+                    editor.stamps = load_stamps();
+                }
+            });
+
+            ui.separator();
+
+            if editor.stamps.is_empty() {
+                ui.label("No stamps saved yet.");
+            }
+            let mut picked = None;
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for (i, stamp) in editor.stamps.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        draw_stamp_preview(ui, stamp);
+                        ui.vertical(|ui| {
+                            ui.label(egui::RichText::new(&stamp.name).strong());
+                            let width = stamp.tiles.iter().map(|r| r.chars().count()).max().unwrap_or(0);
+                            ui.label(format!("{}x{} tiles, {} objects", width, stamp.tiles.len(), stamp.objects.len()));
+                            if ui.button("Use").clicked() {
+                                picked = Some(i);
+                            }
+                        });
+                    });
+                    ui.separator();
+                }
+            });
+            if let Some(i) = picked {
+                editor.clipboard = Some(editor.stamps[i].to_clipboard());
+            }
+        });
+    if !open {
+        editor.show_stamps_dialog = false;
+    }
+}
+
+fn draw_stamp_preview(ui: &mut egui::Ui, stamp: &Stamp) {
+    draw_tiles_preview(ui, &stamp.tiles, STAMP_PREVIEW_SIZE);
+}
+
+/// Solid tiles of `rows` scaled into a `size` square, one rectangle per run of solids in a row.
+fn draw_tiles_preview(ui: &mut egui::Ui, rows: &[String], size: f32) {
+    let w = rows.iter().map(|r| r.chars().count()).max().unwrap_or(0).max(1) as f32;
+    let h = rows.len().max(1) as f32;
+    let cell = (size / w.max(h)).min(8.0);
+    let (response, painter) = ui.allocate_painter(egui::vec2(size, size), egui::Sense::hover());
+    painter.rect_filled(response.rect, 0.0, egui::Color32::from_rgb(20, 20, 20));
+    for (y, row) in rows.iter().enumerate() {
+        let mut run_start = None;
+        // A trailing air cell closes the last run
+        for (x, c) in row.chars().chain(std::iter::once('0')).enumerate() {
+            let solid = c != '0' && c != ' ';
+            match (solid, run_start) {
+                (true, None) => run_start = Some(x),
+                (false, Some(start)) => {
+                    let min = response.rect.min + egui::vec2(start as f32 * cell, y as f32 * cell);
+                    let run = egui::vec2((x - start) as f32 * cell, cell);
+                    painter.rect_filled(egui::Rect::from_min_size(min, run), 0.0, SOLID_TILE_COLOR);
+                    run_start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+pub fn show_map_report(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    let mut open = true;
+    egui::Window::new("Map Report")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            if editor.cached_rooms.is_empty() {
+                ui.label("No map loaded.");
+                return;
+            }
+            let total = editor.map_stats();
+            ui.label(format!("{} rooms: {}", editor.cached_rooms.len(), total.summary()));
+            ui.weak("Per-room counts are in the room list and Room Properties.");
+        });
+    if !open {
+        editor.show_map_report = false;
+    }
+}
+
+pub fn show_validation_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    let mut open = true;
+    egui::Window::new("Validation")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .show(ctx, |ui| {
+            let flagged: Vec<(usize, String, usize)> = editor.cached_rooms.iter().enumerate()
+                .filter(|(_, r)| r.overflow_tiles > 0)
+                .map(|(i, r)| (i, r.level_data.name.clone(), r.overflow_tiles))
+                .collect();
+
+            let hidden: Vec<(usize, String, usize)> = if editor.lint_hidden_bg {
+                editor.cached_rooms.iter().enumerate()
+                    .map(|(i, r)| (i, r.level_data.name.clone(), count_hidden_bg(&r.level_data.solids, &r.level_data.bg)))
+                    .filter(|(_, _, n)| *n > 0)
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            if flagged.is_empty() && hidden.is_empty() {
+                ui.label("No problems found.");
+            } else if !flagged.is_empty() {
+                ui.label(egui::RichText::new("Rooms with tiles outside their bounds:").strong());
+                for (i, name, count) in &flagged {
+                    ui.horizontal(|ui| {
+                        ui.colored_label(egui::Color32::from_rgb(220, 180, 60), "⚠");
+                        ui.label(format!("{}: {} tiles", name, count));
+                        if ui.button("Trim to room bounds").clicked() {
+                            let removed = trim_room_to_bounds(editor, *i);
+                            editor.validation_report = Some(format!("Removed {} tiles from {}.", removed, name));
+                        }
+                    });
+                }
+                ui.add_space(5.0);
+                if ui.button("Trim all rooms to bounds").clicked() {
+                    let (rooms, removed) = trim_map_to_bounds(editor);
+                    editor.validation_report = Some(format!("Removed {} tiles from {} rooms.", removed, rooms));
+                }
+            }
+
+            ui.separator();
+            ui.checkbox(&mut editor.lint_hidden_bg, "Check for bg tiles hidden under solids")
+                .on_hover_text("The game never shows them, but they show up as seams if the solid is removed later");
+            if !hidden.is_empty() {
+                ui.label(egui::RichText::new("Rooms with bg tiles under solids:").strong());
+                for (i, name, count) in &hidden {
+                    ui.horizontal(|ui| {
+                        ui.colored_label(egui::Color32::from_rgb(220, 180, 60), "⚠");
+                        ui.label(format!("{}: {} tiles", name, count));
+                        if ui.button("Clear hidden bg tiles").clicked() {
+                            let cleared = clear_hidden_bg(editor, *i);
+                            editor.validation_report = Some(format!("Cleared {} hidden bg tiles from {}.", cleared, name));
+                        }
+                    });
+                }
+            }
+
+            if let Some(report) = &editor.validation_report {
+                ui.separator();
+                ui.label(report);
+            }
+        });
+    if !open {
+        editor.show_validation = false;
+    }
+}
+
+/// How long ago something happened, coarsely: "12 s ago", "5 min ago", "2 h 10 min ago".
+fn format_age(age: std::time::Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..=59 => format!("{} s ago", secs),
+        60..=3599 => format!("{} min ago", secs / 60),
+        _ => format!("{} h {} min ago", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+pub fn show_history_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    let mut open = true;
+    let mut jump = None;
+    let current_room = editor.current_level_index;
+    egui::Window::new("History")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            ui.label("Click an entry to undo or redo up to it.");
+            ui.checkbox(&mut editor.history_room_only, "Only edits to the current room");
+            ui.separator();
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                let entries = &editor.history.entries;
+                let position = editor.history.position;
+                let entry_text = |entry: &HistoryEntry| {
+                    let room = editor.level_names.get(entry.command.room_idx()).map(String::as_str).unwrap_or("?");
+                    format!("{} ({}), {}", entry.command.description(), room, format_age(entry.time.elapsed()))
+                };
+                let hidden = |entry: &HistoryEntry| editor.history_room_only && entry.command.room_idx() != current_room;
+                // What a jump to `target` also undoes or redoes in the hidden rooms
+                let crossed_note = |target: usize| {
+                    let crossed = entries[position.min(target)..position.max(target)].iter().filter(|e| hidden(e)).count();
+                    let verb = if target < position { "undoes" } else { "redoes" };
+                    (crossed > 0).then(|| format!("Also {} {} edits to other rooms", verb, crossed))
+                };
+                let mut response = ui.selectable_label(position == 0, "Original map");
+                if let Some(note) = crossed_note(0) {
+                    response = response.on_hover_text(note);
+                }
+                if response.clicked() {
+                    jump = Some(0);
+                }
+                let mut i = 0;
+                while i < entries.len() {
+                    // Filtered entries still count, since a jump undoes or redoes every room up to it;
+                    // each run of them stays as one line listing what it holds
+                    if hidden(&entries[i]) {
+                        let end = entries[i..].iter().position(|e| !hidden(e)).map_or(entries.len(), |n| i + n);
+                        let listed: Vec<String> = entries[i..end].iter().map(entry_text).collect();
+                        let mut text = egui::RichText::new(format!("⋯ {} edits to other rooms", end - i)).italics();
+                        if i >= position {
+                            text = text.weak();
+                        }
+                        ui.label(text).on_hover_text(listed.join("\n"));
+                        i = end;
+                        continue;
+                    }
+                    let mut text = egui::RichText::new(entry_text(&entries[i]));
+                    if i >= position {
+                        text = text.weak();
+                    }
+                    let mut response = ui.selectable_label(position == i + 1, text);
+                    if let Some(note) = crossed_note(i + 1) {
+                        response = response.on_hover_text(note);
+                    }
+                    if response.clicked() {
+                        jump = Some(i + 1);
+                    }
+                    i += 1;
+                }
+            });
+        });
+    if let Some(position) = jump {
+        jump_to(editor, position);
+    }
+    if !open {
+        editor.show_history = false;
+    }
+}
+
+pub fn show_review_changes_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    let mut open = true;
+    let mut jump = None;
+    egui::Window::new("Review Changes")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            ui.label("Rooms changed since the map was opened or last saved. Click one to go to it.");
+            if ui.checkbox(&mut editor.show_change_overlay, "Highlight changed tiles on the canvas").changed() {
+                refresh_changes(editor);
+            }
+            ui.separator();
+            if editor.map_changes.is_empty() {
+                ui.label("No changes.");
+            }
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for change in &editor.map_changes {
+                    let (status, color) = match &change.status {
+                        RoomStatus::Added => ("added".to_string(), egui::Color32::from_rgb(120, 200, 120)),
+                        RoomStatus::Removed => ("removed".to_string(), egui::Color32::from_rgb(220, 100, 90)),
+                        RoomStatus::Renamed { from } => (format!("renamed from {}", from), egui::Color32::from_rgb(220, 180, 60)),
+                        RoomStatus::Edited => ("edited".to_string(), ui.visuals().text_color()),
+                    };
+                    let summary = change.summary();
+                    let text = if summary.is_empty() {
+                        format!("{}: {}", change.name, status)
+                    } else {
+                        format!("{}: {} ({})", change.name, status, summary)
+                    };
+                    let label = egui::RichText::new(text).color(color);
+                    match change.room_idx {
+                        Some(idx) => {
+                            if ui.selectable_label(idx == editor.current_level_index, label).clicked() {
+                                jump = Some(idx);
+                            }
+                        }
+                        None => {
+                            ui.label(label);
+                        }
+                    }
+                }
+            });
+        });
+    if let Some(idx) = jump {
+        editor.current_level_index = idx;
+        let view = editor.canvas_rect.unwrap_or_else(|| ctx.available_rect());
+        fit_room_in_view(editor, idx, view);
+    }
+    if !open {
+        editor.show_review_changes = false;
+    }
+}
+
+// Entity attributes the property editor leaves alone
+const HIDDEN_ENTITY_KEYS: [&str; 3] = ["__name", "__children", "id"];
+
+pub fn show_entity_properties_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    let mut open = true;
+    let mut edits = Vec::new();
+    let room_idx = editor.current_level_index;
+    // Open once on the inspected entity, then leave the sections to the user
+    let inspect = editor.inspect_entity.take();
+    egui::Window::new("Entity Properties")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .default_width(300.0)
+        .show(ctx, |ui| {
+            let Some(level) = editor.get_current_level_json() else {
+                ui.label("No room selected.");
+                return;
+            };
+            let entities = level["__children"].as_array().into_iter().flatten()
+                .filter(|c| c["__name"] == "entities" || c["__name"] == "triggers")
+                .flat_map(|c| c["__children"].as_array().into_iter().flatten());
+            egui::ScrollArea::vertical().max_height(500.0).show(ui, |ui| {
+                for entity in entities {
+                    let Some(id) = entity["id"].as_i64() else { continue };
+                    let Some(attrs) = entity.as_object() else { continue };
+                    let name = entity["__name"].as_str().unwrap_or("entity");
+                    let header = egui::CollapsingHeader::new(format!("{} #{}", name, id)).id_source(("entity_props", id));
+                    let header = if inspect == Some(id) { header.open(Some(true)) } else { header };
+                    let shown = header.show(ui, |ui| {
+                        egui::Grid::new(("entity_grid", id)).num_columns(2).show(ui, |ui| {
+                            for (key, value) in attrs.iter().filter(|(k, _)| !HIDDEN_ENTITY_KEYS.contains(&k.as_str())) {
+                                ui.label(key);
+                                if let Some(new_value) = edit_value(ui, value) {
+                                    edits.push((id, key.clone(), new_value));
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    });
+                    if inspect == Some(id) {
+                        shown.header_response.scroll_to_me(Some(egui::Align::TOP));
+                    }
+                }
+            });
+        });
+    for (id, key, value) in edits {
+        set_entity_property(editor, room_idx, id, &key, value);
+    }
+    if !open {
+        editor.show_entity_properties = false;
+    }
+}
+
+/// Name the trigger just drawn with the Triggers tool: a vanilla one from the catalogue or a typed
+/// modded name. Closing the window drops the rectangle.
+pub fn show_new_trigger_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    let Some(mut pending) = editor.new_trigger.take() else { return };
+    let near = bounds_rect(editor, pending.room_idx, pending.bounds).map_or(egui::Pos2::ZERO, |r| r.right_top());
+    let mut open = true;
+    let mut chosen = None;
+    egui::Window::new("New Trigger")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .default_pos(near)
+        .show(ctx, |ui| {
+            let (x, y, w, h) = pending.bounds;
+            ui.label(format!("{}x{} px at ({}, {})", w, h, x, y));
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                for name in TRIGGER_NAMES {
+                    if ui.selectable_label(false, name).clicked() {
+                        chosen = Some(name.to_string());
+                    }
+                }
+            });
+            ui.separator();
+            ui.horizontal(|ui| {
+                let field = ui.add(egui::TextEdit::singleline(&mut pending.custom_name).hint_text("Modded trigger name"));
+                let name = pending.custom_name.trim();
+                let submitted = field.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+                if (ui.add_enabled(!name.is_empty(), egui::Button::new("Add")).clicked() || submitted) && !name.is_empty() {
+                    chosen = Some(name.to_string());
+                }
+            });
+        });
+    match chosen {
+        Some(name) => add_trigger(editor, pending.room_idx, &name, pending.bounds),
+        None if open => editor.new_trigger = Some(pending),
+        None => {}
+    }
+}
+
+pub fn show_image_import_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    let Some(mut import) = editor.image_import.take() else { return };
+    let mut open = true;
+    let mut apply = false;
+    let current_room = editor.level_names.get(editor.current_level_index).cloned();
+    egui::Window::new("Import Room from Image")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(import.path.file_name().map_or(String::new(), |n| n.to_string_lossy().to_string()));
+            let mut changed = false;
+            egui::Grid::new("image_import_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Darkness threshold:");
+                changed |= ui.add(egui::Slider::new(&mut import.threshold, 1..=255))
+                    .on_hover_text("Pixels darker than this become solid")
+                    .changed();
+                ui.end_row();
+                ui.label("Pixels per tile:");
+                ui.horizontal(|ui| {
+                    for scale in IMPORT_SCALES {
+                        changed |= ui.radio_value(&mut import.scale, scale, scale.to_string()).changed();
+                    }
+                });
+                ui.end_row();
+                ui.label("Tile:");
+                ui.label(format!("'{}' (the brush tile)", editor.brush_tile));
+                ui.end_row();
+            });
+            if changed {
+                import.refresh(editor.brush_tile);
+            }
+            ui.separator();
+            ui.radio_value(&mut import.replace_current, false, "Add a new room");
+            ui.add_enabled_ui(!import.replace_current, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Room name:");
+                    ui.text_edit_singleline(&mut import.room_name);
+                });
+            });
+            let replace_label = match &current_room {
+                Some(name) => format!("Replace the solids of {}", name),
+                None => "Replace the solids of the current room".to_string(),
+            };
+            if ui.add_enabled(current_room.is_some(), egui::RadioButton::new(import.replace_current, replace_label)).clicked() {
+                import.replace_current = true;
+            }
+            ui.separator();
+            let (width, height) = import.size();
+            ui.label(format!("{}x{} tiles", width, height));
+            if import.cropped {
+                ui.colored_label(egui::Color32::from_rgb(220, 160, 60),
+                    format!("Cropped to {} tiles per side; use more pixels per tile to fit the whole image.", MAX_IMPORT_TILES));
+            }
+            draw_tiles_preview(ui, &import.solids, IMAGE_IMPORT_PREVIEW_SIZE);
+            ui.add_space(5.0);
+            let valid = import.replace_current || !import.room_name.trim().is_empty();
+            if ui.add_enabled(valid, egui::Button::new("Import")).clicked() {
+                apply = true;
+            }
+        });
+    if apply {
+        // The brush may have changed while the window was open
+        import.refresh(editor.brush_tile);
+        match apply_image_import(editor, &import) {
+            Ok(name) => editor.notifications.info(format!("Imported {} into {}", import.path.display(), name)),
+            Err(e) => editor.notifications.error(format!("Could not import the image: {}", e)),
+        }
+    } else if open {
+        editor.image_import = Some(import);
+    }
+}
+
+/// Widget for one attribute; returns the new value if it was changed.
+fn edit_value(ui: &mut egui::Ui, value: &serde_json::Value) -> Option<serde_json::Value> {
+    use serde_json::Value;
+    match value {
+        Value::Bool(b) => {
+            let mut b = *b;
+            ui.checkbox(&mut b, "").changed().then(|| Value::Bool(b))
+        }
+        Value::Number(n) if n.is_i64() || n.is_u64() => {
+            let mut v = n.as_i64().unwrap_or(0);
+            ui.add(egui::DragValue::new(&mut v)).changed().then(|| serde_json::json!(v))
+        }
+        Value::Number(n) => {
+            let mut v = n.as_f64().unwrap_or(0.0);
+            ui.add(egui::DragValue::new(&mut v).speed(0.1)).changed().then(|| serde_json::json!(v))
+        }
+        Value::String(s) => {
+            let mut s = s.clone();
+            ui.text_edit_singleline(&mut s).changed().then(|| Value::String(s))
+        }
+        other => {
+            ui.label(other.to_string());
+            None
+        }
+    }
+}
+
+/// Pick a tileset by id and save the graphic the editor uses for it.
+pub fn show_export_tileset_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    ensure_tileset_id_path_map_loaded_from_celeste(editor);
+    let mut open = true;
+    let mut export = None;
+    egui::Window::new("Export Tileset PNG")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .default_width(300.0)
+        .show(ctx, |ui| {
+            if !editor.atlas_manager.as_ref().map_or(false, |am| am.has_atlas("Gameplay")) {
+                ui.label("The Gameplay atlas is not loaded; set the Celeste directory first.");
+                return;
+            }
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                let layers = [
+                    ("Foreground", false, tile_xml::TILESET_ID_PATH_MAP_FG.get()),
+                    ("Background", true, tile_xml::TILESET_ID_PATH_MAP_BG.get()),
+                ];
+                for (title, bg, map) in layers {
+                    ui.label(egui::RichText::new(title).strong());
+                    let Some(map) = map else {
+                        ui.label("Tileset list not found in the Celeste install.");
+                        continue;
+                    };
+                    let mut entries: Vec<(&char, &String)> = map.iter().collect();
+                    entries.sort();
+                    for (&id, path) in entries {
+                        let selected = editor.export_tileset == Some((bg, id));
+                        if ui.selectable_label(selected, format!("{}  {}", id, path)).clicked() {
+                            editor.export_tileset = Some((bg, id));
+                        }
+                    }
+                }
+            });
+            ui.separator();
+            let path = editor.export_tileset.and_then(|(bg, id)| {
+                let map = if bg { tile_xml::TILESET_ID_PATH_MAP_BG.get() } else { tile_xml::TILESET_ID_PATH_MAP_FG.get() };
+                tile_xml::get_tileset_path_for_id(map?, id).map(str::to_string)
+            });
+            if ui.add_enabled(path.is_some(), egui::Button::new("Export Tileset PNG...")).clicked() {
+                export = path;
+            }
+        });
+    if let Some(path) = export {
+        let file_name = format!("{}.png", path.rsplit('/').next().unwrap_or(&path));
+        if let Some(out) = rfd::FileDialog::new()
+            .set_title("Export Tileset PNG")
+            .set_file_name(&file_name)
+            .add_filter("PNG Image", &["png"])
+            .save_file()
+        {
+            // Tileset paths in the XML are relative to the atlas' tilesets folder
+            let sprite_path = format!("tilesets/{}", path);
+            let result = editor.atlas_manager.as_ref()
+                .ok_or_else(|| "The Gameplay atlas is not loaded".to_string())
+                .and_then(|atlas| atlas.export_sprite("Gameplay", &sprite_path, &out));
+            match result {
+                Ok(()) => editor.notifications.info(format!("Exported {} to {}", sprite_path, out.display())),
+                Err(e) => editor.notifications.error(e),
+            }
+        }
+    }
+    if !open {
+        editor.show_export_tileset_dialog = false;
+    }
+}
+
+/// Loaded atlases and their sprite keys, to check whether a decal or tileset sprite exists.
+pub fn show_sprite_browser(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    const ROW_HEIGHT: f32 = 18.0;
+    let mut open = true;
+    egui::Window::new("Sprite Browser")
+        .open(&mut open)
+        .resizable(true)
+        .default_width(560.0)
+        .show(ctx, |ui| {
+            let Some(atlas_manager) = editor.atlas_manager.as_ref() else {
+                ui.label("No atlas is loaded; set the Celeste directory first.");
+                return;
+            };
+            let mut names: Vec<&String> = atlas_manager.atlases.keys().collect();
+            names.sort();
+            ui.horizontal(|ui| {
+                ui.label("Atlas");
+                egui::ComboBox::from_id_source("sprite_browser_atlas")
+                    .selected_text(editor.sprite_browser_atlas.as_str())
+                    .show_ui(ui, |ui| {
+                        for name in &names {
+                            ui.selectable_value(&mut editor.sprite_browser_atlas, name.to_string(), name.as_str());
+                        }
+                    });
+                ui.label("Filter");
+                ui.text_edit_singleline(&mut editor.sprite_browser_filter);
+            });
+            let Some(atlas) = atlas_manager.atlases.get(&editor.sprite_browser_atlas) else {
+                ui.label(format!("The {} atlas is not loaded.", editor.sprite_browser_atlas));
+                return;
+            };
+            ui.collapsing(format!("Data files ({})", atlas.data_files.len()), |ui| {
+                for file in &atlas.data_files {
+                    let size = atlas.page_sizes.get(file).map_or(String::new(), |[w, h]| format!("  {}x{}", w, h));
+                    ui.label(format!("{}{}", file, size));
+                }
+            });
+            let filter = editor.sprite_browser_filter.to_lowercase();
+            let mut keys: Vec<&String> = atlas.sprites.keys().filter(|k| k.to_lowercase().contains(&filter)).collect();
+            keys.sort();
+            ui.label(format!("{} of {} sprites", keys.len(), atlas.sprites.len()));
+            ui.separator();
+            ui.horizontal_top(|ui| {
+                ui.vertical(|ui| {
+                    ui.set_width(360.0);
+                    egui::ScrollArea::vertical().id_source("sprite_keys").max_height(320.0).show_rows(ui, ROW_HEIGHT, keys.len(), |ui, range| {
+                        for key in &keys[range] {
+                            let m = &atlas.sprites[*key].metadata;
+                            let selected = editor.sprite_browser_selected.as_deref() == Some(key.as_str());
+                            let text = format!("{}  ({}, {}) {}x{}  off ({}, {})", key, m.x, m.y, m.width, m.height, m.offset_x, m.offset_y);
+                            if ui.selectable_label(selected, egui::RichText::new(text).monospace()).clicked() {
+                                editor.sprite_browser_selected = Some(key.to_string());
+                            }
+                        }
+                    });
+                });
+                ui.separator();
+                ui.vertical(|ui| {
+                    let Some(key) = editor.sprite_browser_selected.clone() else {
+                        ui.label("Click a sprite to preview it.");
+                        return;
+                    };
+                    let Some(sprite) = atlas.get_sprite(&key) else {
+                        ui.label(format!("{} is not in this atlas.", key));
+                        return;
+                    };
+                    ui.label(egui::RichText::new(&key).strong());
+                    if ui.button("Copy Key").clicked() {
+                        ui.output().copied_text = key.clone();
+                    }
+                    let m = &sprite.metadata;
+                    ui.label(format!("Page {}\nx {} y {} w {} h {}\noffset {} {}, real size {}x{}",
+                        sprite.data_file, m.x, m.y, m.width, m.height, m.offset_x, m.offset_y, m.real_width, m.real_height));
+                    let size = egui::vec2(m.width.max(1) as f32, m.height.max(1) as f32);
+                    let (rect, _) = ui.allocate_exact_size(size * (160.0 / size.max_elem()).min(8.0), egui::Sense::hover());
+                    ui.painter().rect_filled(rect, 0.0, egui::Color32::from_gray(40));
+                    atlas_manager.draw_sprite(sprite, ui.painter(), rect, egui::Color32::WHITE, false, false);
+                });
+            });
+            let missing = atlas_manager.missing_sprites();
+            ui.separator();
+            ui.collapsing(format!("Used by the map but not found ({})", missing.len()), |ui| {
+                egui::ScrollArea::vertical().id_source("sprite_misses").max_height(150.0).show(ui, |ui| {
+                    for (atlas_name, path, count) in &missing {
+                        ui.horizontal(|ui| {
+                            ui.colored_label(egui::Color32::from_rgb(230, 120, 80), format!("{}: {}", atlas_name, path));
+                            ui.weak(format!("x{}", count));
+                            if ui.small_button("Copy").clicked() {
+                                ui.output().copied_text = path.clone();
+                            }
+                        });
+                    }
+                });
+            });
+        });
+    if !open {
+        editor.show_sprite_browser = false;
+    }
+}
+
+/// Jump to a room by its 0-based index in the map's room list.
+pub fn show_go_to_room_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    let mut open = true;
+    let mut go = false;
+    let room_count = editor.cached_rooms.len();
+    egui::Window::new("Go To Room")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let input = ui.add(egui::TextEdit::singleline(&mut editor.go_to_room_input).desired_width(60.0));
+                go = input.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+                // Keep typing focus in the field while the window is open
+                if !go { input.request_focus(); }
+                ui.label(format!("Room name, or number of {} rooms (0-{})", room_count, room_count.saturating_sub(1)));
+            });
+        });
+    if go {
+        let input = editor.go_to_room_input.trim();
+        // A room named like a number wins over the number
+        let target = editor.rooms_by_name.get(input).copied()
+            .or_else(|| input.parse::<usize>().ok().filter(|_| room_count > 0).map(|i| i.min(room_count - 1)));
+        match target {
+            Some(index) => {
+                editor.current_level_index = index;
+                fit_room_in_view(editor, index, ctx.available_rect());
+                editor.show_go_to_room = false;
+            }
+            None => editor.notifications.warn(format!("'{}' is not a room name or number", input)),
+        }
+    }
+    if !open {
+        editor.show_go_to_room = false;
+    }
+}
+
+/// Search entities, triggers and decals of every room by name or attribute value.
+pub fn show_find_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    let mut open = true;
+    let mut jump = None;
+    egui::Window::new("Find")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            let input = ui.add(egui::TextEdit::singleline(&mut editor.find_query).hint_text("Name, attribute value or decal path"));
+            if editor.find_focus {
+                input.request_focus();
+                editor.find_focus = false;
+            }
+            if input.changed() {
+                editor.find_results = find_objects(editor, &editor.find_query);
+            }
+            if !editor.find_query.trim().is_empty() {
+                ui.label(format!("{} results", editor.find_results.len()));
+            }
+            ui.separator();
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for (i, hit) in editor.find_results.iter().enumerate() {
+                    let room = editor.level_names.get(hit.room_idx).map(String::as_str).unwrap_or("?");
+                    let text = format!("{}: {} {} ({})", room, hit.kind, hit.name, hit.matched);
+                    if ui.selectable_label(false, text).clicked() {
+                        jump = Some(i);
+                    }
+                }
+            });
+        });
+    if let Some(hit) = jump.and_then(|i| editor.find_results.get(i).cloned()) {
+        if let Some(room) = editor.cached_rooms.get(hit.room_idx) {
+            let ld = &room.level_data;
+            let global_scale = crate::ui::transform::ViewTransform::of(editor).scale();
+            let target = egui::vec2(ld.x + hit.x as f32, ld.y + hit.y as f32) * global_scale;
+            let zoom = editor.zoom_level;
+            editor.current_level_index = hit.room_idx;
+            move_view_to(editor, target - ctx.available_rect().center().to_vec2(), zoom);
+            editor.find_flash = Some((hit.room_idx, hit.x, hit.y, std::time::Instant::now()));
+        }
+    }
+    if !open {
+        editor.show_find = false;
+    }
+}
+
+/// Values being typed into the Room Properties window, before they are applied.
+#[derive(Clone, Copy, Debug)]
+pub struct RoomPropertiesForm {
+    room_idx: usize,
+    /// Room bounds the form was filled from; a change elsewhere (undo, drag) reloads it.
+    loaded: (i64, i64, i64, i64),
+    x: i64,
+    y: i64,
+    width: i64,
+    height: i64,
+}
+
+impl RoomPropertiesForm {
+    fn new(room_idx: usize, rect: (i64, i64, i64, i64)) -> Self {
+        let (x, y, width, height) = rect;
+        Self { room_idx, loaded: rect, x, y, width, height }
+    }
+}
+
+pub fn show_room_properties_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    let mut open = true;
+    let idx = editor.current_level_index;
+    let Some(level) = editor.get_current_level() else {
+        editor.show_room_properties = false;
+        return;
+    };
+    let (x, y, width, height) = level.bounds();
+    let rect = (x.0 as i64, y.0 as i64, width.0 as i64, height.0 as i64);
+    let mut form = match editor.room_properties {
+        Some(f) if f.room_idx == idx && f.loaded == rect => f,
+        _ => RoomPropertiesForm::new(idx, rect),
+    };
+    let neighbor = nearest_room(editor, idx)
+        .and_then(|i| Some((i, room_rect(map_model::levels(editor.map_data.as_ref()?)?.get(i)?))));
+    let neighbor_name = neighbor.and_then(|(i, _)| editor.level_names.get(i)).cloned();
+    let room_name = editor.level_names.get(idx).cloned().unwrap_or_default();
+    let color_index = level.extra.get("c").and_then(serde_json::Value::as_u64).unwrap_or(0) as usize;
+    let (music, alt_music) = editor.get_current_level_json().map_or((None, None), |l| {
+        let (music, alt) = room_music(l);
+        (music.map(str::to_string), alt.map(str::to_string))
+    });
+    let map_key = editor.bin_path.clone().unwrap_or_default();
+    let mut override_color = editor.room_colors.get(&map_key).and_then(|m| m.get(&room_name)).copied();
+    let mut new_color_index = None;
+    let mut override_changed = false;
+    let mut apply = false;
+    let mut new_pos = None;
+    let brush_tile = editor.brush_tile;
+    let stats = editor.cached_rooms.get(idx).map(|r| r.stats);
+    let main_tile = stats.as_ref().and_then(|s| s.dominant_fg_tile);
+    let mut new_brush_tile = None;
+    ensure_tileset_id_path_map_loaded_from_celeste(editor);
+    let tileset_name = |id: char| tile_xml::TILESET_ID_PATH_MAP_FG.get()
+        .and_then(|m| tile_xml::get_tileset_path_for_id(m, id))
+        .unwrap_or("unknown");
+    egui::Window::new("Room Properties")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(egui::RichText::new(editor.level_names.get(idx).map(String::as_str).unwrap_or("?")).strong());
+            ui.label("Pixels; every value must be a multiple of 8.");
+            ui.add_space(5.0);
+            let step = map_model::PIXELS_PER_TILE as i64;
+            let mut valid = true;
+            egui::Grid::new("room_properties_grid").num_columns(3).show(ui, |ui| {
+                for (label, value, min) in [
+                    ("X", &mut form.x, i64::MIN),
+                    ("Y", &mut form.y, i64::MIN),
+                    ("Width", &mut form.width, step),
+                    ("Height", &mut form.height, step),
+                ] {
+                    ui.label(label);
+                    ui.add(egui::DragValue::new(value).speed(step as f64));
+                    if *value % step != 0 || *value < min {
+                        valid = false;
+                        ui.colored_label(egui::Color32::RED, if *value < min { "too small" } else { "not a multiple of 8" });
+                    }
+                    ui.end_row();
+                }
+            });
+            ui.add_space(5.0);
+            let changed = (form.x, form.y, form.width, form.height) != rect;
+            ui.horizontal(|ui| {
+                if ui.add_enabled(valid && changed, egui::Button::new("Apply")).clicked() {
+                    apply = true;
+                }
+                if ui.add_enabled(changed, egui::Button::new("Revert")).clicked() {
+                    form = RoomPropertiesForm::new(idx, rect);
+                }
+            });
+            ui.separator();
+            let hint = neighbor_name.as_deref().map_or("No other rooms".to_string(), |n| format!("Nearest room: {}", n));
+            ui.label(hint);
+            ui.horizontal(|ui| {
+                if ui.add_enabled(neighbor.is_some(), egui::Button::new("Align Left Edge to Neighbor")).clicked() {
+                    new_pos = neighbor.map(|(_, (nx, _, _, _))| (nx, rect.1));
+                }
+                if ui.add_enabled(neighbor.is_some(), egui::Button::new("Align Top Edge to Neighbor")).clicked() {
+                    new_pos = neighbor.map(|(_, (_, ny, _, _))| (rect.0, ny));
+                }
+            });
+            if ui.button("Snap Position to Nearest 8").clicked() {
+                new_pos = Some((snap_to_tile(rect.0), snap_to_tile(rect.1)));
+            }
+            if let Some(stats) = &stats {
+                ui.separator();
+                egui::Grid::new("room_stats_grid").num_columns(2).show(ui, |ui| {
+                    for (label, count) in [
+                        ("FG tiles", stats.fg_tiles),
+                        ("BG tiles", stats.bg_tiles),
+                        ("Decals", stats.decals),
+                        ("Entities", stats.entities),
+                        ("Area (tiles)", stats.area),
+                    ] {
+                        ui.label(label);
+                        ui.label(count.to_string());
+                        ui.end_row();
+                    }
+                    let (load, color) = stats.heaviness();
+                    ui.label("Load");
+                    ui.colored_label(color, load);
+                    ui.end_row();
+                });
+            }
+            ui.separator();
+            egui::Grid::new("room_music_grid").num_columns(2).show(ui, |ui| {
+                for (label, path) in [("Music", &music), ("Alt music", &alt_music)] {
+                    ui.label(label);
+                    match path {
+                        Some(path) => { ui.label(music_track_name(path)).on_hover_text(path); }
+                        None => { ui.weak("none"); }
+                    }
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Color");
+                for (i, color) in ROOM_COLORS.iter().enumerate() {
+                    let stroke = if i == color_index {
+                        egui::Stroke::new(2.0, SELECTION_COLOR)
+                    } else {
+                        egui::Stroke::new(1.0, egui::Color32::DARK_GRAY)
+                    };
+                    let swatch = egui::Button::new("").fill(*color).stroke(stroke);
+                    if ui.add_sized([COLOR_SWATCH_SIZE, COLOR_SWATCH_SIZE], swatch).on_hover_text(format!("c = {}", i)).clicked() {
+                        new_color_index = Some(i as i64);
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Editor color");
+                let mut srgb = override_color.unwrap_or([255, 255, 255]);
+                if ui.color_edit_button_srgb(&mut srgb).changed() {
+                    override_color = Some(srgb);
+                    override_changed = true;
+                }
+                if ui.add_enabled(override_color.is_some(), egui::Button::new("Clear")).clicked() {
+                    override_color = None;
+                    override_changed = true;
+                }
+            }).response.on_hover_text("Only shown in this editor; the map keeps its own color");
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Tile ID");
+                let mut text = brush_tile.to_string();
+                let field = egui::TextEdit::singleline(&mut text).desired_width(16.0).font(egui::TextStyle::Monospace);
+                if ui.add(field).changed() {
+                    // The last character typed wins, so typing into the field replaces the id
+                    new_brush_tile = text.chars().rev().find(|c| c.is_ascii_alphanumeric() && *c != '0');
+                }
+                ui.label(tileset_name(brush_tile));
+            }).response.on_hover_text("Tile placed by the brush, fill and line tools");
+            if let Some(tile) = main_tile.filter(|&t| t != brush_tile) {
+                if ui.button(format!("Use this room's main tile: {} ({})", tile, tileset_name(tile))).clicked() {
+                    new_brush_tile = Some(tile);
+                }
+            }
+        });
+    if let Some(tile) = new_brush_tile {
+        editor.brush_tile = tile;
+    }
+    if let Some(c) = new_color_index {
+        set_room_color(editor, idx, c);
+    }
+    if override_changed {
+        let colors = editor.room_colors.entry(map_key).or_default();
+        match override_color {
+            Some(color) => { colors.insert(room_name, color); }
+            None => { colors.remove(&room_name); }
+        }
+        save_room_colors(&editor.room_colors);
+    }
+    if apply {
+        set_room_rect(editor, idx, form.x, form.y, form.width, form.height);
+    }
+    if let Some((x, y)) = new_pos {
+        set_room_position(editor, idx, x, y);
+    }
+    // Keep the typed values until the room changes underneath them
+    editor.room_properties = Some(form);
+    if !open {
+        editor.show_room_properties = false;
+        editor.room_properties = None;
+    }
+}
+
+/// Map-wide information, plus the Everest `.meta.yaml` sidecar when there is one.
+pub fn show_map_properties_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    let mut open = true;
+    let mut save_meta = false;
+    let package = editor.map_data.as_ref()
+        .and_then(|m| m["package"].as_str())
+        .map(str::to_string);
+    egui::Window::new("Map Properties")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            egui::Grid::new("map_properties_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Package");
+                ui.horizontal(|ui| {
+                    ui.label(package.as_deref().unwrap_or("?"));
+                    let copy = ui.add_enabled(package.is_some(), egui::Button::new("📋").small())
+                        .on_hover_text("Copy the package name, for mod code that refers to this map");
+                    if let (true, Some(package)) = (copy.clicked(), &package) {
+                        ui.output().copied_text = package.clone();
+                        editor.notifications.info("Copied!");
+                    }
+                });
+                ui.end_row();
+                ui.label("Rooms");
+                ui.label(editor.level_names.len().to_string());
+                ui.end_row();
+            });
+            ui.separator();
+            let Some(meta) = &mut editor.map_meta else {
+                let hint = editor.bin_path.as_deref()
+                    .map(|p| format!("No {} next to this map.", meta_path(p).file_name().unwrap_or_default().to_string_lossy()))
+                    .unwrap_or_else(|| "Save the map to give it a .meta.yaml.".to_string());
+                ui.label(hint);
+                return;
+            };
+            ui.label(egui::RichText::new(meta.path.display().to_string()).weak());
+            ui.add_space(5.0);
+
+            let mut intro = meta.get_str("IntroType").unwrap_or("").to_string();
+            let mut grade = meta.get_str("ColorGrade").unwrap_or("").to_string();
+            let mut dreaming = meta.get("Dreaming").and_then(|v| v.as_bool()).unwrap_or(false);
+            let text = |s: &str| (!s.is_empty()).then(|| serde_yaml::Value::String(s.to_string()));
+            egui::Grid::new("map_meta_edit_grid").num_columns(2).show(ui, |ui| {
+                ui.label("IntroType");
+                let mut intro_changed = false;
+                egui::ComboBox::from_id_source("meta_intro_type")
+                    .selected_text(if intro.is_empty() { "(default)" } else { intro.as_str() })
+                    .show_ui(ui, |ui| {
+                        intro_changed |= ui.selectable_value(&mut intro, String::new(), "(default)").changed();
+                        for t in INTRO_TYPES {
+                            intro_changed |= ui.selectable_value(&mut intro, t.to_string(), t).changed();
+                        }
+                    });
+                if intro_changed {
+                    meta.set("IntroType", text(&intro));
+                }
+                ui.end_row();
+                ui.label("ColorGrade");
+                if ui.text_edit_singleline(&mut grade).changed() {
+                    meta.set("ColorGrade", text(&grade));
+                }
+                ui.end_row();
+                ui.label("Dreaming");
+                if ui.checkbox(&mut dreaming, "").changed() {
+                    meta.set("Dreaming", Some(serde_yaml::Value::Bool(dreaming)));
+                }
+                ui.end_row();
+            });
+            if ui.button("Save .meta.yaml").clicked() {
+                save_meta = true;
+            }
+            ui.separator();
+            egui::CollapsingHeader::new("All fields").show(ui, |ui| {
+                egui::Grid::new("map_meta_grid").num_columns(2).show(ui, |ui| {
+                    for (key, value) in meta.summary() {
+                        ui.label(key);
+                        ui.label(value);
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+    if save_meta {
+        if let Some(meta) = &editor.map_meta {
+            match meta.save() {
+                Ok(()) => editor.notifications.info(format!("Saved {}", meta.path.display())),
+                Err(e) => editor.notifications.error(e),
+            }
+        }
+    }
+    if !open {
+        editor.show_map_properties = false;
+    }
+}
+
+/// Visibility, solo and draw order of the render layers; drag a handle to move a layer. Top of the list is drawn first.
+pub fn show_layer_order_dialog(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    let mut open = true;
+    let mut rows = Vec::new();
+    let mut released = false;
+    let mut reset = false;
+    // Layer whose toggle was clicked, and whether Alt was held (solo instead of show/hide)
+    let mut toggled: Option<(LayerId, bool)> = None;
+    egui::Window::new("Layers")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label("Drag ☰ to reorder. Layers lower in the list draw on top.");
+            ui.label("Alt+click a layer to solo it; Alt+click again to end the solo.");
+            ui.add_space(5.0);
+            for (i, id) in editor.view_settings.layer_order.iter().enumerate() {
+                let row = ui.horizontal(|ui| {
+                    let handle = ui.add(egui::Label::new("☰").sense(egui::Sense::drag()));
+                    if handle.drag_started() { editor.layer_order_drag = Some(i); }
+                    if handle.drag_released() { released = true; }
+                    let mut visible = editor.layer_visibility[id.index()];
+                    let soloed = editor.solo_layer.map_or(false, |(solo, _)| solo == *id);
+                    let mut text = egui::RichText::new(if soloed { format!("{} (solo)", id.label()) } else { id.label().to_string() });
+                    if editor.layer_order_drag == Some(i) || soloed { text = text.strong(); }
+                    if !visible { text = text.weak(); }
+                    let check = ui.checkbox(&mut visible, "");
+                    let label = ui.add(egui::Label::new(text).sense(egui::Sense::click()));
+                    if check.clicked() || label.clicked() {
+                        toggled = Some((*id, ui.input().modifiers.alt));
+                    }
+                }).response.rect;
+                rows.push(row);
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Reset to Default").clicked() {
+                    reset = true;
+                }
+                if let Some((solo, _)) = editor.solo_layer {
+                    if ui.button("End Solo").clicked() {
+                        toggled = Some((solo, true));
+                    }
+                }
+            });
+        });
+    match toggled {
+        Some((id, true)) => toggle_solo_layer(editor, id),
+        Some((id, false)) => editor.layer_visibility[id.index()] ^= true,
+        None => {}
+    }
+    if !open {
+        editor.show_layer_order = false;
+        editor.layer_order_drag = None;
+    }
+    if reset {
+        editor.view_settings.layer_order = LayerId::ALL.to_vec();
+        save_view_settings(&editor.view_settings);
+    }
+    let Some(from) = editor.layer_order_drag else { return };
+    let pointer_y = ctx.input().pointer.interact_pos().map(|p| p.y);
+    if let (Some(y), Some(first), Some(last)) = (pointer_y, rows.first(), rows.last()) {
+        let target = rows.iter().position(|r| y < r.bottom()).unwrap_or(rows.len() - 1);
+        let target = if y < first.top() { 0 } else if y > last.bottom() { rows.len() - 1 } else { target };
+        if !released {
+            let line_y = if target > from { rows[target].bottom() } else { rows[target].top() };
+            ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("layer_order_drop")))
+                .hline(rows[target].x_range(), line_y, egui::Stroke::new(2.0, SELECTION_COLOR));
+            return;
+        }
+        editor.layer_order_drag = None;
+        if from != target {
+            let id = editor.view_settings.layer_order.remove(from);
+            editor.view_settings.layer_order.insert(target, id);
+            save_view_settings(&editor.view_settings);
+        }
+    } else if released {
+        editor.layer_order_drag = None;
+    }
+}
@@ -0,0 +1,138 @@
+use eframe::egui;
+use egui::{Color32, Pos2, Rect, Stroke, Vec2};
+use serde_json::Value;
+
+use crate::data::celeste_atlas::{AtlasManager, Sprite};
+use crate::ui::render::apply_tint;
+
+// Fallback colours when the atlas has no sprite for the entity
+const SPIKE_COLOR: Color32 = Color32::from_rgb(220, 220, 235);
+const JUMPTHRU_COLOR: Color32 = Color32::from_rgb(170, 120, 70);
+// Size of one spike or jumpthru segment, in Celeste pixels
+const SEGMENT: f32 = 8.0;
+
+#[derive(Clone, Copy, PartialEq)]
+enum SpikeDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl SpikeDirection {
+    fn from_entity_name(name: &str) -> Option<Self> {
+        match name {
+            "spikesUp" => Some(Self::Up),
+            "spikesDown" => Some(Self::Down),
+            "spikesLeft" => Some(Self::Left),
+            "spikesRight" => Some(Self::Right),
+            _ => None,
+        }
+    }
+
+    fn suffix(self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Down => "down",
+            Self::Left => "left",
+            Self::Right => "right",
+        }
+    }
+
+    /// Spikes run along x when they point up or down.
+    fn horizontal(self) -> bool {
+        matches!(self, Self::Up | Self::Down)
+    }
+}
+
+/// Gameplay sprite tiled along a spike or jumpthru entity, or None for any other entity.
+pub fn sized_entity_sprite(e: &Value) -> Option<String> {
+    let name = e["__name"].as_str().unwrap_or("");
+    if let Some(dir) = SpikeDirection::from_entity_name(name) {
+        let kind = e["type"].as_str().unwrap_or("default");
+        return Some(format!("danger/spikes/{}_{}00", kind, dir.suffix()));
+    }
+    if name == "jumpThru" {
+        let texture = match e["texture"].as_str().unwrap_or("default") {
+            "default" | "" => "wood",
+            other => other,
+        };
+        return Some(format!("objects/jumpthru/{}", texture));
+    }
+    None
+}
+
+/// The atlas together with the entity's sized sprite, when the atlas has it.
+fn gameplay_sprite<'a>(atlas: Option<&'a AtlasManager>, e: &Value) -> Option<(&'a AtlasManager, &'a Sprite)> {
+    let atlas = atlas?;
+    Some((atlas, atlas.get_sprite("Gameplay", &sized_entity_sprite(e)?)?))
+}
+
+/// Draw spikes and jumpthrus tiled along their length, with sprites from `atlas` when given.
+/// Returns false for any other entity, which is then drawn as a plain box.
+pub fn render_sized_entity(atlas: Option<&AtlasManager>, painter: &egui::Painter, e: &Value, origin: Pos2, scale: f32, tint: Color32) -> bool {
+    let name = e["__name"].as_str().unwrap_or("");
+    let x = e["x"].as_f64().unwrap_or(0.0) as f32;
+    let y = e["y"].as_f64().unwrap_or(0.0) as f32;
+    let at = |px: f32, py: f32| origin + Vec2::new(px, py) * scale;
+    if let Some(dir) = SpikeDirection::from_entity_name(name) {
+        let key = if dir.horizontal() { "width" } else { "height" };
+        let count = (e[key].as_f64().unwrap_or(SEGMENT as f64) as f32 / SEGMENT).max(1.0) as usize;
+        let sprite = gameplay_sprite(atlas, e);
+        for i in 0..count {
+            let along = i as f32 * SEGMENT;
+            let (sx, sy) = if dir.horizontal() { (x + along, y) } else { (x, y + along) };
+            match sprite {
+                Some((atlas, spr)) => {
+                    let (w, h) = (spr.metadata.width as f32, spr.metadata.height as f32);
+                    // Up and left spikes hang off the entity position; down and right extend from it
+                    let min = match dir {
+                        SpikeDirection::Up => at(sx, sy - h),
+                        SpikeDirection::Left => at(sx - w, sy),
+                        _ => at(sx, sy),
+                    };
+                    let rect = Rect::from_min_size(min, Vec2::new(w, h) * scale);
+                    atlas.draw_sprite(spr, painter, rect, tint, false, false);
+                }
+                None => draw_spike_triangle(painter, dir, at(sx, sy), SEGMENT * scale, tint),
+            }
+        }
+        return true;
+    }
+    if name == "jumpThru" {
+        let width = e["width"].as_f64().unwrap_or(SEGMENT as f64) as f32;
+        let count = (width / SEGMENT).max(1.0) as usize;
+        match gameplay_sprite(atlas, e) {
+            Some((atlas, spr)) => {
+                let columns = (spr.metadata.width as f32 / SEGMENT).max(1.0) as usize;
+                for i in 0..count {
+                    // Ends use the first and last columns of the strip, the middle the one between
+                    let column = if columns < 3 { 0 } else if i == 0 { 0 } else if i + 1 == count { 2 } else { 1 };
+                    let region = Rect::from_min_size(Pos2::new(column as f32 * SEGMENT, 0.0), Vec2::splat(SEGMENT));
+                    let rect = Rect::from_min_size(at(x + i as f32 * SEGMENT, y), Vec2::splat(SEGMENT * scale));
+                    atlas.draw_sprite_region(spr, painter, rect, tint, region);
+                }
+            }
+            None => {
+                let rect = Rect::from_min_size(at(x, y), Vec2::new(width, 3.0) * scale);
+                painter.rect_filled(rect, 0.0, apply_tint(JUMPTHRU_COLOR, tint));
+                painter.hline(rect.x_range(), rect.top(), Stroke::new(1.0, apply_tint(Color32::WHITE, tint)));
+            }
+        }
+        return true;
+    }
+    false
+}
+
+/// One spike as a flat triangle with its base on `base` and its tip in the spike direction.
+fn draw_spike_triangle(painter: &egui::Painter, dir: SpikeDirection, base: Pos2, size: f32, tint: Color32) {
+    let depth = size * 0.75;
+    let points = match dir {
+        SpikeDirection::Up => vec![base, base + Vec2::new(size, 0.0), base + Vec2::new(size / 2.0, -depth)],
+        SpikeDirection::Down => vec![base, base + Vec2::new(size / 2.0, depth), base + Vec2::new(size, 0.0)],
+        SpikeDirection::Left => vec![base, base + Vec2::new(-depth, size / 2.0), base + Vec2::new(0.0, size)],
+        SpikeDirection::Right => vec![base, base + Vec2::new(0.0, size), base + Vec2::new(depth, size / 2.0)],
+    };
+    let color = apply_tint(SPIKE_COLOR, tint);
+    painter.add(egui::Shape::convex_polygon(points, color.linear_multiply(0.6), Stroke::new(1.0, color)));
+}
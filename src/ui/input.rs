@@ -1,128 +1,609 @@
 use eframe::egui;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
 
 use crate::app::CelesteMapEditor;
+use crate::config::bookmarks::{save_bookmarks, ViewBookmark};
 use crate::config::keybindings::InputBinding;
-use crate::map::editor::{place_block, remove_block};
-use crate::map::loader::save_map;
+use crate::map::clipboard::{begin_selection, copy_selection, paste_clipboard, update_selection};
+use crate::map::resize::{begin_room_resize, end_room_resize, update_room_resize};
+use crate::map::editor::{begin_stroke, continue_stroke, end_stroke, flood_fill, line_click, pick_tile, EditorTool};
+use crate::map::entities::{end_entity_drag, update_entity_drag};
+use crate::map::entity_selection::{begin_object_press, copy_object_selection, delete_selection, end_object_drag, nudge_selection, update_object_drag};
+use crate::map::history::{redo, undo};
+use crate::map::triggers::{begin_trigger_press, end_trigger_drag, inspect_trigger_at, update_trigger_drag};
+use crate::map::loader::{request_reload, save_map};
+use crate::ui::transform::{ViewTransform, MAX_ZOOM, MIN_ZOOM};
 
-pub fn handle_input(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
-    let input = ctx.input();
-
-    // Handle mouse wheel for zooming
-    let scroll_delta = input.scroll_delta.y;
-    if scroll_delta != 0.0 {
-        // Calculate the zoom center (use mouse position or center of screen)
-        let zoom_center = input.pointer.hover_pos().unwrap_or_else(|| {
-            let screen_rect = ctx.available_rect();
-            egui::Pos2::new(screen_rect.width() / 2.0, screen_rect.height() / 2.0)
+// Length of an animated zoom or camera move
+const VIEW_ANIMATION_DURATION: Duration = Duration::from_millis(120);
+// Share of the view a fitted room fills, leaving a margin around it
+const FIT_ROOM_FILL: f32 = 0.9;
+// Distance from the canvas edge, in screen points, within which a drag scrolls the view
+const AUTOSCROLL_MARGIN: f32 = 24.0;
+const BOOKMARK_KEYS: [egui::Key; 9] = [
+    egui::Key::Num1, egui::Key::Num2, egui::Key::Num3, egui::Key::Num4, egui::Key::Num5,
+    egui::Key::Num6, egui::Key::Num7, egui::Key::Num8, egui::Key::Num9,
+];
+
+/// A zoom or camera move in progress.
+#[derive(Clone, Copy, Debug)]
+pub struct CameraAnimation {
+    pub from_pos: egui::Vec2,
+    pub from_zoom: f32,
+    pub to_pos: egui::Vec2,
+    pub to_zoom: f32,
+    /// Screen point kept fixed while zooming; when set the camera follows the zoom and `to_pos` is unused.
+    pub anchor: Option<egui::Pos2>,
+    pub start: Instant,
+}
+
+/// Step the running animation. The app requests repaints while one is active.
+fn animate_camera(editor: &mut CelesteMapEditor) {
+    let Some(anim) = editor.camera_animation else { return };
+    let t = (anim.start.elapsed().as_secs_f32() / VIEW_ANIMATION_DURATION.as_secs_f32()).min(1.0);
+    // Ease out
+    let k = 1.0 - (1.0 - t) * (1.0 - t);
+    let zoom = anim.from_zoom + (anim.to_zoom - anim.from_zoom) * k;
+    match anim.anchor {
+        Some(anchor) => editor.set_zoom(zoom, anchor),
+        None => {
+            editor.camera_pos = anim.from_pos + (anim.to_pos - anim.from_pos) * k;
+            editor.zoom_level = zoom;
+        }
+    }
+    if t >= 1.0 {
+        editor.camera_animation = None;
+    }
+}
+
+/// The zoom level the view is at or heading to.
+fn target_zoom(editor: &CelesteMapEditor) -> f32 {
+    editor.camera_animation.map_or(editor.zoom_level, |a| a.to_zoom)
+}
+
+/// Zoom to `zoom` around the screen point `anchor`, animated unless disabled.
+pub fn zoom_to(editor: &mut CelesteMapEditor, zoom: f32, anchor: egui::Pos2) {
+    let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    if !editor.view_settings.animate_view {
+        editor.camera_animation = None;
+        editor.set_zoom(zoom, anchor);
+        return;
+    }
+    editor.camera_animation = Some(CameraAnimation {
+        from_pos: editor.camera_pos,
+        from_zoom: editor.zoom_level,
+        to_pos: editor.camera_pos,
+        to_zoom: zoom,
+        anchor: Some(anchor),
+        start: Instant::now(),
+    });
+}
+
+/// Multiply the zoom around `anchor`. Repeated steps compound on the animation target, not the
+/// in-between zoom, so fast scrolling isn't lost.
+pub fn zoom_by(editor: &mut CelesteMapEditor, factor: f32, anchor: egui::Pos2) {
+    zoom_to(editor, target_zoom(editor) * factor, anchor);
+}
+
+/// Move the camera to `pos` at `zoom`, animated unless disabled.
+pub fn move_view_to(editor: &mut CelesteMapEditor, pos: egui::Vec2, zoom: f32) {
+    let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    if !editor.view_settings.animate_view {
+        editor.camera_animation = None;
+        editor.camera_pos = pos;
+        editor.zoom_level = zoom;
+        return;
+    }
+    editor.camera_animation = Some(CameraAnimation {
+        from_pos: editor.camera_pos,
+        from_zoom: editor.zoom_level,
+        to_pos: pos,
+        to_zoom: zoom,
+        anchor: None,
+        start: Instant::now(),
+    });
+}
+
+/// Camera position and zoom that centre the world rectangle `world` (in Celeste pixels) in `view`.
+fn fit_world_rect(world: egui::Rect, view: egui::Rect) -> (egui::Vec2, f32) {
+    let pixel_size = crate::ui::render::TILE_SIZE / 8.0;
+    let zoom = FIT_ROOM_FILL * (view.width() / (world.width() * pixel_size)).min(view.height() / (world.height() * pixel_size));
+    let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    let center = world.center().to_vec2() * pixel_size * zoom;
+    (center - view.center().to_vec2(), zoom)
+}
+
+fn room_world_rect(editor: &CelesteMapEditor, idx: usize) -> Option<egui::Rect> {
+    let ld = &editor.cached_rooms.get(idx)?.level_data;
+    Some(egui::Rect::from_min_size(egui::pos2(ld.x, ld.y), egui::vec2(ld.width, ld.height)))
+}
+
+/// Zoom and centre the camera so room `idx` fills `view`.
+pub fn fit_room_in_view(editor: &mut CelesteMapEditor, idx: usize, view: egui::Rect) {
+    let Some(room) = room_world_rect(editor, idx) else { return };
+    let (pos, zoom) = fit_world_rect(room, view);
+    move_view_to(editor, pos, zoom);
+}
+
+/// Jump, without animating, to the view of a freshly opened map: every room when all are shown,
+/// otherwise the current room.
+pub fn fit_map_in_view(editor: &mut CelesteMapEditor, view: egui::Rect) {
+    let bounds = if editor.show_all_rooms {
+        (0..editor.cached_rooms.len()).filter_map(|idx| room_world_rect(editor, idx)).reduce(|a, b| a.union(b))
+    } else {
+        room_world_rect(editor, editor.current_level_index)
+    };
+    let Some(bounds) = bounds else { return };
+    let (pos, zoom) = fit_world_rect(bounds, view);
+    editor.camera_animation = None;
+    editor.camera_pos = pos;
+    editor.zoom_level = zoom;
+}
+
+/// Back to zoom 1.0 centred on the current room (or the world origin without one). The escape
+/// hatch for a view lost far away or in a broken state; a camera that isn't finite is replaced
+/// at once instead of being animated from.
+pub fn reset_view(editor: &mut CelesteMapEditor, view: egui::Rect) {
+    let center = room_world_rect(editor, editor.current_level_index).map_or(egui::Pos2::ZERO, |r| r.center());
+    let pos = center.to_vec2() * ViewTransform::new(egui::Vec2::ZERO, 1.0).scale() - view.center().to_vec2();
+    if !editor.camera_pos.is_finite() || !editor.zoom_level.is_finite() {
+        editor.camera_animation = None;
+        editor.camera_pos = pos;
+        editor.zoom_level = 1.0;
+        return;
+    }
+    move_view_to(editor, pos, 1.0);
+}
+
+/// Show the Find window with its field focused, refreshing results for the current map.
+/// Show the Open dialog, starting from the open map's path.
+pub fn open_open_dialog(editor: &mut CelesteMapEditor) {
+    editor.show_open_dialog = true;
+    editor.open_path = editor.bin_path.clone().unwrap_or_default();
+    editor.open_mod_map = None;
+}
+
+pub fn open_find(editor: &mut CelesteMapEditor) {
+    editor.show_find = true;
+    editor.find_focus = true;
+    editor.find_results = crate::map::search::find_objects(editor, &editor.find_query);
+}
+
+/// The editor's camera, zoom, display mode and room, as a bookmark.
+pub fn current_view(editor: &CelesteMapEditor) -> ViewBookmark {
+    ViewBookmark {
+        camera_x: editor.camera_pos.x,
+        camera_y: editor.camera_pos.y,
+        zoom: editor.zoom_level,
+        show_all_rooms: editor.show_all_rooms,
+        room: editor.current_level_index,
+    }
+}
+
+/// Store the current view in bookmark `slot` of the open map.
+pub fn set_bookmark(editor: &mut CelesteMapEditor, slot: u8) {
+    let key = editor.bin_path.clone().unwrap_or_default();
+    let bookmark = current_view(editor);
+    editor.bookmarks.entry(key).or_default().insert(slot, bookmark);
+    save_bookmarks(&editor.bookmarks);
+    editor.notifications.info(format!("Saved view bookmark {}", slot));
+}
+
+/// Restore bookmark `slot` of the open map, animated if enabled.
+pub fn go_to_bookmark(editor: &mut CelesteMapEditor, slot: u8) {
+    let key = editor.bin_path.clone().unwrap_or_default();
+    let Some(bookmark) = editor.bookmarks.get(&key).and_then(|b| b.get(&slot)).copied() else { return };
+    editor.show_all_rooms = bookmark.show_all_rooms;
+    editor.current_level_index = bookmark.room.min(editor.level_names.len().saturating_sub(1));
+    move_view_to(editor, egui::vec2(bookmark.camera_x, bookmark.camera_y), bookmark.zoom);
+}
+
+// Every pointer button, for copying which ones are held out of the input state
+const POINTER_BUTTONS: [egui::PointerButton; 5] = [
+    egui::PointerButton::Primary, egui::PointerButton::Secondary, egui::PointerButton::Middle,
+    egui::PointerButton::Extra1, egui::PointerButton::Extra2,
+];
+
+/// What `handle_input` needs from egui this frame. Copied out so the input lock is released
+/// before any editing code runs, since that code may read the context again.
+struct FrameInput {
+    hover_pos: Option<egui::Pos2>,
+    pointer_delta: egui::Vec2,
+    pointer_moving: bool,
+    /// Pointer buttons that went down this frame.
+    buttons_pressed: Vec<egui::PointerButton>,
+    /// Pointer buttons whose second click of a double click was released this frame.
+    buttons_double_clicked: Vec<egui::PointerButton>,
+    buttons_down: Vec<egui::PointerButton>,
+    /// Key presses this frame, key repeat included.
+    keys_pressed: Vec<egui::Key>,
+    /// Keys that went down this frame, without key repeat.
+    keys_struck: Vec<egui::Key>,
+    keys_down: HashSet<egui::Key>,
+    modifiers: egui::Modifiers,
+    scroll_delta: egui::Vec2,
+    zoom_delta: f32,
+    /// Seconds since the last frame.
+    dt: f32,
+}
+
+impl FrameInput {
+    /// `keys_held` holds the keys down at the end of the previous frame and is updated to this one's.
+    fn read(ctx: &egui::Context, keys_held: &mut HashSet<egui::Key>) -> Self {
+        let input = ctx.input();
+        let pointer = &input.pointer;
+        let keys_pressed: Vec<egui::Key> = input.events.iter().filter_map(|e| match e {
+            egui::Event::Key { key, pressed: true, .. } => Some(*key),
+            _ => None,
+        }).collect();
+        // A press of a key that was already down is key repeat
+        let keys_struck = keys_pressed.iter().copied().filter(|k| !keys_held.contains(k)).collect();
+        *keys_held = input.keys_down.clone();
+        Self {
+            hover_pos: pointer.hover_pos(),
+            pointer_delta: pointer.delta(),
+            pointer_moving: pointer.is_moving(),
+            buttons_pressed: input.events.iter().filter_map(|e| match e {
+                egui::Event::PointerButton { button, pressed: true, .. } => Some(*button),
+                _ => None,
+            }).collect(),
+            buttons_double_clicked: POINTER_BUTTONS.into_iter().filter(|b| pointer.button_double_clicked(*b)).collect(),
+            buttons_down: POINTER_BUTTONS.into_iter().filter(|b| pointer.button_down(*b)).collect(),
+            keys_pressed,
+            keys_struck,
+            keys_down: input.keys_down.clone(),
+            modifiers: input.modifiers,
+            scroll_delta: input.scroll_delta,
+            zoom_delta: input.zoom_delta(),
+            dt: input.stable_dt,
+        }
+    }
+
+    /// Drop the input egui widgets have claimed, so the map only sees the rest: every key while a text
+    /// field has focus, and new presses, scrolling and zooming unless the pointer is over the canvas
+    /// with no window or menu on top. Buttons already held stay down so a drag begun on the canvas
+    /// carries on and ends normally, unless a widget is being dragged.
+    fn drop_captured(&mut self, ctx: &egui::Context, canvas: Option<egui::Rect>) {
+        if ctx.wants_keyboard_input() {
+            self.keys_pressed.clear();
+            self.keys_struck.clear();
+            self.keys_down.clear();
+        }
+        let over_canvas = self.hover_pos.map_or(false, |pos| {
+            canvas.map_or(true, |c| c.contains(pos))
+                && ctx.layer_id_at(pos).map_or(true, |layer| layer.order == egui::Order::Background)
         });
+        let widget_dragged = ctx.is_using_pointer();
+        if !over_canvas || widget_dragged {
+            self.buttons_pressed.clear();
+            self.buttons_double_clicked.clear();
+            self.scroll_delta = egui::Vec2::ZERO;
+            self.zoom_delta = 1.0;
+        }
+        if widget_dragged {
+            self.buttons_down.clear();
+        }
+    }
+
+    fn key_pressed(&self, key: egui::Key) -> bool {
+        self.keys_pressed.contains(&key)
+    }
 
-        let old_zoom = editor.zoom_level;
-        if scroll_delta > 0.0 {
-            editor.zoom_level *= 1.1;
-            editor.static_dirty = true;
-        } else {
-            editor.zoom_level /= 1.1;
-            editor.static_dirty = true;
+    fn button_down(&self, button: egui::PointerButton) -> bool {
+        self.buttons_down.contains(&button)
+    }
+
+    /// The binding went down this frame. Key repeat and presses of other buttons while it is held don't count.
+    fn pressed(&self, binding: &InputBinding) -> bool {
+        match binding {
+            InputBinding::Key(key) => self.keys_struck.contains(key),
+            InputBinding::MouseButton(button) => self.buttons_pressed.contains(button),
         }
-        if editor.zoom_level < 0.1 {
-            editor.zoom_level = 0.1;
+    }
+
+    /// The binding was double-clicked this frame; never for key bindings.
+    fn double_clicked(&self, binding: &InputBinding) -> bool {
+        matches!(binding, InputBinding::MouseButton(button) if self.buttons_double_clicked.contains(button))
+    }
+
+    /// The binding is held.
+    fn down(&self, binding: &InputBinding) -> bool {
+        match binding {
+            InputBinding::Key(key) => self.keys_down.contains(key),
+            InputBinding::MouseButton(button) => self.button_down(*button),
         }
-        
-        // Adjust camera position to zoom toward mouse cursor
-        let zoom_ratio = editor.zoom_level / old_zoom;
-        let offset = (zoom_ratio - 1.0) * zoom_center.to_vec2();
-        editor.camera_pos = zoom_ratio * editor.camera_pos + offset;
-        editor.static_dirty = true;
+    }
+
+    /// Keyboard-only bindings; mouse buttons never trigger these shortcuts.
+    fn key_binding_pressed(&self, binding: &InputBinding) -> bool {
+        matches!(binding, InputBinding::Key(key) if self.key_pressed(*key))
+    }
+}
+
+/// How far into the autoscroll margin `pos` is along one axis, from 0 outside it to 1 at the edge.
+fn edge_depth(pos: f32, min: f32, max: f32) -> f32 {
+    let near_min = (AUTOSCROLL_MARGIN - (pos - min)) / AUTOSCROLL_MARGIN;
+    let near_max = (AUTOSCROLL_MARGIN - (max - pos)) / AUTOSCROLL_MARGIN;
+    near_max.clamp(0.0, 1.0) - near_min.clamp(0.0, 1.0)
+}
+
+/// Scroll the view while a drag holds the pointer near the canvas edge, faster the closer it is.
+/// Drags convert the pointer through `camera_pos` each frame, so they carry on in world space;
+/// the selection band is kept in screen space and is shifted to match.
+fn autoscroll(editor: &mut CelesteMapEditor, ctx: &egui::Context, pos: egui::Pos2, dt: f32) {
+    let Some(canvas) = editor.canvas_rect else { return };
+    let direction = egui::vec2(
+        edge_depth(pos.x, canvas.min.x, canvas.max.x),
+        edge_depth(pos.y, canvas.min.y, canvas.max.y),
+    );
+    if direction == egui::Vec2::ZERO {
+        return;
+    }
+    let delta = direction * editor.view_settings.autoscroll_speed * dt;
+    editor.camera_animation = None;
+    editor.camera_pos += delta;
+    if let Some((start, _)) = editor.selection_band.as_mut() {
+        *start -= delta;
+    }
+    ctx.request_repaint();
+}
+
+pub fn handle_input(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    animate_camera(editor);
+    // Menu and key zooms anchor on the middle of the canvas, not of the window around the panels
+    let screen_center = editor.canvas_rect.map_or(ctx.available_rect().center(), |r| r.center());
+    let mut input = FrameInput::read(ctx, &mut editor.keys_held);
+    input.drop_captured(ctx, editor.canvas_rect);
+    let command = input.modifiers.command;
+
+    let pointer_center = input.hover_pos.unwrap_or(screen_center);
+
+    // Pinch and Ctrl+scroll arrive as a zoom factor; follow the gesture directly
+    if input.zoom_delta != 1.0 {
+        editor.camera_animation = None;
+        editor.set_zoom(editor.zoom_level * input.zoom_delta, pointer_center);
+    }
+
+    // Horizontal scrolling always pans; vertical scrolling pans or zooms depending on the setting
+    let scroll_delta = input.scroll_delta;
+    let scroll_pans = editor.view_settings.scroll_pans != input.modifiers.shift;
+    if scroll_delta.x != 0.0 || (scroll_pans && scroll_delta.y != 0.0) {
+        let pan = if scroll_pans { scroll_delta } else { egui::vec2(scroll_delta.x, 0.0) };
+        editor.camera_pos -= pan;
+    }
+    if !scroll_pans && scroll_delta.y != 0.0 {
+        let factor = if scroll_delta.y > 0.0 { 1.1 } else { 1.0 / 1.1 };
+        zoom_by(editor, factor, pointer_center);
     }
 
     // Handle keyboard shortcuts
-    let zoom_in_pressed = match &editor.key_bindings.zoom_in {
-        InputBinding::Key(key) => input.key_pressed(*key),
-        InputBinding::MouseButton(_) => false, // Only support keys for these shortcuts
-    };
-    
-    if zoom_in_pressed {
-        editor.zoom_level *= 1.2;
-        editor.static_dirty = true;
-    }
-    
-    let zoom_out_pressed = match &editor.key_bindings.zoom_out {
-        InputBinding::Key(key) => input.key_pressed(*key),
-        InputBinding::MouseButton(_) => false,
-    };
-    
-    if zoom_out_pressed {
-        editor.zoom_level /= 1.2;
-        if editor.zoom_level < 0.1 {
-            editor.zoom_level = 0.1;
-        }
-        editor.static_dirty = true;
+    if input.key_binding_pressed(&editor.key_bindings.zoom_in) {
+        zoom_by(editor, 1.2, screen_center);
+    }
+    if input.key_binding_pressed(&editor.key_bindings.zoom_out) {
+        zoom_by(editor, 1.0 / 1.2, screen_center);
+    }
+    if input.key_binding_pressed(&editor.key_bindings.reset_view) {
+        reset_view(editor, editor.canvas_rect.unwrap_or_else(|| ctx.available_rect()));
     }
-    
     // Use modifiers.ctrl to check for Ctrl key instead of separate KeyCode
-    let save_pressed = match &editor.key_bindings.save {
-        InputBinding::Key(key) => input.key_pressed(*key) && input.modifiers.ctrl,
-        InputBinding::MouseButton(_) => false,
-    };
-    
-    if save_pressed {
+    if input.key_binding_pressed(&editor.key_bindings.save) && input.modifiers.ctrl {
         save_map(editor);
     }
-    
-    let open_pressed = match &editor.key_bindings.open {
-        InputBinding::Key(key) => input.key_pressed(*key) && input.modifiers.ctrl,
-        InputBinding::MouseButton(_) => false,
-    };
-    
-    if open_pressed {
-        editor.show_open_dialog = true;
-    }
-
-    // Handle mouse input for interaction with the map
-    let pointer = &input.pointer;
-    
-    // Check if the pan key/button is pressed
-    let pan_pressed = match &editor.key_bindings.pan {
-        InputBinding::Key(key) => input.key_down(*key),
-        InputBinding::MouseButton(button) => pointer.button_down(*button),
-    };
-    
-    // Handle panning with dragging
-    if pointer.is_moving() && pan_pressed {
+    if input.key_binding_pressed(&editor.key_bindings.open) && input.modifiers.ctrl {
+        open_open_dialog(editor);
+    }
+    if command && input.key_pressed(egui::Key::F) && editor.map_data.is_some() {
+        open_find(editor);
+    }
+    if input.key_binding_pressed(&editor.key_bindings.go_to_room) && command && input.modifiers.shift && editor.map_data.is_some() {
+        editor.go_to_room_input.clear();
+        editor.show_go_to_room = true;
+    }
+
+    // View toggles; skipped while a Ctrl shortcut is held
+    if !command {
+        let toggled = |binding: &InputBinding| input.key_binding_pressed(binding);
+        if toggled(&editor.key_bindings.toggle_grid) {
+            editor.show_grid = !editor.show_grid;
+        }
+        if toggled(&editor.key_bindings.toggle_labels) {
+            editor.show_labels = !editor.show_labels;
+        }
+        if toggled(&editor.key_bindings.toggle_all_rooms) {
+            editor.show_all_rooms = !editor.show_all_rooms;
+        }
+        if toggled(&editor.key_bindings.toggle_textures) {
+            editor.use_textures = !editor.use_textures;
+        }
+        if toggled(&editor.key_bindings.toggle_fg_decals) {
+            editor.show_fgdecals = !editor.show_fgdecals;
+        }
+
+        // Tool switches; the select tool shares its default key with save, so it needs Alt
+        let alt = input.modifiers.alt;
+        let tools = [
+            (&editor.key_bindings.eyedropper, false, EditorTool::Eyedropper),
+            (&editor.key_bindings.flood_fill, false, EditorTool::FloodFill),
+            (&editor.key_bindings.line_tool, false, EditorTool::Line),
+            (&editor.key_bindings.rect_select, true, EditorTool::RectSelect),
+            (&editor.key_bindings.move_tool, false, EditorTool::Move),
+        ];
+        let picked = tools.iter()
+            .find(|(binding, needs_alt, _)| *needs_alt == alt && toggled(binding))
+            .map(|(_, _, tool)| *tool);
+        if let Some(tool) = picked {
+            // Pressing the active tool's key again returns to the brush
+            editor.active_tool = if editor.active_tool == tool { EditorTool::Brush } else { tool };
+            editor.line_start = None;
+        }
+    }
+
+    // Handle panning with dragging; keep the drag origin while the button is held
+    if input.down(&editor.key_bindings.pan) {
         if !editor.dragging {
-            editor.drag_start = pointer.hover_pos();
+            editor.drag_start = input.hover_pos;
             editor.dragging = true;
         }
-        
-        let delta = pointer.delta();
-        editor.camera_pos -= delta;
-        editor.static_dirty = true;
+        if input.pointer_moving {
+            editor.camera_pos -= input.pointer_delta;
+        }
     } else {
         editor.dragging = false;
         editor.drag_start = None;
     }
-    
-    // Handle placing/removing blocks
-    let place_pressed = match &editor.key_bindings.place_block {
-        InputBinding::Key(key) => input.key_pressed(*key),
-        InputBinding::MouseButton(button) => input.pointer.any_pressed() && pointer.button_down(*button),
-    };
-    
-    if place_pressed {
-        if let Some(pos) = pointer.hover_pos() {
-            place_block(editor, pos);
+
+    // Shift + primary drag selects a rectangle of tiles instead of painting; once a stroke
+    // is under way, Shift locks it to an axis instead
+    let selecting = input.modifiers.shift && editor.paint_stroke.is_none();
+    let primary_down = input.button_down(egui::PointerButton::Primary);
+    if selecting {
+        if let Some(pos) = input.hover_pos {
+            if input.buttons_pressed.contains(&egui::PointerButton::Primary) {
+                begin_selection(editor, pos);
+            } else if primary_down {
+                update_selection(editor, pos);
+            }
         }
     }
 
-    let remove_pressed = match &editor.key_bindings.remove_block {
-        InputBinding::Key(key) => input.key_pressed(*key),
-        InputBinding::MouseButton(button) => input.pointer.any_pressed() && pointer.button_down(*button),
+    let objects_active = editor.active_tool == EditorTool::Entities && editor.object_selection.is_some();
+    if command && input.key_pressed(egui::Key::C) {
+        if objects_active { copy_object_selection(editor) } else { copy_selection(editor) }
+    }
+    // Selected entities and decals: delete, or nudge by the snap step
+    if objects_active && !command {
+        if input.key_pressed(egui::Key::Delete) || input.key_pressed(egui::Key::Backspace) {
+            delete_selection(editor);
+        }
+        let step = editor.entity_snap.unwrap_or(1).max(1) as f64;
+        let nudges = [
+            (egui::Key::ArrowLeft, -step, 0.0),
+            (egui::Key::ArrowRight, step, 0.0),
+            (egui::Key::ArrowUp, 0.0, -step),
+            (egui::Key::ArrowDown, 0.0, step),
+        ];
+        for (key, dx, dy) in nudges {
+            if input.key_pressed(key) {
+                nudge_selection(editor, dx, dy);
+            }
+        }
+    }
+    if command && input.key_pressed(egui::Key::V) {
+        if let Some(pos) = input.hover_pos {
+            paste_clipboard(editor, pos);
+        }
+    }
+    if command && input.key_pressed(egui::Key::Z) {
+        if input.modifiers.shift { redo(editor) } else { undo(editor) }
+    }
+    if command && input.key_pressed(egui::Key::Y) {
+        redo(editor);
+    }
+    if editor.map_data.is_some() {
+        for (slot, key) in (1..).zip(BOOKMARK_KEYS) {
+            if !input.key_pressed(key) { continue; }
+            if command {
+                set_bookmark(editor, slot);
+            } else {
+                go_to_bookmark(editor, slot);
+            }
+        }
+    }
+    if input.key_pressed(egui::Key::F5) && editor.map_data.is_some() {
+        request_reload(editor);
+    }
+    if input.key_pressed(egui::Key::Escape) {
+        editor.selection = None;
+        editor.object_selection = None;
+        editor.line_start = None;
+    }
+
+    let drag_active = editor.paint_stroke.is_some()
+        || editor.room_resize.is_some()
+        || editor.entity_drag.is_some()
+        || editor.group_drag.is_some()
+        || editor.selection_band.is_some()
+        || (primary_down && editor.selection.is_some() && (selecting || editor.active_tool == EditorTool::RectSelect));
+    if drag_active && editor.view_settings.autoscroll {
+        if let Some(pos) = input.hover_pos {
+            autoscroll(editor, ctx, pos, input.dt);
+        }
+    }
+
+    // Handle placing/removing blocks: a press starts a stroke, every frame the button stays down paints
+    // up to the pointer, and the release ends it as one undo step. Key bindings paint once per press.
+    // Ctrl+click continues in a straight line from the last painted tile
+    let place_pressed = input.pressed(&editor.key_bindings.place_block);
+    let place_down = input.down(&editor.key_bindings.place_block);
+    let remove_pressed = input.pressed(&editor.key_bindings.remove_block);
+    let remove_down = input.down(&editor.key_bindings.remove_block);
+    let drags = |binding: &InputBinding| matches!(binding, InputBinding::MouseButton(_));
+    let stroke_held = match &editor.paint_stroke {
+        Some(stroke) if stroke.erase => remove_down && drags(&editor.key_bindings.remove_block),
+        Some(_) => place_down && drags(&editor.key_bindings.place_block) && editor.active_tool == EditorTool::Brush,
+        None => false,
     };
-    
-    if remove_pressed {
-        if let Some(pos) = pointer.hover_pos() {
-            remove_block(editor, pos);
+    if stroke_held {
+        if let Some(pos) = input.hover_pos {
+            continue_stroke(editor, pos, input.modifiers.shift);
+        }
+    } else {
+        end_stroke(editor);
+    }
+    // Room border handles take the click before the active tool
+    if editor.room_resize.is_some() {
+        if !place_down {
+            end_room_resize(editor);
+        } else if let Some(pos) = input.hover_pos {
+            update_room_resize(editor, pos);
+        }
+    } else if place_pressed && !selecting && editor.paint_stroke.is_none() {
+        if let Some(pos) = input.hover_pos {
+            begin_room_resize(editor, pos);
+        }
+    }
+    if !selecting && editor.paint_stroke.is_none() && editor.room_resize.is_none() {
+        if let Some(pos) = input.hover_pos {
+            match editor.active_tool {
+                EditorTool::Brush if place_pressed => begin_stroke(editor, pos, false, command),
+                EditorTool::Eyedropper if place_pressed => pick_tile(editor, pos),
+                EditorTool::FloodFill if place_pressed => flood_fill(editor, pos),
+                EditorTool::Line if place_pressed => line_click(editor, pos),
+                EditorTool::RectSelect if place_pressed => begin_selection(editor, pos),
+                EditorTool::RectSelect if place_down => update_selection(editor, pos),
+                EditorTool::Move if place_down && input.pointer_moving => {
+                    editor.camera_pos -= input.pointer_delta;
+                }
+                EditorTool::Entities if place_pressed => begin_object_press(editor, pos),
+                EditorTool::Entities if place_down && editor.entity_drag.is_some() => update_entity_drag(editor, pos),
+                EditorTool::Entities if place_down => update_object_drag(editor, pos),
+                EditorTool::Triggers if input.double_clicked(&editor.key_bindings.place_block) => {
+                    inspect_trigger_at(editor, pos);
+                }
+                EditorTool::Triggers if place_pressed => begin_trigger_press(editor, pos),
+                EditorTool::Triggers if place_down => update_trigger_drag(editor, pos),
+                _ => {}
+            }
+        }
+    }
+    // Released, or the tool changed mid-drag
+    let entities_released = !place_down || editor.active_tool != EditorTool::Entities;
+    if editor.entity_drag.is_some() && entities_released {
+        end_entity_drag(editor);
+    }
+    if (editor.group_drag.is_some() || editor.selection_band.is_some()) && entities_released {
+        end_object_drag(editor);
+    }
+    let triggers_released = !place_down || editor.active_tool != EditorTool::Triggers;
+    if (editor.trigger_draft.is_some() || editor.trigger_resize.is_some()) && triggers_released {
+        end_trigger_drag(editor);
+    }
+
+    if remove_pressed && !selecting && editor.paint_stroke.is_none() {
+        if let Some(pos) = input.hover_pos {
+            begin_stroke(editor, pos, true, command);
         }
     }
 }
\ No newline at end of file
@@ -1,5 +1,8 @@
 pub mod dialogs;
+pub mod entity_render;
 pub mod input;
 pub mod render;
 pub mod tile_neighbors;
-pub mod loading;
\ No newline at end of file
+pub mod transform;
+pub mod loading;
+pub mod notifications;
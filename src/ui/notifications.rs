@@ -0,0 +1,107 @@
+use eframe::egui;
+use std::time::{Duration, Instant};
+use log::{info, warn, error};
+
+// How long info notifications stay on screen
+const INFO_LIFETIME: Duration = Duration::from_secs(4);
+const CARD_WIDTH: f32 = 320.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    fn color(self) -> egui::Color32 {
+        match self {
+            Severity::Info => egui::Color32::from_rgb(60, 110, 170),
+            Severity::Warning => egui::Color32::from_rgb(170, 130, 40),
+            Severity::Error => egui::Color32::from_rgb(170, 50, 50),
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Severity::Info => "Info",
+            Severity::Warning => "Warning",
+            Severity::Error => "Error",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Notification {
+    pub severity: Severity,
+    pub message: String,
+    pub created: Instant,
+}
+
+/// Stack of dismissible notification cards drawn in the bottom-right corner.
+/// Infos expire on their own; warnings and errors stay until dismissed.
+#[derive(Default)]
+pub struct Notifications {
+    pub items: Vec<Notification>,
+}
+
+impl Notifications {
+    pub fn push(&mut self, severity: Severity, message: impl Into<String>) {
+        let message = message.into();
+        match severity {
+            Severity::Info => info!("{}", message),
+            Severity::Warning => warn!("{}", message),
+            Severity::Error => error!("{}", message),
+        }
+        self.items.push(Notification { severity, message, created: Instant::now() });
+    }
+
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(Severity::Info, message);
+    }
+
+    pub fn warn(&mut self, message: impl Into<String>) {
+        self.push(Severity::Warning, message);
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(Severity::Error, message);
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        self.items.retain(|n| n.severity != Severity::Info || n.created.elapsed() < INFO_LIFETIME);
+        if self.items.is_empty() {
+            return;
+        }
+        if self.items.iter().any(|n| n.severity == Severity::Info) {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+
+        let mut dismissed = None;
+        egui::Area::new("notifications")
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -40.0])
+            .show(ctx, |ui| {
+                ui.set_max_width(CARD_WIDTH);
+                for (i, n) in self.items.iter().enumerate() {
+                    egui::Frame::popup(ui.style())
+                        .fill(n.severity.color())
+                        .show(ui, |ui| {
+                            ui.set_width(CARD_WIDTH);
+                            ui.horizontal(|ui| {
+                                ui.label(egui::RichText::new(n.severity.title()).strong().color(egui::Color32::WHITE));
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                    if ui.small_button("✕").clicked() {
+                                        dismissed = Some(i);
+                                    }
+                                });
+                            });
+                            ui.label(egui::RichText::new(&n.message).color(egui::Color32::WHITE));
+                        });
+                    ui.add_space(4.0);
+                }
+            });
+        if let Some(i) = dismissed {
+            self.items.remove(i);
+        }
+    }
+}
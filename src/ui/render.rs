@@ -1,10 +1,32 @@
+use std::ops::Range;
+
 use eframe::egui;
 use egui::{Color32, Pos2, Rect, Stroke, Vec2};
+use serde::{Serialize, Deserialize};
 use crate::app::CelesteMapEditor;
-use crate::map::loader::{save_map, save_map_as};
+use crate::app::tabs::{copy_room_to_tab, new_tab, request_close_tab, switch_tab, tab_title};
+use crate::map::changes::refresh_changes;
+use crate::map::clipboard::copy_selection;
+use crate::map::loader::{load_map, request_reload, save_map, save_map_as};
+use crate::map::rooms::{camera_offset, checkpoint_position, move_room, music_track_name, room_music, spawn_positions};
+use crate::map::history::{redo, undo};
+use crate::map::image_import::pick_import_image;
+use crate::ui::input::{go_to_bookmark, move_view_to, open_find, open_open_dialog, reset_view, zoom_by, zoom_to};
+use crate::config::launch::save_launch_settings;
+use crate::config::view_settings::{default_selected_outline, default_unselected_outline, save_view_settings, OutlineStyle};
+use crate::map::collectibles::CollectibleKind;
+use crate::map::playtest::test_in_celeste;
+use crate::map::resize::handle_rects;
+use crate::map::entity_selection::selection_rects;
+use crate::map::triggers::{bounds_rect, level_triggers, trigger_handle_rects};
+use crate::map::summary::export_room_summary;
+use crate::map::editor::{get_solids_offset, hovered_room, level_local_tile, EditorTool, DECAL_SNAP_OPTIONS, SNAP_OPTIONS};
+use crate::map::stamps::load_stamps;
+use crate::data::map_model::{self, TileCoord};
 use crate::data::tile_xml::{self, ensure_tileset_id_path_map_loaded_from_celeste};
-use log::debug;
 use crate::ui::tile_neighbors::TileNeighbors;
+use crate::ui::entity_render::render_sized_entity;
+use crate::ui::transform::{RoomGrid, ViewTransform};
 
 // Constants
 pub const TILE_SIZE: f32 = 20.0;
@@ -13,13 +35,74 @@ pub const SOLID_TILE_COLOR: Color32 = Color32::from_rgb(200, 200, 200);
 pub const BG_COLOR: Color32 = Color32::from_rgb(30, 30, 30);
 pub const INFILL_COLOR: Color32 = Color32::from_rgb(40, 36, 60);
 pub const EXTERNAL_BORDER_COLOR: Color32 = Color32::from_rgb(220, 220, 220);
-pub const ROOM_CONTOUR_SELECTED: Color32 = Color32::from_rgb(110, 130, 170);
-pub const ROOM_CONTOUR_UNSELECTED: Color32 = Color32::from_rgb(60, 120, 220);
+pub const ENTITY_COLOR: Color32 = Color32::from_rgb(230, 120, 60);
+pub const TRIGGER_COLOR: Color32 = Color32::from_rgb(130, 170, 230);
+// Trigger names are drawn once the trigger is at least this tall on screen
+const TRIGGER_LABEL_MIN_HEIGHT: f32 = 14.0;
+pub const SELECTION_COLOR: Color32 = Color32::from_rgb(255, 210, 60);
+pub const DRAG_INDICATOR_COLOR: Color32 = Color32::from_rgb(255, 255, 255);
+pub const CHECKPOINT_COLOR: Color32 = Color32::from_rgb(120, 220, 120);
+const SUBGRID_COLOR: Color32 = Color32::from_rgba_premultiplied(60, 60, 60, 60);
+// Entity snap sub-grid is only drawn from this zoom on, and only if its lines are this far apart on screen
+const SUBGRID_MIN_ZOOM: f32 = 1.5;
+const SUBGRID_MIN_SPACING: f32 = 4.0;
+// Screen grid: a line every 20 tiles, in Celeste pixels, drawn once its lines are this far apart on screen
+const SCREEN_GRID_PIXELS: f32 = 160.0;
+const SCREEN_GRID_MIN_SPACING: f32 = 12.0;
+const SCREEN_GRID_COLOR: Color32 = Color32::from_rgb(110, 110, 140);
+const CHECKPOINT_MARKER_RADIUS: f32 = 10.0;
+const COLLECTIBLE_MARKER_RADIUS: f32 = 8.0;
+// Checkpoint overlay icon: the chapter select flag from Gui, then the in-game flag
+const CHECKPOINT_ICON_PATHS: &[&str] = &["areaselect/checkpoint", "objects/checkpoint/flag00"];
+// The game camera's view, in Celeste pixels
+const CAMERA_WIDTH: f32 = 320.0;
+const CAMERA_HEIGHT: f32 = 180.0;
+const CAMERA_FRAME_COLOR: Color32 = Color32::from_rgb(250, 120, 200);
+// How long a Find result's marker pulses after it is picked
+const FIND_FLASH_DURATION: f32 = 1.5;
+const CAMERA_CELL_COLOR: Color32 = Color32::from_rgba_premultiplied(120, 60, 100, 120);
+// Fill of solids changed since the last load or save (View > Highlight Unsaved Changes)
+const CHANGE_OVERLAY_COLOR: Color32 = Color32::from_rgba_premultiplied(40, 140, 150, 110);
+
+// Indices into `CelesteMapEditor::layer_visibility`, in the default draw order
+pub const LAYER_STYLEGROUNDS: usize = 0;
+pub const LAYER_BG_TILES: usize = 1;
+pub const LAYER_BG_DECALS: usize = 2;
+pub const LAYER_FG_TILES: usize = 3;
+pub const LAYER_FG_DECALS: usize = 4;
+pub const LAYER_ENTITIES: usize = 5;
+pub const LAYER_COUNT: usize = 6;
 
 const DECAL_SCALE: f32 = 1.0;
+// Opacity factor applied to SOLID_TILE_COLOR in ghost mode
+const GHOST_TILE_OPACITY: f32 = 0.5;
+// Opacity of the other layers while one is soloed
+const SOLO_DIM_OPACITY: f32 = 0.15;
+// Collision view: solid tiles are drawn flat, untinted and untextured
+const COLLISION_SOLID_COLOR: Color32 = Color32::WHITE;
+// Heat map gradient endpoints and the fill range they map to
+const HEAT_COLD: Color32 = Color32::from_rgb(40, 90, 220);
+const HEAT_HOT: Color32 = Color32::from_rgb(220, 50, 40);
+const HEAT_MIN_FILL: f32 = 0.2;
+const HEAT_MAX_FILL: f32 = 0.8;
+const HEAT_ALPHA: f32 = 0.45;
+// Alpha (30%) used for dimmed neighbouring rooms in single-room mode
+const NEIGHBOR_ROOM_ALPHA: u8 = 77;
 // Culling threshold based on zoom level
 const CULLING_THRESHOLD_BASE: f32 = 50.0;
 
+/// How foreground tiles are drawn
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TileRenderMode {
+    /// Textured (or fallback coloured) tiles
+    #[default]
+    Full,
+    /// Flat translucent grey rects, no texturing
+    Ghost,
+    /// Tiles are not drawn at all
+    Hidden,
+}
+
 // Cached representation for rendering
 #[derive(Clone, Default)]
 pub struct LevelRenderData {
@@ -34,12 +117,15 @@ pub struct LevelRenderData {
     pub offset_y: i32,
     pub autotile_coords: Vec<Vec<Option<(u32, u32)>>>, // cache for autotiling (foreground)
     pub bg_autotile_coords: Vec<Vec<Option<(u32, u32)>>>, // cache for autotiling (background)
-    pub fg_xml_path: String,
-    pub bg_xml_path: String,
     pub neighbor_masks: Vec<Vec<TileNeighbors>>,
 }
 
 impl LevelRenderData {
+    /// Same size and solids/bg tiles, so the room thumbnail would look the same.
+    pub fn same_tiles(&self, other: &LevelRenderData) -> bool {
+        self.width == other.width && self.height == other.height && self.solids == other.solids && self.bg == other.bg
+    }
+
     pub fn compute_autotile_coords(&mut self, xml_path: &str) {
         let tilesets = tile_xml::get_tilesets_with_rules(xml_path);
         let is_solid = |c: char| is_solid_tile(c);
@@ -61,18 +147,101 @@ impl LevelRenderData {
     }
 }
 
-/// Returns the color for a tile character, or None if a texture should be used.
-fn get_tile_color(_tile_char: char) -> Option<Color32> {
-    None
+// Colours used to tell tilesets apart in flat-colour mode
+const TILESET_PALETTE: [Color32; 12] = [
+    Color32::from_rgb(230, 100, 90),
+    Color32::from_rgb(240, 170, 70),
+    Color32::from_rgb(230, 220, 90),
+    Color32::from_rgb(140, 210, 90),
+    Color32::from_rgb(70, 190, 130),
+    Color32::from_rgb(70, 200, 210),
+    Color32::from_rgb(80, 140, 230),
+    Color32::from_rgb(130, 110, 230),
+    Color32::from_rgb(190, 100, 220),
+    Color32::from_rgb(230, 110, 170),
+    Color32::from_rgb(170, 140, 110),
+    Color32::from_rgb(150, 160, 170),
+];
+
+/// Returns the flat colour for a tile character, blended with the layer's base colour
+/// so fg and bg stay distinguishable.
+fn get_tile_color(tile_char: char, base: Color32) -> Color32 {
+    let hash = (tile_char as u32).wrapping_mul(2_654_435_761) >> 16;
+    let p = TILESET_PALETTE[hash as usize % TILESET_PALETTE.len()];
+    let mix = |a: u8, b: u8| ((a as u16 + b as u16) / 2) as u8;
+    Color32::from_rgb(mix(p.r(), base.r()), mix(p.g(), base.g()), mix(p.b(), base.b()))
+}
+
+/// Multiply a colour by a tint, component-wise (premultiplied).
+pub(crate) fn apply_tint(color: Color32, tint: Color32) -> Color32 {
+    let mul = |a: u8, b: u8| ((a as u16 * b as u16) / 255) as u8;
+    Color32::from_rgba_premultiplied(
+        mul(color.r(), tint.r()),
+        mul(color.g(), tint.g()),
+        mul(color.b(), tint.b()),
+        mul(color.a(), tint.a()),
+    )
+}
+
+/// Room colours by the level's `c` attribute, in the order other Celeste editors use.
+pub const ROOM_COLORS: [Color32; 7] = [
+    Color32::from_rgb(255, 255, 255),
+    Color32::from_rgb(246, 115, 94),
+    Color32::from_rgb(133, 246, 94),
+    Color32::from_rgb(55, 215, 227),
+    Color32::from_rgb(55, 107, 227),
+    Color32::from_rgb(195, 55, 227),
+    Color32::from_rgb(227, 55, 115),
+];
+// Opacity of the room colour backdrop, kept low so tiles stay readable
+const ROOM_TINT_ALPHA: f32 = 0.12;
+// Dashes of the dashed current-room outline, in screen points
+const OUTLINE_DASH_LENGTH: f32 = 10.0;
+const OUTLINE_GAP_LENGTH: f32 = 6.0;
+// Pulsing current-room outline speed, in radians per second
+const OUTLINE_PULSE_SPEED: f64 = 4.0;
+
+/// Editor override for the room, else its `c` colour. None for uncoloured rooms.
+pub fn room_color(editor: &CelesteMapEditor, name: &str, json: &serde_json::Value) -> Option<Color32> {
+    let key = editor.bin_path.as_deref().unwrap_or_default();
+    if let Some([r, g, b]) = editor.room_colors.get(key).and_then(|m| m.get(name)) {
+        return Some(Color32::from_rgb(*r, *g, *b));
+    }
+    match json["c"].as_u64() {
+        Some(c) if c > 0 => ROOM_COLORS.get(c as usize).copied(),
+        _ => None,
+    }
+}
+
+/// Blue below HEAT_MIN_FILL, red above HEAT_MAX_FILL, blended in between
+fn heat_color(fill: f32) -> Color32 {
+    let t = ((fill - HEAT_MIN_FILL) / (HEAT_MAX_FILL - HEAT_MIN_FILL)).clamp(0.0, 1.0);
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t) as u8;
+    Color32::from_rgb(
+        lerp(HEAT_COLD.r(), HEAT_HOT.r()),
+        lerp(HEAT_COLD.g(), HEAT_HOT.g()),
+        lerp(HEAT_COLD.b(), HEAT_HOT.b()),
+    ).linear_multiply(HEAT_ALPHA)
 }
 
 /// Is this a solid tile?
 fn is_solid_tile(c: char) -> bool {
-    c != '0'
+    // Rows padded with spaces are air too
+    c != '0' && c != ' '
+}
+
+/// Pad every row to `width` chars with air, since Celeste strips trailing '0's.
+/// Longer rows are left untouched.
+pub fn normalise_solids_grid(solids: &mut Vec<Vec<char>>, width: usize) {
+    for row in solids.iter_mut() {
+        if row.len() < width {
+            row.resize(width, '0');
+        }
+    }
 }
 
 /// Extract level data from JSON node.
-pub(crate) fn extract_level_data(level: &serde_json::Value, editor: &CelesteMapEditor) -> Option<LevelRenderData> {
+pub fn extract_level_data(level: &serde_json::Value, editor: &CelesteMapEditor) -> Option<LevelRenderData> {
     let x = level["x"].as_f64()? as f32;
     let y = level["y"].as_f64()? as f32;
     let width = level.get("width").and_then(|v| v.as_f64()).unwrap_or(320.0) as f32;
@@ -80,8 +249,8 @@ pub(crate) fn extract_level_data(level: &serde_json::Value, editor: &CelesteMapE
 
     let mut solids = Vec::new();
     let mut bg = Vec::new();
-    let offset_x = 0;
-    let offset_y = 0;
+    // Tiles are drawn where edits land: on the solids grid, shifted by its offset
+    let (offset_x, offset_y) = get_solids_offset(level);
     if let Some(children) = level["__children"].as_array() {
         for child in children {
             if child["__name"] == "solids" {
@@ -100,9 +269,10 @@ pub(crate) fn extract_level_data(level: &serde_json::Value, editor: &CelesteMapE
             }
         }
     }
+    let room_width_tiles = (width / 8.0) as usize;
+    normalise_solids_grid(&mut solids, room_width_tiles);
+    normalise_solids_grid(&mut bg, room_width_tiles);
     let name = level["name"].as_str().unwrap_or("").to_string();
-    let fg_xml_path = get_celeste_fgtiles_xml_path_from_editor(editor);
-    let bg_xml_path = get_celeste_bgtiles_xml_path_from_editor(editor);
     let mut ld = LevelRenderData {
         name,
         x,
@@ -115,13 +285,11 @@ pub(crate) fn extract_level_data(level: &serde_json::Value, editor: &CelesteMapE
         offset_y,
         autotile_coords: Vec::new(),
         bg_autotile_coords: Vec::new(),
-        fg_xml_path: fg_xml_path.clone(),
-        bg_xml_path: bg_xml_path.clone(),
         neighbor_masks: Vec::new(),
     };
     // Compute autotile coordinates on load
-    ld.compute_autotile_coords(&fg_xml_path);
-    ld.compute_bg_autotile_coords(&bg_xml_path);
+    ld.compute_autotile_coords(&editor.fg_xml_path);
+    ld.compute_bg_autotile_coords(&editor.bg_xml_path);
     // Compute neighbor masks for internal detection
     ld.neighbor_masks = ld.solids.iter().enumerate().map(|(y, row)| {
         row.iter().enumerate().map(|(x, &_tile)| {
@@ -132,7 +300,7 @@ pub(crate) fn extract_level_data(level: &serde_json::Value, editor: &CelesteMapE
 }
 
 /// Normalize decal path to "decals/..."
-fn normalize_decal_path(texture: &str) -> String {
+pub(crate) fn normalize_decal_path(texture: &str) -> String {
     let mut key = texture.replace("\\", "/");
     if key.ends_with(".png") { key.truncate(key.len()-4); }
     if !key.starts_with("decals/") { key = format!("decals/{}", key); }
@@ -155,47 +323,22 @@ fn render_any_tile(
     infill_color: Color32,
     tileset_id_path_map: Option<&std::collections::HashMap<char, String>>,
     xml_path: &str,
-    debug_tag: &str,
+    tint: Color32,
 ) {
-    // TEMP DEBUG: print mapping status for first tile
-    if x == 0 && y == 0 {
-        #[cfg(debug_assertions)]
-        debug!("[{} TILE DEBUG] tile char: {}", debug_tag, _tile);
-        if let Some(map) = tileset_id_path_map {
-            if let Some(path) = tile_xml::get_tileset_path_for_id(map, _tile) {
-                #[cfg(debug_assertions)]
-                debug!("[{} TILE DEBUG] tileset path for '{}': {}", debug_tag, _tile, path);
-                let sprite_path = format!("tilesets/{}", path);
-                #[cfg(debug_assertions)]
-                debug!("[{} TILE DEBUG] sprite_path: {}", debug_tag, sprite_path);
-                if let Some(atlas_mgr) = &editor.atlas_manager {
-                    let found = atlas_mgr.get_sprite("Gameplay", &sprite_path).is_some();
-                    #[cfg(debug_assertions)]
-                    debug!("[{} TILE DEBUG] atlas get_sprite('{}'): {}", debug_tag, sprite_path, found);
-                } else {
-                    #[cfg(debug_assertions)]
-                    debug!("[{} TILE DEBUG] atlas_manager is None", debug_tag);
-                }
-            } else {
-                #[cfg(debug_assertions)]
-                debug!("[{} TILE DEBUG] No tileset path for '{}'", debug_tag, _tile);
-            }
-        } else {
-            #[cfg(debug_assertions)]
-            debug!("[{} TILE DEBUG] TILESET_ID_PATH_MAP is None", debug_tag);
-        }
-    }
     if !visible || _tile == '0' || _tile == ' ' {
         return;
     }
-    let global_scale = TILE_SIZE / 8.0 * editor.zoom_level;
-    let world_x0 = (ld.x + ld.offset_x as f32) * global_scale;
-    let world_y0 = (ld.y + ld.offset_y as f32) * global_scale;
-    let px = world_x0 + x as f32 * tile_size - editor.camera_pos.x;
-    let py = world_y0 + y as f32 * tile_size - editor.camera_pos.y;
-    let pos = Pos2::new(px, py);
+    let pos = ViewTransform::of(editor).room_tile_to_screen(RoomGrid::of_render_data(ld), (x as i32, y as i32));
     let rect = Rect::from_min_size(pos, Vec2::splat(tile_size));
 
+    if editor.collision_debug_mode {
+        // Collision pass replaces the textured/coloured tile entirely; air stays transparent
+        if !is_air_or_empty(_tile) {
+            painter.rect_filled(rect, 0.0, COLLISION_SOLID_COLOR);
+        }
+        return;
+    }
+
     // Infill check
     let _internal = if let Some(neighs_row) = ld.neighbor_masks.get(y) {
         if let Some(mask) = neighs_row.get(x) {
@@ -203,7 +346,9 @@ fn render_any_tile(
         } else { false }
     } else { false };
     let mut drew_texture = false;
-    if !autotile_coords.is_empty() {
+    if !editor.use_textures {
+        // Flat-colour mode: skip atlas lookups entirely
+    } else if !autotile_coords.is_empty() {
         if let Some(coord) = autotile_coords.get(y).and_then(|row| row.get(x)).and_then(|v| *v) {
             if let Some(map) = tileset_id_path_map {
                 if let Some(path) = tile_xml::get_tileset_path_for_id(map, _tile) {
@@ -214,7 +359,7 @@ fn render_any_tile(
                     if let Some(atlas_mgr) = &editor.atlas_manager {
                         let sprite_path = format!("tilesets/{}", path);
                         if let Some(sprite) = atlas_mgr.get_sprite("Gameplay", &sprite_path) {
-                            atlas_mgr.draw_sprite_region(sprite, painter, rect, Color32::WHITE, region);
+                            atlas_mgr.draw_sprite_region(sprite, painter, rect, tint, region);
                             drew_texture = true;
                         }
                     }
@@ -234,7 +379,7 @@ fn render_any_tile(
                     if let Some(atlas_mgr) = &editor.atlas_manager {
                         let sprite_path = format!("tilesets/{}", path);
                         if let Some(sprite) = atlas_mgr.get_sprite("Gameplay", &sprite_path) {
-                            atlas_mgr.draw_sprite_region(sprite, painter, rect, Color32::WHITE, region);
+                            atlas_mgr.draw_sprite_region(sprite, painter, rect, tint, region);
                             drew_texture = true;
                         }
                     }
@@ -243,28 +388,27 @@ fn render_any_tile(
         }
     }
     if !drew_texture {
-        #[cfg(debug_assertions)]
-        debug!("[{} TILE DEBUG] drew fallback color for '{}'", debug_tag, _tile);
         // Fallback: draw colored rect
-        let color = get_tile_color(_tile).unwrap_or(infill_color);
-        painter.rect_filled(rect, 0.0, color);
+        let color = get_tile_color(_tile, infill_color);
+        painter.rect_filled(rect, 0.0, apply_tint(color, tint));
+        let border_color = apply_tint(EXTERNAL_BORDER_COLOR, tint);
 
         // External borders
         // Up
         if !(y > 0 && x < tiles[y-1].len() && !is_air_or_empty(tiles[y-1][x])) {
-            painter.rect_filled(Rect::from_min_size(Pos2::new(pos.x, pos.y - 1.0), Vec2::new(tile_size, 1.0)), 0.0, EXTERNAL_BORDER_COLOR);
+            painter.rect_filled(Rect::from_min_size(Pos2::new(pos.x, pos.y - 1.0), Vec2::new(tile_size, 1.0)), 0.0, border_color);
         }
         // Down
         if !(y + 1 < tiles.len() && x < tiles[y+1].len() && !is_air_or_empty(tiles[y+1][x])) {
-            painter.rect_filled(Rect::from_min_size(Pos2::new(pos.x, pos.y + tile_size), Vec2::new(tile_size, 1.0)), 0.0, EXTERNAL_BORDER_COLOR);
+            painter.rect_filled(Rect::from_min_size(Pos2::new(pos.x, pos.y + tile_size), Vec2::new(tile_size, 1.0)), 0.0, border_color);
         }
         // Left
         if !(x > 0 && x - 1 < tiles[y].len() && !is_air_or_empty(tiles[y][x-1])) {
-            painter.rect_filled(Rect::from_min_size(Pos2::new(pos.x - 1.0, pos.y), Vec2::new(1.0, tile_size)), 0.0, EXTERNAL_BORDER_COLOR);
+            painter.rect_filled(Rect::from_min_size(Pos2::new(pos.x - 1.0, pos.y), Vec2::new(1.0, tile_size)), 0.0, border_color);
         }
         // Right
         if !(x + 1 < tiles[y].len() && !is_air_or_empty(tiles[y][x+1])) {
-            painter.rect_filled(Rect::from_min_size(Pos2::new(pos.x + tile_size, pos.y), Vec2::new(1.0, tile_size)), 0.0, EXTERNAL_BORDER_COLOR);
+            painter.rect_filled(Rect::from_min_size(Pos2::new(pos.x + tile_size, pos.y), Vec2::new(1.0, tile_size)), 0.0, border_color);
         }
     }
 }
@@ -279,6 +423,7 @@ fn render_tile(
     _tile: char,
     _tile_size: f32,
     visible: bool,
+    tint: Color32,
 ) {
     ensure_tileset_id_path_map_loaded_from_celeste(editor);
     render_any_tile(
@@ -295,11 +440,30 @@ fn render_tile(
         &|c| !is_solid_tile(c),
         SOLID_TILE_COLOR,
         tile_xml::TILESET_ID_PATH_MAP_FG.get(),
-        &ld.fg_xml_path,
-        "FG",
+        &editor.fg_xml_path,
+        tint,
     );
 }
 
+/// Render a single foreground tile as a flat translucent rect (ghost mode)
+fn render_ghost_tile(
+    painter: &egui::Painter,
+    ld: &LevelRenderData,
+    editor: &CelesteMapEditor,
+    x: usize,
+    y: usize,
+    tile: char,
+    tile_size: f32,
+    tint: Color32,
+) {
+    if !is_solid_tile(tile) {
+        return;
+    }
+    let pos = ViewTransform::of(editor).room_tile_to_screen(RoomGrid::of_render_data(ld), (x as i32, y as i32));
+    let rect = Rect::from_min_size(pos, Vec2::splat(tile_size));
+    painter.rect_filled(rect, 0.0, apply_tint(SOLID_TILE_COLOR.linear_multiply(GHOST_TILE_OPACITY), tint));
+}
+
 /// Render a single background tile (filled + borders) using the passed LevelRenderData
 fn render_bg_tile(
     painter: &egui::Painter,
@@ -310,7 +474,12 @@ fn render_bg_tile(
     _tile: char,
     _tile_size: f32,
     visible: bool,
+    tint: Color32,
 ) {
+    // Background tiles never collide, so the collision view leaves them out
+    if editor.collision_debug_mode {
+        return;
+    }
     ensure_tileset_id_path_map_loaded_from_celeste(editor);
     render_any_tile(
         painter,
@@ -326,8 +495,8 @@ fn render_bg_tile(
         &|c| c == '0',
         INFILL_COLOR,
         tile_xml::TILESET_ID_PATH_MAP_BG.get(),
-        &ld.bg_xml_path,
-        "BG",
+        &editor.bg_xml_path,
+        tint,
     );
 }
 
@@ -341,6 +510,7 @@ fn render_decals(
     room_x: f32,
     room_y: f32,
     filter_fn: &dyn Fn(&serde_json::Value) -> bool,
+    tint: Color32,
 ) {
     if let Some(children) = level["__children"].as_array() {
         for c in children.iter().filter(|c| filter_fn(c)) {
@@ -357,21 +527,24 @@ fn render_decals(
                         .as_ref()
                         .and_then(|am| am.get_sprite("Gameplay", &path))
                     {
-                        let global_scale = TILE_SIZE / 8.0 * editor.zoom_level;
-                        let center_x = (room_x + x) * global_scale - editor.camera_pos.x;
-                        let center_y = (room_y + y) * global_scale - editor.camera_pos.y;
+                        let transform = ViewTransform::of(editor);
+                        let global_scale = transform.scale();
+                        let center = transform.world_to_screen(Pos2::new(room_x + x, room_y + y));
 
-                        let width_px  = spr.metadata.width  as f32 * sx * global_scale * DECAL_SCALE;
-                        let height_px = spr.metadata.height as f32 * sy * global_scale * DECAL_SCALE;
+                        // Negative scale mirrors the sprite; the size itself stays positive
+                        let width_px  = spr.metadata.width  as f32 * sx.abs() * global_scale * DECAL_SCALE;
+                        let height_px = spr.metadata.height as f32 * sy.abs() * global_scale * DECAL_SCALE;
 
-                        let pos  = Pos2::new(center_x - width_px  * 0.5, center_y - height_px * 0.5);
+                        let pos  = Pos2::new(center.x - width_px  * 0.5, center.y - height_px * 0.5);
                         let size = Vec2::new(width_px, height_px);
 
                         editor.atlas_manager.as_ref().unwrap().draw_sprite(
                             spr,
                             painter,
                             Rect::from_min_size(pos, size),
-                            Color32::WHITE,
+                            tint,
+                            sx < 0.0,
+                            sy < 0.0,
                         );
                     }
                 }
@@ -380,6 +553,66 @@ fn render_decals(
     }
 }
 
+/// Render entities as outlined boxes, except spikes and jumpthrus which are tiled along their length
+fn render_entities(
+    editor: &CelesteMapEditor,
+    painter: &egui::Painter,
+    level: &serde_json::Value,
+    room_x: f32,
+    room_y: f32,
+    tint: Color32,
+) {
+    let transform = ViewTransform::of(editor);
+    let global_scale = transform.scale();
+    let color = apply_tint(ENTITY_COLOR, tint);
+    let atlas = editor.atlas_manager.as_ref().filter(|_| editor.use_textures);
+    if let Some(children) = level["__children"].as_array() {
+        for c in children.iter().filter(|c| c["__name"] == "entities") {
+            if let Some(ents) = c["__children"].as_array() {
+                let origin = transform.world_to_screen(Pos2::new(room_x, room_y));
+                for e in ents {
+                    if render_sized_entity(atlas, painter, e, origin, global_scale, tint) { continue; }
+                    let x = e["x"].as_f64().unwrap_or(0.0) as f32;
+                    let y = e["y"].as_f64().unwrap_or(0.0) as f32;
+                    let w = e["width"].as_f64().unwrap_or(8.0).max(1.0) as f32;
+                    let h = e["height"].as_f64().unwrap_or(8.0).max(1.0) as f32;
+
+                    let pos = transform.world_to_screen(Pos2::new(room_x + x, room_y + y));
+                    let rect = Rect::from_min_size(pos, Vec2::new(w * global_scale, h * global_scale));
+                    painter.rect_filled(rect, 0.0, color.linear_multiply(0.3));
+                    painter.rect_stroke(rect, 0.0, Stroke::new(1.0, color));
+                }
+            }
+        }
+    }
+}
+
+/// Triggers as translucent boxes labelled with their name
+fn render_triggers(
+    editor: &CelesteMapEditor,
+    painter: &egui::Painter,
+    level: &serde_json::Value,
+    room_x: f32,
+    room_y: f32,
+    tint: Color32,
+) {
+    let transform = ViewTransform::of(editor);
+    let color = apply_tint(TRIGGER_COLOR, tint);
+    for t in level_triggers(level) {
+        let x = t["x"].as_f64().unwrap_or(0.0) as f32;
+        let y = t["y"].as_f64().unwrap_or(0.0) as f32;
+        let w = t["width"].as_f64().unwrap_or(8.0).max(1.0) as f32;
+        let h = t["height"].as_f64().unwrap_or(8.0).max(1.0) as f32;
+        let rect = transform.world_rect_to_screen(Rect::from_min_size(Pos2::new(room_x + x, room_y + y), Vec2::new(w, h)));
+        painter.rect_filled(rect, 0.0, color.linear_multiply(0.15));
+        painter.rect_stroke(rect, 0.0, Stroke::new(1.0, color));
+        if rect.height() >= TRIGGER_LABEL_MIN_HEIGHT {
+            let name = t["__name"].as_str().unwrap_or("trigger");
+            painter.with_clip_rect(rect).text(rect.center(), egui::Align2::CENTER_CENTER, name, egui::FontId::proportional(11.0), color);
+        }
+    }
+}
+
 /// Calcule le début de la grille (pour x ou y)
 fn compute_grid_start(cam_coord: f32, tile_size: f32) -> f32 {
     cam_coord % tile_size
@@ -418,6 +651,82 @@ fn draw_grid(painter: &egui::Painter, view: Rect, cam: Vec2, tile_size: f32, zoo
     }
 }
 
+/// Lines every SCREEN_GRID_PIXELS of the map, aligned on its origin, for lining rooms up with screen boundaries
+fn draw_screen_grid(editor: &CelesteMapEditor, painter: &egui::Painter, view: Rect) {
+    let transform = ViewTransform::of(editor);
+    let spacing = SCREEN_GRID_PIXELS * transform.scale();
+    if spacing < SCREEN_GRID_MIN_SPACING { return; }
+    let stroke = Stroke::new(2.0, SCREEN_GRID_COLOR);
+    let first = transform.screen_to_world(view.min).to_vec2() / SCREEN_GRID_PIXELS;
+    let start = transform.world_to_screen((first.floor() * SCREEN_GRID_PIXELS).to_pos2());
+    let mut x = start.x;
+    while x <= view.max.x {
+        painter.vline(x, view.y_range(), stroke);
+        x += spacing;
+    }
+    let mut y = start.y;
+    while y <= view.max.y {
+        painter.hline(view.x_range(), y, stroke);
+        y += spacing;
+    }
+}
+
+/// "YYYY-MM-DD HH:MM UTC" without pulling in a date crate
+fn format_utc(time: std::time::SystemTime) -> String {
+    let secs = time.duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs()) as i64;
+    let (days, rem) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+    // Civil-from-days (Howard Hinnant)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02} {:02}:{:02} UTC", year, month, day, rem / 3600, rem % 3600 / 60)
+}
+
+fn snap_label(snap: Option<u32>) -> String {
+    match snap {
+        Some(px) => format!("{} px",px),
+        None => "Off".to_string(),
+    }
+}
+
+/// Pixel snap lines inside the current room, drawn while working on entities and zoomed in enough to tell them apart
+fn draw_snap_subgrid(editor: &CelesteMapEditor, painter: &egui::Painter) {
+    let Some(step) = editor.entity_snap else { return };
+    if step >= 8 || editor.zoom_level < SUBGRID_MIN_ZOOM { return; }
+    let Some(room) = editor.cached_rooms.get(editor.current_level_index) else { return };
+    let ld = &room.level_data;
+    let transform = ViewTransform::of(editor);
+    let spacing = step as f32 * transform.scale();
+    if spacing < SUBGRID_MIN_SPACING { return; }
+    let room_rect = transform.room_rect(ld);
+    let rect = room_rect.intersect(painter.clip_rect());
+    if !rect.is_positive() { return; }
+    let stroke = Stroke::new(1.0, SUBGRID_COLOR);
+    let (origin_x, origin_y) = (room_rect.min.x, room_rect.min.y);
+    let mut x = origin_x + ((rect.min.x - origin_x) / spacing).ceil() * spacing;
+    while x < rect.max.x {
+        painter.vline(x, rect.y_range(), stroke);
+        x += spacing;
+    }
+    let mut y = origin_y + ((rect.min.y - origin_y) / spacing).ceil() * spacing;
+    while y < rect.max.y {
+        painter.hline(rect.x_range(), y, stroke);
+        y += spacing;
+    }
+}
+
+/// Columns and rows of the tile grid `tiles` of `ld` overlapping the screen rect `rect`
+fn visible_tile_range<T>(editor: &CelesteMapEditor, ld: &LevelRenderData, tiles: &[Vec<T>], rect: Rect) -> (Range<usize>, Range<usize>) {
+    let cols = tiles.iter().map(Vec::len).max().unwrap_or(0);
+    ViewTransform::of(editor).visible_room_tiles(RoomGrid::of_render_data(ld), cols, tiles.len(), rect)
+}
+
 /// Batch render tiles
 fn batch_render_tiles(
     editor: &mut CelesteMapEditor,
@@ -426,32 +735,21 @@ fn batch_render_tiles(
     _tile_size: f32,
     rect: Rect,
     _ctx: &egui::Context,
+    ghost: bool,
+    tint: Color32,
 ) {
-    // convert room origin from Celeste pixels (8px units) into tile-space
-    let origin_tiles_x = (ld.x + ld.offset_x as f32) / 8.0;
-    let origin_tiles_y = (ld.y + ld.offset_y as f32) / 8.0;
-
-    // compute the range of tile indices intersecting our expanded view
-    let start_x = ((rect.min.x + editor.camera_pos.x) / (TILE_SIZE * editor.zoom_level) - origin_tiles_x)
-        .floor()
-        .max(0.0) as usize;
-    let start_y = ((rect.min.y + editor.camera_pos.y) / (TILE_SIZE * editor.zoom_level) - origin_tiles_y)
-        .floor()
-        .max(0.0) as usize;
-    let end_x   = ((rect.max.x + editor.camera_pos.x) / (TILE_SIZE * editor.zoom_level) - origin_tiles_x)
-        .ceil()
-        .max(0.0) as usize;
-    let end_y   = ((rect.max.y + editor.camera_pos.y) / (TILE_SIZE * editor.zoom_level) - origin_tiles_y)
-        .ceil()
-        .max(0.0) as usize;
+    let (cols, rows) = visible_tile_range(editor, ld, &ld.solids, rect);
 
     // only iterate over those rows/cols
-    for yy in start_y..=end_y {
-        if yy >= ld.solids.len() { continue; }
-        for xx in start_x..=end_x {
+    for yy in rows {
+        for xx in cols.clone() {
             if xx >= ld.solids[yy].len() { continue; }
             let _tile = ld.solids[yy][xx];
-            render_tile(painter, ld, editor, xx, yy, _tile, TILE_SIZE * editor.zoom_level, true);
+            if ghost {
+                render_ghost_tile(painter, ld, editor, xx, yy, _tile, TILE_SIZE * editor.zoom_level, tint);
+            } else {
+                render_tile(painter, ld, editor, xx, yy, _tile, TILE_SIZE * editor.zoom_level, true, tint);
+            }
         }
     }
 }
@@ -464,31 +762,15 @@ fn batch_render_bg_tiles(
     _tile_size: f32,
     rect: Rect,
     _ctx: &egui::Context,
+    tint: Color32,
 ) {
-    // convert room origin from Celeste pixels (8px units) into tile-space
-    let origin_tiles_x = (ld.x + ld.offset_x as f32) / 8.0;
-    let origin_tiles_y = (ld.y + ld.offset_y as f32) / 8.0;
-
-    // compute the range of tile indices intersecting our expanded view
-    let start_x = ((rect.min.x + editor.camera_pos.x) / (TILE_SIZE * editor.zoom_level) - origin_tiles_x)
-        .floor()
-        .max(0.0) as usize;
-    let start_y = ((rect.min.y + editor.camera_pos.y) / (TILE_SIZE * editor.zoom_level) - origin_tiles_y)
-        .floor()
-        .max(0.0) as usize;
-    let end_x   = ((rect.max.x + editor.camera_pos.x) / (TILE_SIZE * editor.zoom_level) - origin_tiles_x)
-        .ceil()
-        .max(0.0) as usize;
-    let end_y   = ((rect.max.y + editor.camera_pos.y) / (TILE_SIZE * editor.zoom_level) - origin_tiles_y)
-        .ceil()
-        .max(0.0) as usize;
-
-    for yy in start_y..=end_y {
-        if yy >= ld.bg.len() { continue; }
-        for xx in start_x..=end_x {
+    let (cols, rows) = visible_tile_range(editor, ld, &ld.bg, rect);
+
+    for yy in rows {
+        for xx in cols.clone() {
             if xx >= ld.bg[yy].len() { continue; }
             let _tile = ld.bg[yy][xx];
-            render_bg_tile(painter, ld, editor, xx, yy, _tile, TILE_SIZE * editor.zoom_level, true);
+            render_bg_tile(painter, ld, editor, xx, yy, _tile, TILE_SIZE * editor.zoom_level, true, tint);
         }
     }
 }
@@ -504,7 +786,60 @@ pub trait Layer {
         tile_size: f32,
         view: Rect,
         ctx: &egui::Context,
+        tint: Color32,
     );
+
+    /// Tile layers are the only ones drawn for dimmed neighbouring rooms.
+    fn is_tile_layer(&self) -> bool {
+        false
+    }
+}
+
+/// Parallax backgrounds, approximating the in-game view behind a room.
+pub struct StylegroundLayer;
+impl Layer for StylegroundLayer {
+    fn render(
+        &self,
+        editor: &mut CelesteMapEditor,
+        painter: &egui::Painter,
+        ld: &LevelRenderData,
+        _json: Option<&serde_json::Value>,
+        _tile_size: f32,
+        view: Rect,
+        _ctx: &egui::Context,
+        tint: Color32,
+    ) {
+        let Some(atlas) = editor.atlas_manager.as_ref() else { return };
+        let transform = ViewTransform::of(editor);
+        let global_scale = transform.scale();
+        let clip = transform.room_rect(ld).intersect(view);
+        if !clip.is_positive() { return; }
+        let painter = painter.with_clip_rect(clip);
+        for layer in editor.parallax_layers.iter().filter(|l| l.applies_to(&ld.name)) {
+            let Some(spr) = atlas.get_sprite("Gameplay", &layer.texture) else { continue };
+            let size = Vec2::new(spr.metadata.width as f32, spr.metadata.height as f32) * global_scale;
+            if size.x <= 0.0 || size.y <= 0.0 { continue; }
+            // In game the layer sits at (x, y) - camera * scroll on screen; the editor camera stands in
+            let mut start = Pos2::new(
+                layer.x * global_scale - editor.camera_pos.x * layer.scroll_x,
+                layer.y * global_scale - editor.camera_pos.y * layer.scroll_y,
+            );
+            if layer.loop_x { start.x -= ((start.x - clip.min.x) / size.x).ceil() * size.x; }
+            if layer.loop_y { start.y -= ((start.y - clip.min.y) / size.y).ceil() * size.y; }
+            let color = apply_tint(layer.color, tint);
+            let mut y = start.y;
+            while y < clip.max.y {
+                let mut x = start.x;
+                while x < clip.max.x {
+                    atlas.draw_sprite(spr, &painter, Rect::from_min_size(Pos2::new(x, y), size), color, false, false);
+                    if !layer.loop_x { break; }
+                    x += size.x;
+                }
+                if !layer.loop_y { break; }
+                y += size.y;
+            }
+        }
+    }
 }
 
 pub struct BgTileLayer;
@@ -518,10 +853,15 @@ impl Layer for BgTileLayer {
         tile_size: f32,
         view: Rect,
         ctx: &egui::Context,
+        tint: Color32,
     ) {
         let margin = CULLING_THRESHOLD_BASE * (2.0 / editor.zoom_level.max(0.1));
         let expanded_view = view.expand(margin);
-        batch_render_bg_tiles(editor, painter, ld, tile_size, expanded_view, ctx);
+        batch_render_bg_tiles(editor, painter, ld, tile_size, expanded_view, ctx, tint);
+    }
+
+    fn is_tile_layer(&self) -> bool {
+        true
     }
 }
 
@@ -536,6 +876,7 @@ impl Layer for BgDecalLayer {
         _tile_size: f32,
         _view: Rect,
         ctx: &egui::Context,
+        tint: Color32,
     ) {
         if let Some(json) = json {
             render_decals(
@@ -547,6 +888,7 @@ impl Layer for BgDecalLayer {
                 ld.x,
                 ld.y,
                 &|c| c["__name"] == "bgdecals",
+                tint,
             );
         }
     }
@@ -563,12 +905,20 @@ impl Layer for FgTileLayer {
         tile_size: f32,
         view: Rect,
         ctx: &egui::Context,
+        tint: Color32,
     ) {
-        if editor.show_tiles {
-            let margin = CULLING_THRESHOLD_BASE * (2.0 / editor.zoom_level.max(0.1));
-            let expanded_view = view.expand(margin);
-            batch_render_tiles(editor, painter, ld, tile_size, expanded_view, ctx);
-        }
+        let ghost = match editor.tile_render_mode {
+            TileRenderMode::Full => false,
+            TileRenderMode::Ghost => true,
+            TileRenderMode::Hidden => return,
+        };
+        let margin = CULLING_THRESHOLD_BASE * (2.0 / editor.zoom_level.max(0.1));
+        let expanded_view = view.expand(margin);
+        batch_render_tiles(editor, painter, ld, tile_size, expanded_view, ctx, ghost, tint);
+    }
+
+    fn is_tile_layer(&self) -> bool {
+        true
     }
 }
 
@@ -583,6 +933,7 @@ impl Layer for FgDecalLayer {
         _tile_size: f32,
         _view: Rect,
         ctx: &egui::Context,
+        tint: Color32,
     ) {
         if editor.show_fgdecals {
             if let Some(json) = json {
@@ -595,24 +946,132 @@ impl Layer for FgDecalLayer {
                     ld.x,
                     ld.y,
                     &|c| c["__name"] == "fgdecals",
+                    tint,
                 );
             }
         }
     }
 }
 
+pub struct EntityLayer;
+impl Layer for EntityLayer {
+    fn render(
+        &self,
+        editor: &mut CelesteMapEditor,
+        painter: &egui::Painter,
+        ld: &LevelRenderData,
+        json: Option<&serde_json::Value>,
+        _tile_size: f32,
+        _view: Rect,
+        _ctx: &egui::Context,
+        tint: Color32,
+    ) {
+        if let Some(json) = json {
+            render_entities(editor, painter, json, ld.x, ld.y, tint);
+            render_triggers(editor, painter, json, ld.x, ld.y, tint);
+        }
+    }
+}
+
+/// Names a draw layer. The order of these in `ViewSettings::layer_order` is the draw order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LayerId {
+    Stylegrounds,
+    BgTiles,
+    BgDecals,
+    FgTiles,
+    FgDecals,
+    Entities,
+}
+
+impl LayerId {
+    /// Default draw order, back to front.
+    pub const ALL: [LayerId; LAYER_COUNT] = [
+        LayerId::Stylegrounds,
+        LayerId::BgTiles,
+        LayerId::BgDecals,
+        LayerId::FgTiles,
+        LayerId::FgDecals,
+        LayerId::Entities,
+    ];
+
+    /// Index into `CelesteMapEditor::layer_visibility`.
+    pub fn index(self) -> usize {
+        match self {
+            LayerId::Stylegrounds => LAYER_STYLEGROUNDS,
+            LayerId::BgTiles => LAYER_BG_TILES,
+            LayerId::BgDecals => LAYER_BG_DECALS,
+            LayerId::FgTiles => LAYER_FG_TILES,
+            LayerId::FgDecals => LAYER_FG_DECALS,
+            LayerId::Entities => LAYER_ENTITIES,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LayerId::Stylegrounds => "Stylegrounds",
+            LayerId::BgTiles => "Background Tiles",
+            LayerId::BgDecals => "Background Decals",
+            LayerId::FgTiles => "Foreground Tiles",
+            LayerId::FgDecals => "Foreground Decals",
+            LayerId::Entities => "Entities",
+        }
+    }
+
+    fn layer(self) -> Box<dyn Layer> {
+        match self {
+            LayerId::Stylegrounds => Box::new(StylegroundLayer),
+            LayerId::BgTiles => Box::new(BgTileLayer),
+            LayerId::BgDecals => Box::new(BgDecalLayer),
+            LayerId::FgTiles => Box::new(FgTileLayer),
+            LayerId::FgDecals => Box::new(FgDecalLayer),
+            LayerId::Entities => Box::new(EntityLayer),
+        }
+    }
+}
+
+/// `order` without repeats and with any missing layer appended, so a stale config can't lose one.
+pub fn normalize_layer_order(order: &[LayerId]) -> Vec<LayerId> {
+    let mut normalized: Vec<LayerId> = Vec::with_capacity(LAYER_COUNT);
+    for id in order.iter().chain(LayerId::ALL.iter()) {
+        if !normalized.contains(id) {
+            normalized.push(*id);
+        }
+    }
+    normalized
+}
+
+/// Solo `id`, or end the solo if `id` is already soloed. Ending a solo puts back the visibility
+/// toggles from before it started.
+pub fn toggle_solo_layer(editor: &mut CelesteMapEditor, id: LayerId) {
+    if let Some((solo, saved)) = editor.solo_layer.take() {
+        editor.layer_visibility = saved;
+        if solo == id {
+            return;
+        }
+    }
+    editor.solo_layer = Some((id, editor.layer_visibility));
+    editor.layer_visibility[id.index()] = true;
+}
+
+/// Tint for a layer, dimmed when another layer is soloed.
+fn layer_tint(editor: &CelesteMapEditor, id: LayerId, tint: Color32) -> Color32 {
+    match editor.solo_layer {
+        Some((solo, _)) if solo != id => tint.linear_multiply(SOLO_DIM_OPACITY),
+        _ => tint,
+    }
+}
+
 pub struct LayerRegistry {
-    pub layers: Vec<Box<dyn Layer>>,
+    pub layers: Vec<(LayerId, Box<dyn Layer>)>,
 }
 impl LayerRegistry {
     pub fn new() -> Self {
+        Self::from_order(&LayerId::ALL)
+    }
+    pub fn from_order(order: &[LayerId]) -> Self {
         Self {
-            layers: vec![
-                Box::new(BgTileLayer),
-                Box::new(BgDecalLayer),
-                Box::new(FgTileLayer),
-                Box::new(FgDecalLayer),
-            ],
+            layers: order.iter().map(|id| (*id, id.layer())).collect(),
         }
     }
     pub fn render_all(
@@ -624,15 +1083,24 @@ impl LayerRegistry {
         tile_size: f32,
         view: Rect,
         ctx: &egui::Context,
+        tint: Color32,
+        tiles_only: bool,
     ) {
-        for layer in &self.layers {
-            layer.render(editor, painter, ld, json, tile_size, view, ctx);
+        for (id, layer) in &self.layers {
+            if !editor.layer_visibility.get(id.index()).copied().unwrap_or(true) {
+                continue;
+            }
+            if tiles_only && !layer.is_tile_layer() {
+                continue;
+            }
+            let tint = layer_tint(editor, *id, tint);
+            layer.render(editor, painter, ld, json, tile_size, view, ctx, tint);
         }
     }
 }
 
 /// Render room content
-fn render_room_content(
+pub fn render_room_content(
     editor: &mut CelesteMapEditor,
     painter: &egui::Painter,
     ld: &LevelRenderData,
@@ -640,11 +1108,16 @@ fn render_room_content(
     tile_size: f32,
     view: Rect,
     ctx: &egui::Context,
+    tint: Color32,
+    tiles_only: bool,
 ) {
     // Crée un registre de couches à chaque appel (pas de static mut)
-    let registry = LayerRegistry::new();
+    let registry = LayerRegistry::from_order(&editor.view_settings.layer_order);
+    // Keep edge tiles from bleeding into the neighbouring rooms
+    let room_rect = ViewTransform::of(editor).room_rect(ld);
+    let painter = painter.with_clip_rect(room_rect);
     registry.render_all(
-        editor, painter, ld, Some(json), tile_size, view, ctx,
+        editor, &painter, ld, Some(json), tile_size, view, ctx, tint, tiles_only,
     );
     // Les overlays/labels/outlines restent traités après
 }
@@ -665,26 +1138,43 @@ fn render_all_rooms(
             let room = &editor.cached_rooms[i];
             (room.level_data.clone(), room.json.clone())
         };
-        // Compute room rectangle in world coordinates
-        let global_scale = TILE_SIZE / 8.0 * editor.zoom_level;
-        let room_x = (ld.x) * global_scale - editor.camera_pos.x;
-        let room_y = (ld.y) * global_scale - editor.camera_pos.y;
-        let room_w = ld.width * global_scale;
-        let room_h = ld.height * global_scale;
-        let room_rect = egui::Rect::from_min_size(
-            egui::Pos2::new(room_x, room_y),
-            egui::Vec2::new(room_w, room_h),
-        );
-        // Expand view for culling margin
-        let margin = CULLING_THRESHOLD_BASE * (2.0 / editor.zoom_level.max(0.1));
-        let expanded_view = view.expand(margin);
+        // Compute room rectangle on screen
+        let room_rect = ViewTransform::of(editor).room_rect(&ld);
         // Cull rooms not in view
-        if room_rect.intersects(expanded_view) {
+        if room_in_view(editor, room_rect, view) {
             let sel = i == editor.current_level_index;
-            render_room_content(editor, painter, &ld, &json, _tile_size, view, _ctx);
-            render_room_outline_and_label(editor, painter, &ld, _tile_size, _ctx, sel);
+            if editor.show_heat_map {
+                render_room_heat(editor, painter, &ld, i);
+            } else if let Some(color) = room_color(editor, &ld.name, &json) {
+                painter.rect_filled(room_rect, 0.0, color.linear_multiply(ROOM_TINT_ALPHA));
+            }
+            render_room_content(editor, painter, &ld, &json, _tile_size, view, _ctx, Color32::WHITE, false);
+            // The current room's outline comes last so touching rooms don't cover it
+            if !sel {
+                render_room_outline_and_label(editor, painter, &ld, _tile_size, _ctx, false);
+            }
         }
     }
+    if let Some(ld) = editor.cached_rooms.get(editor.current_level_index).map(|r| r.level_data.clone()) {
+        if room_in_view(editor, ViewTransform::of(editor).room_rect(&ld), view) {
+            render_room_outline_and_label(editor, painter, &ld, _tile_size, _ctx, true);
+        }
+    }
+}
+
+/// Culling check for a room's screen rectangle: it intersects the view plus a zoom-dependent margin
+fn room_in_view(editor: &CelesteMapEditor, room_rect: Rect, view: Rect) -> bool {
+    let margin = CULLING_THRESHOLD_BASE * (2.0 / editor.zoom_level.max(0.1));
+    room_rect.intersects(view.expand(margin))
+}
+
+/// Rooms that pass the culling check for the canvas as of the last frame
+fn visible_room_count(editor: &CelesteMapEditor) -> usize {
+    let Some(view) = editor.canvas_rect else { return 0 };
+    let transform = ViewTransform::of(editor);
+    editor.cached_rooms.iter()
+        .filter(|room| room_in_view(editor, transform.room_rect(&room.level_data), view))
+        .count()
 }
 
 /// Render only current room
@@ -701,13 +1191,73 @@ fn render_current_room(
             let room = &editor.cached_rooms[idx];
             (room.level_data.clone(), room.json.clone())
         };
-        render_room_content(editor, painter, &ld, &json, _tile_size, view, _ctx);
+        if editor.show_neighbor_rooms {
+            render_neighbor_rooms(editor, painter, &ld, idx, _tile_size, view, _ctx);
+        }
+        if editor.show_heat_map {
+            render_room_heat(editor, painter, &ld, idx);
+        }
+        render_room_content(editor, painter, &ld, &json, _tile_size, view, _ctx, Color32::WHITE, false);
         render_room_outline_and_label(editor, painter, &ld, _tile_size, _ctx, true);
     }
 }
 
+/// Fill the room background with its heat map colour
+fn render_room_heat(editor: &CelesteMapEditor, painter: &egui::Painter, ld: &LevelRenderData, idx: usize) {
+    let Some(room) = editor.cached_rooms.get(idx) else { return };
+    let rect = ViewTransform::of(editor).room_rect(ld);
+    painter.rect_filled(rect, 0.0, heat_color(room.stats.fill_ratio()));
+}
+
+/// Render the tiles of rooms touching `current`, dimmed, for context in single-room mode
+fn render_neighbor_rooms(
+    editor: &mut CelesteMapEditor,
+    painter: &egui::Painter,
+    current: &LevelRenderData,
+    current_idx: usize,
+    tile_size: f32,
+    view: Rect,
+    ctx: &egui::Context,
+) {
+    let current_rect = Rect::from_min_size(Pos2::new(current.x, current.y), Vec2::new(current.width, current.height));
+    let tint = Color32::from_white_alpha(NEIGHBOR_ROOM_ALPHA);
+    for i in 0..editor.cached_rooms.len() {
+        if i == current_idx { continue; }
+        let ld = &editor.cached_rooms[i].level_data;
+        let rect = Rect::from_min_size(Pos2::new(ld.x, ld.y), Vec2::new(ld.width, ld.height));
+        // Rooms sharing an edge count as touching
+        if !rect.intersects(current_rect.expand(1.0)) { continue; }
+        // Drawing needs the editor mutably, so only the few touching rooms are copied out
+        let (ld, json) = (ld.clone(), editor.cached_rooms[i].json.clone());
+        render_room_content(editor, painter, &ld, &json, tile_size, view, ctx, tint, true);
+    }
+}
+
+// Room names scale with zoom between these sizes, in points
+const LABEL_MIN_SIZE: f32 = 10.0;
+const LABEL_MAX_SIZE: f32 = 48.0;
+// Rooms narrower than this on screen, in points, get no name
+const LABEL_MIN_ROOM_WIDTH: f32 = 40.0;
+const LABEL_BACKING_COLOR: Color32 = Color32::from_rgba_premultiplied(0, 0, 0, 160);
+
+/// Laid-out room name and where it goes on screen, or None when the room is too small to label
+fn room_label(editor: &CelesteMapEditor, painter: &egui::Painter, ld: &LevelRenderData) -> Option<(Rect, std::sync::Arc<egui::Galley>)> {
+    let rect=ViewTransform::of(editor).room_rect(ld);
+    if !editor.show_labels || rect.width()<LABEL_MIN_ROOM_WIDTH { return None; }
+    let size=(editor.view_settings.label_size*editor.zoom_level).clamp(LABEL_MIN_SIZE,LABEL_MAX_SIZE);
+    let galley=painter.layout_no_wrap(ld.name.clone(),egui::FontId::proportional(size),Color32::WHITE);
+    // A name wider than its room would spill over the neighbours
+    if galley.size().x+10.0>rect.width() { return None; }
+    let text_rect=if editor.view_settings.center_labels {
+        Rect::from_center_size(rect.center(),galley.size())
+    } else {
+        Rect::from_min_size(rect.min+Vec2::splat(5.0),galley.size())
+    };
+    Some((text_rect,galley))
+}
+
 /// Draw outline and label
-fn render_room_outline_and_label(
+pub fn render_room_outline_and_label(
     editor: &CelesteMapEditor,
     painter: &egui::Painter,
     ld: &LevelRenderData,
@@ -715,53 +1265,578 @@ fn render_room_outline_and_label(
     _ctx: &egui::Context,
     selected: bool,
 ) {
-    let global_scale = TILE_SIZE / 8.0 * editor.zoom_level;
-    let px=(ld.x)*global_scale-editor.camera_pos.x;
-    let py=(ld.y)*global_scale-editor.camera_pos.y;
-    let w=ld.width*global_scale;
-    let h=ld.height*global_scale;
-    let rect=Rect::from_min_size(Pos2::new(px,py),Vec2::new(w,h));
-    let col=if selected {ROOM_CONTOUR_SELECTED} else {ROOM_CONTOUR_UNSELECTED};
-    let th=if selected {3.0} else {2.0};
-    painter.rect_stroke(rect,0.0,Stroke::new(th,col));
-    if editor.show_labels {
-        painter.text(Pos2::new(px+5.0,py+5.0),egui::Align2::LEFT_TOP,&ld.name,egui::FontId::proportional(16.0),Color32::WHITE);
+    let rect=ViewTransform::of(editor).room_rect(ld);
+    if selected {
+        render_selected_outline(editor,painter,rect,_ctx);
+    } else {
+        let [r,g,b]=editor.view_settings.unselected_outline;
+        painter.rect_stroke(rect,0.0,Stroke::new(2.0,Color32::from_rgb(r,g,b)));
+    }
+    if let Some((text_rect,galley))=room_label(editor,painter,ld) {
+        if editor.view_settings.center_labels {
+            painter.rect_filled(text_rect.expand(4.0),3.0,LABEL_BACKING_COLOR);
+        }
+        painter.galley(text_rect.min,galley);
+    }
+}
+
+/// The current room's outline, in the colour and style set under View > Room Outlines
+fn render_selected_outline(editor: &CelesteMapEditor, painter: &egui::Painter, rect: Rect, ctx: &egui::Context) {
+    let [r,g,b]=editor.view_settings.selected_outline;
+    let color=Color32::from_rgb(r,g,b);
+    match editor.view_settings.selected_outline_style {
+        OutlineStyle::Solid => { painter.rect_stroke(rect,0.0,Stroke::new(3.0,color)); }
+        OutlineStyle::Dashed => {
+            let path=[rect.left_top(),rect.right_top(),rect.right_bottom(),rect.left_bottom(),rect.left_top()];
+            painter.extend(egui::Shape::dashed_line(&path,Stroke::new(3.0,color),OUTLINE_DASH_LENGTH,OUTLINE_GAP_LENGTH));
+        }
+        OutlineStyle::Pulsing => {
+            let pulse=((ctx.input().time*OUTLINE_PULSE_SPEED).sin()*0.5+0.5) as f32;
+            painter.rect_stroke(rect,0.0,Stroke::new(2.0+2.0*pulse,color.linear_multiply(0.6+0.4*pulse)));
+            ctx.request_repaint();
+        }
+    }
+}
+
+/// Music of the room whose label is under the pointer, as a tooltip
+fn room_label_tooltip(editor: &CelesteMapEditor, painter: &egui::Painter, ctx: &egui::Context, pointer: Pos2) {
+    let rooms: Vec<usize> = if editor.show_all_rooms { (0..editor.cached_rooms.len()).collect() } else { vec![editor.current_level_index] };
+    let Some(room) = rooms.into_iter().filter_map(|i| editor.cached_rooms.get(i))
+        .find(|room| room_label(editor,painter,&room.level_data).map_or(false,|(r,_)| r.contains(pointer)))
+    else { return };
+    let (music, alt_music) = room_music(&room.json);
+    let mut text = format!("Music: {}", music.map_or("none", music_track_name));
+    if let Some(alt) = alt_music { text.push_str(&format!("\nAlt music: {}", music_track_name(alt))); }
+    egui::show_tooltip_text(ctx, egui::Id::new("room_label_tooltip"), text);
+}
+
+/// Grips on the current room's border; the dragged one is highlighted
+fn render_resize_handles(editor: &CelesteMapEditor, painter: &egui::Painter) {
+    let dragged = editor.room_resize.as_ref().map(|d| d.handle);
+    for (handle, rect) in handle_rects(editor, editor.current_level_index) {
+        let [r, g, b] = editor.view_settings.selected_outline;
+        let fill = if dragged == Some(handle) { SELECTION_COLOR } else { Color32::from_rgb(r, g, b) };
+        painter.rect(rect, 0.0, fill, Stroke::new(1.0, Color32::BLACK));
+    }
+}
+
+/// Memory held by the atlases, in the canvas corner
+fn render_memory_usage(editor: &CelesteMapEditor, painter: &egui::Painter, view: Rect) {
+    const MIB: f32 = 1024.0 * 1024.0;
+    let text = match editor.atlas_manager.as_ref().map(|am| am.memory_usage()) {
+        Some((textures, pixels)) => format!("Atlases: {:.1} MiB textures, {:.1} MiB CPU pixels", textures as f32 / MIB, pixels as f32 / MIB),
+        None => "Atlases: not loaded".to_string(),
+    };
+    let pos = view.left_top() + Vec2::splat(8.0);
+    let galley = painter.layout_no_wrap(text, egui::FontId::monospace(12.0), Color32::WHITE);
+    painter.rect_filled(Rect::from_min_size(pos, galley.size()).expand(4.0), 3.0, Color32::from_black_alpha(180));
+    painter.galley(pos, galley);
+}
+
+/// Zoom from which the solids debug overlay has room for its text
+const SOLIDS_DEBUG_MIN_ZOOM: f32 = 5.0;
+
+/// Raw solids characters and the autotile coordinate picked for each, over the visible tiles
+fn render_solids_debug(editor: &CelesteMapEditor, painter: &egui::Painter, view: Rect) {
+    let tile_size = TILE_SIZE * editor.zoom_level;
+    let rooms: Vec<usize> = if editor.show_all_rooms {
+        (0..editor.cached_rooms.len()).collect()
+    } else {
+        vec![editor.current_level_index]
+    };
+    let char_font = egui::FontId::monospace(tile_size * 0.3);
+    let coord_font = egui::FontId::monospace(tile_size * 0.15);
+    for idx in rooms {
+        let Some(room) = editor.cached_rooms.get(idx) else { continue };
+        let ld = &room.level_data;
+        let origin = ViewTransform::of(editor).room_tile_to_screen(RoomGrid::of_render_data(ld), (0, 0));
+        for (y, row) in ld.solids.iter().enumerate() {
+            for (x, &tile) in row.iter().enumerate() {
+                let rect = Rect::from_min_size(origin + Vec2::new(x as f32, y as f32) * tile_size, Vec2::splat(tile_size));
+                if !rect.intersects(view) { continue; }
+                painter.rect_stroke(rect.shrink(1.0), 0.0, Stroke::new(1.0, GRID_COLOR));
+                let color = if is_solid_tile(tile) { Color32::YELLOW } else { Color32::GRAY };
+                painter.text(rect.center(), egui::Align2::CENTER_CENTER, tile.to_string(), char_font.clone(), color);
+                let coord = ld.autotile_coords.get(y).and_then(|r| r.get(x)).copied().flatten();
+                if let Some((cx, cy)) = coord {
+                    painter.text(rect.center_bottom() - Vec2::new(0.0, 2.0), egui::Align2::CENTER_BOTTOM, format!("{},{}", cx, cy), coord_font.clone(), Color32::LIGHT_BLUE);
+                }
+            }
+        }
     }
 }
 
 /// Main app rendering
 pub fn render_app(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
     render_top_panel(editor,ctx);
+    render_tab_bar(editor,ctx);
     render_bottom_panel(editor,ctx);
+    if editor.show_room_list && !editor.cached_rooms.is_empty() { render_room_list(editor,ctx); }
+    if editor.show_checkpoints && !editor.cached_rooms.is_empty() { render_room_order_panel(editor,ctx); }
+    if editor.show_collectibles && !editor.cached_rooms.is_empty() { render_collectibles_panel(editor,ctx); }
     render_central_panel(editor,ctx);
+    if !editor.use_textures || !editor.atlas_manager.as_ref().map_or(false, |am| am.has_atlas("Gameplay")) {
+        render_tileset_legend(editor,ctx);
+    }
+}
+
+/// One pixel per tile: fg solids bright, bg tiles dim, air transparent
+fn room_thumbnail_image(ld: &LevelRenderData) -> egui::ColorImage {
+    let w = ((ld.width / 8.0) as usize).max(1);
+    let h = ((ld.height / 8.0) as usize).max(1);
+    let mut image = egui::ColorImage::new([w, h], Color32::TRANSPARENT);
+    let tile_at = |grid: &Vec<Vec<char>>, x: usize, y: usize| grid.get(y).and_then(|r| r.get(x)).copied().unwrap_or('0');
+    for y in 0..h {
+        for x in 0..w {
+            image[(x, y)] = if is_solid_tile(tile_at(&ld.solids, x, y)) {
+                SOLID_TILE_COLOR
+            } else if is_solid_tile(tile_at(&ld.bg, x, y)) {
+                SOLID_TILE_COLOR.linear_multiply(0.35)
+            } else {
+                Color32::TRANSPARENT
+            };
+        }
+    }
+    image
+}
+
+/// Rooms sorted top-to-bottom then left-to-right, each with a thumbnail, name and size
+fn render_room_list(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    const ROW_HEIGHT: f32 = 40.0;
+    let mut order: Vec<usize> = (0..editor.cached_rooms.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&editor.cached_rooms[a].level_data, &editor.cached_rooms[b].level_data);
+        a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x))
+    });
+    let mut clicked = None;
+    let mut copy_to = None;
+    let mut free_space = editor.copy_room_to_free_space;
+    // Rooms copy into the maps of the other tabs
+    let targets: Vec<(usize, String)> = (0..editor.open_maps.len())
+        .filter(|&t| t != editor.active_tab)
+        .map(|t| (t, tab_title(editor, t)))
+        .collect();
+    egui::SidePanel::left("room_list").resizable(true).default_width(200.0).show(ctx,|ui|{
+        ui.heading("Rooms");
+        egui::ScrollArea::vertical().show_rows(ui,ROW_HEIGHT,order.len(),|ui,range|{
+            for &i in &order[range] {
+                let room = &mut editor.cached_rooms[i];
+                let texture = room.thumbnail.get_or_insert_with(|| {
+                    ctx.load_texture(format!("room_thumb_{}",i), room_thumbnail_image(&room.level_data), egui::TextureFilter::Nearest)
+                });
+                let size = texture.size_vec2();
+                let thumb = size * ((ROW_HEIGHT - 4.0) / size.x.max(size.y));
+                let ld = &room.level_data;
+                let selected = editor.current_level_index == i;
+                ui.horizontal(|ui|{
+                    ui.set_height(ROW_HEIGHT);
+                    let (rect,_) = ui.allocate_exact_size(Vec2::splat(ROW_HEIGHT - 4.0),egui::Sense::hover());
+                    ui.painter().rect_filled(rect,0.0,BG_COLOR);
+                    ui.painter().image(texture.id(),Rect::from_center_size(rect.center(),thumb),Rect::from_min_max(Pos2::ZERO,Pos2::new(1.0,1.0)),Color32::WHITE);
+                    let label = format!("{}\n{}x{}",ld.name,(ld.width/8.0) as i32,(ld.height/8.0) as i32);
+                    let resp = ui.selectable_label(selected,label).on_hover_text(room.stats.summary());
+                    if resp.clicked() { clicked = Some(i); }
+                    let (load,color) = room.stats.heaviness();
+                    ui.colored_label(color,"●").on_hover_text(format!("{} to draw: {} drawables",load,room.stats.drawables()));
+                    resp.context_menu(|ui|{
+                        ui.menu_button("Copy Room to",|ui|{
+                            if targets.is_empty() { ui.weak("Open another map in a tab first"); }
+                            for (t,title) in &targets {
+                                if ui.button(title).clicked(){ copy_to = Some((i,*t));ui.close_menu(); }
+                            }
+                        });
+                        ui.checkbox(&mut free_space,"Move into free space");
+                    });
+                    // Music column, with the full event paths on hover
+                    let (music, alt_music) = room_music(&room.json);
+                    if let Some(music) = music {
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center),|ui|{
+                            let paths = match alt_music { Some(alt) => format!("{}\nAlt: {}",music,alt), None => music.to_string() };
+                            ui.weak(music_track_name(music)).on_hover_text(paths);
+                        });
+                    }
+                });
+            }
+        });
+    });
+    editor.copy_room_to_free_space = free_space;
+    if let Some((i,t)) = copy_to { copy_room_to_tab(editor,i,t); }
+    if let Some(i) = clicked {
+        editor.current_level_index = i;
+        let ld = &editor.cached_rooms[i].level_data;
+        let global_scale = ViewTransform::of(editor).scale();
+        let center = Vec2::new(ld.x + ld.width / 2.0, ld.y + ld.height / 2.0) * global_scale;
+        let zoom = editor.zoom_level;
+        move_view_to(editor, center - ctx.available_rect().center().to_vec2(), zoom);
+    }
+}
+
+/// Number the checkpoints in map order and link consecutive ones with arrows
+fn render_checkpoint_order(editor: &CelesteMapEditor, painter: &egui::Painter) {
+    let transform = ViewTransform::of(editor);
+    let markers: Vec<Pos2> = editor.cached_rooms.iter().filter_map(|room| {
+        let (cx, cy) = checkpoint_position(&room.json)?;
+        let ld = &room.level_data;
+        Some(transform.world_to_screen(Pos2::new(ld.x + cx as f32, ld.y + cy as f32)))
+    }).collect();
+    let stroke = Stroke::new(2.0, CHECKPOINT_COLOR);
+    for pair in markers.windows(2) {
+        let dir = pair[1] - pair[0];
+        let len = dir.length();
+        if len <= 2.0 * CHECKPOINT_MARKER_RADIUS { continue; }
+        let unit = dir / len;
+        painter.arrow(pair[0] + unit * CHECKPOINT_MARKER_RADIUS, unit * (len - 2.0 * CHECKPOINT_MARKER_RADIUS), stroke);
+    }
+    for (n, pos) in markers.iter().enumerate() {
+        if draw_overlay_icon(editor, painter, CHECKPOINT_ICON_PATHS, *pos, 2.0 * CHECKPOINT_MARKER_RADIUS) {
+            // The number moves to the flag's corner so the icon stays readable
+            let corner = *pos + Vec2::splat(CHECKPOINT_MARKER_RADIUS);
+            painter.text(corner, egui::Align2::CENTER_CENTER, (n + 1).to_string(), egui::FontId::proportional(12.0), CHECKPOINT_COLOR);
+            continue;
+        }
+        painter.circle(*pos, CHECKPOINT_MARKER_RADIUS, Color32::from_black_alpha(200), stroke);
+        painter.text(*pos, egui::Align2::CENTER_CENTER, (n + 1).to_string(), egui::FontId::proportional(14.0), CHECKPOINT_COLOR);
+    }
+}
+
+/// Draw the first of `paths` found in any loaded atlas, fitted in a `size` square centred on `pos`.
+/// Returns false when textures are off or none was found, so the caller draws its plain marker.
+fn draw_overlay_icon(editor: &CelesteMapEditor, painter: &egui::Painter, paths: &[&str], pos: Pos2, size: f32) -> bool {
+    if !editor.use_textures { return false; }
+    let Some(atlas) = editor.atlas_manager.as_ref() else { return false };
+    let Some(sprite) = paths.iter().find_map(|path| atlas.get_sprite_any(path)) else { return false };
+    let sprite_size = Vec2::new(sprite.metadata.width as f32, sprite.metadata.height as f32).max(Vec2::splat(1.0));
+    let fit = size / sprite_size.x.max(sprite_size.y);
+    atlas.draw_sprite(sprite, painter, Rect::from_center_size(pos, sprite_size * fit), Color32::WHITE, false, false);
+    true
+}
+
+/// Top-left of the game camera for a player standing at `spawn`, in room pixels: centred on the
+/// player, shifted by the camera offset and kept inside the room like the game does.
+fn spawn_camera_origin(ld: &LevelRenderData, spawn: (f64, f64), offset: (f64, f64)) -> Vec2 {
+    let x = spawn.0 as f32 - CAMERA_WIDTH / 2.0 + offset.0 as f32;
+    let y = spawn.1 as f32 - CAMERA_HEIGHT / 2.0 + offset.1 as f32;
+    Vec2::new(
+        x.min(ld.width - CAMERA_WIDTH).max(0.0),
+        y.min(ld.height - CAMERA_HEIGHT).max(0.0),
+    )
+}
+
+/// Camera frames over the current room: one per spawn point, and optionally the room cut into screens
+fn render_camera_frames(editor: &CelesteMapEditor, painter: &egui::Painter) {
+    let Some(room) = editor.cached_rooms.get(editor.current_level_index) else { return };
+    let ld = &room.level_data;
+    let transform = ViewTransform::of(editor);
+    let global_scale = transform.scale();
+    let to_screen = |p: Vec2| transform.world_to_screen(Pos2::new(ld.x, ld.y) + p);
+    let camera_size = Vec2::new(CAMERA_WIDTH, CAMERA_HEIGHT) * global_scale;
+    let room_rect = Rect::from_min_size(to_screen(Vec2::ZERO), Vec2::new(ld.width, ld.height) * global_scale);
+    if editor.tile_camera_frames {
+        let stroke = Stroke::new(1.0, CAMERA_CELL_COLOR);
+        let mut x = CAMERA_WIDTH;
+        while x < ld.width {
+            painter.vline(to_screen(Vec2::new(x, 0.0)).x, room_rect.y_range(), stroke);
+            x += CAMERA_WIDTH;
+        }
+        let mut y = CAMERA_HEIGHT;
+        while y < ld.height {
+            painter.hline(room_rect.x_range(), to_screen(Vec2::new(0.0, y)).y, stroke);
+            y += CAMERA_HEIGHT;
+        }
+    }
+    let offset = camera_offset(&room.json);
+    for spawn in spawn_positions(&room.json) {
+        let frame = Rect::from_min_size(to_screen(spawn_camera_origin(ld, spawn, offset)), camera_size);
+        painter.rect_stroke(frame, 0.0, Stroke::new(2.0, CAMERA_FRAME_COLOR));
+    }
+}
+
+/// Pulsing ring around the Find result last jumped to; cleared once it has played out
+fn render_find_flash(editor: &mut CelesteMapEditor, painter: &egui::Painter, ctx: &egui::Context) {
+    let Some((room_idx, x, y, start)) = editor.find_flash else { return };
+    let t = start.elapsed().as_secs_f32();
+    let Some(room) = editor.cached_rooms.get(room_idx).filter(|_| t < FIND_FLASH_DURATION) else {
+        editor.find_flash = None;
+        return;
+    };
+    let ld = &room.level_data;
+    let center = ViewTransform::of(editor).world_to_screen(Pos2::new(ld.x + x as f32, ld.y + y as f32));
+    let pulse = (t * 6.0).sin() * 0.5 + 0.5;
+    let fade = 1.0 - t / FIND_FLASH_DURATION;
+    painter.circle_stroke(center, 10.0 + 8.0 * pulse, Stroke::new(3.0, SELECTION_COLOR.linear_multiply(fade)));
+    ctx.request_repaint();
+}
+
+/// Marker over each collectible, drawn regardless of entity layer visibility
+fn render_collectible_markers(editor: &CelesteMapEditor, painter: &egui::Painter) {
+    let transform = ViewTransform::of(editor);
+    for c in &editor.collectibles {
+        if !editor.show_all_rooms && c.room_idx != editor.current_level_index { continue; }
+        let Some(room) = editor.cached_rooms.get(c.room_idx) else { continue };
+        let ld = &room.level_data;
+        let pos = transform.world_to_screen(Pos2::new(ld.x + c.x as f32, ld.y + c.y as f32));
+        if draw_overlay_icon(editor, painter, c.kind.icon_paths(), pos, 2.0 * COLLECTIBLE_MARKER_RADIUS) { continue; }
+        let color = c.kind.color();
+        painter.circle(pos, COLLECTIBLE_MARKER_RADIUS, Color32::from_black_alpha(200), Stroke::new(2.0, color));
+        painter.text(pos, egui::Align2::CENTER_CENTER, c.kind.symbol(), egui::FontId::proportional(12.0), color);
+    }
+}
+
+/// Collectibles with their order; clicking one centres the view on it
+fn render_collectibles_panel(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    let collectibles = &editor.collectibles;
+    let mut clicked = None;
+    egui::SidePanel::right("collectibles").resizable(true).default_width(220.0).show(ctx,|ui|{
+        ui.heading("Collectibles");
+        let berries = collectibles.iter().filter(|c| c.kind == CollectibleKind::Strawberry).count();
+        ui.label(format!("{} berries, {} total", berries, collectibles.len()));
+        ui.separator();
+        egui::ScrollArea::vertical().show(ui,|ui|{
+            let mut berry = 0;
+            for c in collectibles {
+                let room = editor.level_names.get(c.room_idx).map(String::as_str).unwrap_or("?");
+                let mut text = match c.kind {
+                    CollectibleKind::Strawberry => {
+                        berry += 1;
+                        format!("#{} {} in {}", berry, c.kind.label(), room)
+                    }
+                    _ => format!("{} in {}", c.kind.label(), room),
+                };
+                if let (Some(cp), Some(order)) = (c.checkpoint_id, c.order) {
+                    text.push_str(&format!(" (cp {}, order {})", cp, order));
+                }
+                if ui.selectable_label(c.room_idx == editor.current_level_index, egui::RichText::new(text).color(c.kind.color())).clicked() {
+                    clicked = Some(c.clone());
+                }
+            }
+        });
+    });
+    if let Some(c) = clicked {
+        editor.current_level_index = c.room_idx;
+        let ld = &editor.cached_rooms[c.room_idx].level_data;
+        let global_scale = ViewTransform::of(editor).scale();
+        let target = Vec2::new(ld.x + c.x as f32, ld.y + c.y as f32) * global_scale;
+        let zoom = editor.zoom_level;
+        move_view_to(editor, target - ctx.available_rect().center().to_vec2(), zoom);
+    }
+}
+
+/// Rooms in `levels` array order; drag a handle to move a room
+fn render_room_order_panel(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    let mut rows = Vec::new();
+    let mut released = false;
+    egui::SidePanel::right("room_order").resizable(true).default_width(180.0).show(ctx,|ui|{
+        ui.heading("Room Order");
+        ui.label("Drag ☰ to reorder");
+        egui::ScrollArea::vertical().show(ui,|ui|{
+            let mut checkpoint = 0;
+            for (i,room) in editor.cached_rooms.iter().enumerate() {
+                let row = ui.horizontal(|ui|{
+                    let handle = ui.add(egui::Label::new("☰").sense(egui::Sense::drag()));
+                    if handle.drag_started() { editor.room_order_drag = Some(i); }
+                    if handle.drag_released() { released = true; }
+                    let mut text = egui::RichText::new(&room.level_data.name);
+                    if editor.room_order_drag == Some(i) { text = text.strong(); }
+                    ui.label(text);
+                    if checkpoint_position(&room.json).is_some() {
+                        checkpoint += 1;
+                        ui.colored_label(CHECKPOINT_COLOR,format!("#{}",checkpoint));
+                    }
+                }).response.rect;
+                rows.push(row);
+            }
+        });
+    });
+    let Some(from) = editor.room_order_drag else { return };
+    let pointer_y = ctx.input().pointer.interact_pos().map(|p| p.y);
+    if let (Some(y), Some(first), Some(last)) = (pointer_y, rows.first(), rows.last()) {
+        let target = rows.iter().position(|r| y < r.bottom()).unwrap_or(rows.len() - 1);
+        let target = if y < first.top() { 0 } else if y > last.bottom() { rows.len() - 1 } else { target };
+        if !released {
+            // Show where the room will land
+            let line_y = if target > from { rows[target].bottom() } else { rows[target].top() };
+            ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground,egui::Id::new("room_order_drop")))
+                .hline(rows[target].x_range(),line_y,Stroke::new(2.0,SELECTION_COLOR));
+            return;
+        }
+        editor.room_order_drag = None;
+        move_room(editor,from,target);
+    } else if released {
+        editor.room_order_drag = None;
+    }
+}
+
+/// Legend of the fg tile ids in the current room with their tileset and flat colour
+fn render_tileset_legend(editor: &CelesteMapEditor, ctx: &egui::Context) {
+    let Some(room) = editor.cached_rooms.get(editor.current_level_index) else { return };
+    let mut ids: Vec<char> = room.level_data.solids.iter().flatten()
+        .copied()
+        .filter(|&c| is_solid_tile(c))
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.is_empty() { return; }
+    ensure_tileset_id_path_map_loaded_from_celeste(editor);
+    let path_map = tile_xml::TILESET_ID_PATH_MAP_FG.get();
+
+    egui::Area::new("tileset_legend")
+        .anchor(egui::Align2::RIGHT_TOP, [-10.0, 40.0])
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(egui::RichText::new(format!("Tilesets in {}", room.level_data.name)).strong());
+                for id in ids {
+                    let name = path_map
+                        .and_then(|m| tile_xml::get_tileset_path_for_id(m, id))
+                        .unwrap_or("unknown");
+                    ui.horizontal(|ui| {
+                        let (rect, _) = ui.allocate_exact_size(Vec2::splat(12.0), egui::Sense::hover());
+                        ui.painter().rect_filled(rect, 2.0, get_tile_color(id, SOLID_TILE_COLOR));
+                        ui.monospace(id.to_string());
+                        ui.label(name);
+                    });
+                }
+            });
+        });
 }
 
 fn render_top_panel(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
     egui::TopBottomPanel::top("top_panel").show(ctx,|ui|{
         ui.horizontal(|ui|{
             ui.menu_button("File",|ui|{
+                if ui.button("New Map...").clicked(){ editor.show_new_map_dialog=true;ui.close_menu(); }
                 if ui.button("Open...").clicked(){ editor.show_open_dialog=true;ui.close_menu(); }
+                if ui.button("New Tab").clicked(){ new_tab(editor);ui.close_menu(); }
+                if ui.button("Close Tab").clicked(){ request_close_tab(editor,editor.active_tab);ui.close_menu(); }
+                if ui.add_enabled(editor.bin_path.is_some(),egui::Button::new("Reload (F5)")).clicked(){ request_reload(editor);ui.close_menu(); }
                 if ui.button("Save").clicked(){ save_map(editor);ui.close_menu(); }
                 if ui.button("Save As...").clicked(){ save_map_as(editor);ui.close_menu(); }
+                if ui.add_enabled(editor.map_data.is_some(),egui::Button::new("Map Properties...")).clicked(){ editor.show_map_properties=true;ui.close_menu(); }
+                if ui.add_enabled(!editor.cached_rooms.is_empty(),egui::Button::new("Export Room Summary...")).clicked(){ ui.close_menu();export_room_summary(editor); }
+                if ui.add_enabled(editor.map_data.is_some(),egui::Button::new("Import Room from Image...")).clicked(){ ui.close_menu();pick_import_image(editor); }
+                ui.separator();
+                if editor.celeste_assets.celeste_dir.is_none() {
+                    ui.add_enabled(false,egui::Button::new("Test in Celeste")).on_disabled_hover_text("Set the Celeste path first (File > Set Celeste Path...)");
+                } else {
+                    ui.menu_button("Test in Celeste",|ui|{
+                        if ui.add_enabled(editor.map_data.is_some(),egui::Button::new("Save and Launch")).clicked(){ test_in_celeste(editor);ui.close_menu(); }
+                        let mut changed=ui.checkbox(&mut editor.launch_settings.console,"Everest Console (--console)").changed();
+                        ui.horizontal(|ui|{ ui.label("Test mod:");changed|=ui.text_edit_singleline(&mut editor.launch_settings.mod_name).changed(); });
+                        if changed { save_launch_settings(&editor.launch_settings); }
+                    });
+                }
                 ui.separator();
                 if ui.button("Set Celeste Path...").clicked(){ editor.show_celeste_path_dialog=true;ui.close_menu(); }
                 ui.separator();
                 if ui.button("Quit").clicked(){ std::process::exit(0); }
             });
+            ui.menu_button("Edit",|ui|{
+                if ui.add_enabled(editor.history.can_undo(),egui::Button::new("Undo (Ctrl+Z)")).clicked(){ undo(editor);ui.close_menu(); }
+                if ui.add_enabled(editor.history.can_redo(),egui::Button::new("Redo (Ctrl+Y)")).clicked(){ redo(editor);ui.close_menu(); }
+                if ui.button("History...").clicked(){ editor.show_history=true;ui.close_menu(); }
+                if ui.add_enabled(editor.saved_snapshot.is_some(),egui::Button::new("Review Changes...")).clicked(){ editor.show_review_changes=true;refresh_changes(editor);ui.close_menu(); }
+                if ui.add_enabled(editor.map_data.is_some(),egui::Button::new("Find... (Ctrl+F)")).clicked(){ open_find(editor);ui.close_menu(); }
+                ui.separator();
+                if ui.button("Room Properties...").clicked(){ editor.show_room_properties=true;ui.close_menu(); }
+                if ui.button("Entity Properties...").clicked(){ editor.show_entity_properties=true;ui.close_menu(); }
+                ui.separator();
+                if ui.add_enabled(editor.selection.is_some(),egui::Button::new("Copy Selection")).clicked(){ copy_selection(editor);ui.close_menu(); }
+                if ui.add_enabled(editor.selection.is_some(),egui::Button::new("Clear Selection")).clicked(){ editor.selection=None;ui.close_menu(); }
+                ui.separator();
+                if ui.button("Stamps...").clicked(){ editor.stamps=load_stamps();editor.show_stamps_dialog=true;ui.close_menu(); }
+            });
             ui.menu_button("View",|ui|{
                 let _prev=editor.show_fgdecals;
-                if ui.checkbox(&mut editor.show_fgdecals,"Show Fg Decals").changed(){ editor.static_dirty=true; }
-                if ui.checkbox(&mut editor.show_tiles,"Show Tiles").changed(){ editor.static_dirty=true; }
+                ui.checkbox(&mut editor.show_fgdecals,"Show Fg Decals");
+                ui.checkbox(&mut editor.layer_visibility[LAYER_ENTITIES],"Show Entities");
+                ui.checkbox(&mut editor.layer_visibility[LAYER_STYLEGROUNDS],"Show Stylegrounds");
+                if ui.button("Layers...").clicked(){ editor.show_layer_order=true;ui.close_menu(); }
+                ui.label("Tiles:");
+                ui.radio_value(&mut editor.tile_render_mode,TileRenderMode::Full,"Full");
+                ui.radio_value(&mut editor.tile_render_mode,TileRenderMode::Ghost,"Ghost");
+                ui.radio_value(&mut editor.tile_render_mode,TileRenderMode::Hidden,"Hidden");
+                ui.checkbox(&mut editor.collision_debug_mode,"Collision View").on_hover_text("Solid tiles in white, air and background tiles transparent");
+                ui.checkbox(&mut editor.show_room_list,"Room List");
+                ui.checkbox(&mut editor.show_checkpoints,"Checkpoint Order");
+                ui.checkbox(&mut editor.show_collectibles,"Collectibles");
+                ui.checkbox(&mut editor.show_camera_frames,"Camera Frames").on_hover_text("The 320x180 game camera at each spawn in the current room");
+                ui.add_enabled(editor.show_camera_frames,egui::Checkbox::new(&mut editor.tile_camera_frames,"Tile Room into Screens"));
                 ui.checkbox(&mut editor.show_all_rooms,"Show All Rooms");
+                ui.add_enabled(!editor.show_all_rooms,egui::Checkbox::new(&mut editor.show_neighbor_rooms,"Show Neighboring Rooms"));
                 ui.checkbox(&mut editor.show_grid,"Show Grid");
+                ui.checkbox(&mut editor.show_screen_grid,"Show Screen Grid").on_hover_text("A line every 20 tiles (160 px), to line rooms up with screen boundaries");
                 ui.checkbox(&mut editor.show_labels,"Show Labels");
+                ui.add_enabled_ui(editor.show_labels,|ui|{
+                    if ui.add(egui::Slider::new(&mut editor.view_settings.label_size,6.0..=48.0).text("Label Size")).changed(){ save_view_settings(&editor.view_settings); }
+                    if ui.checkbox(&mut editor.view_settings.center_labels,"Center Labels").changed(){ save_view_settings(&editor.view_settings); }
+                });
+                ui.menu_button("Room Outlines",|ui|{
+                    let mut changed=false;
+                    ui.horizontal(|ui|{ changed|=ui.color_edit_button_srgb(&mut editor.view_settings.selected_outline).changed(); ui.label("Current Room"); });
+                    ui.horizontal(|ui|{ changed|=ui.color_edit_button_srgb(&mut editor.view_settings.unselected_outline).changed(); ui.label("Other Rooms"); });
+                    for style in OutlineStyle::ALL { changed|=ui.radio_value(&mut editor.view_settings.selected_outline_style,style,style.label()).changed(); }
+                    if ui.button("Reset Colors").clicked(){
+                        editor.view_settings.selected_outline=default_selected_outline();
+                        editor.view_settings.unselected_outline=default_unselected_outline();
+                        changed=true;
+                    }
+                    if changed { save_view_settings(&editor.view_settings); }
+                });
+                ui.checkbox(&mut editor.show_heat_map,"Heat Map");
+                if ui.checkbox(&mut editor.show_change_overlay,"Highlight Unsaved Changes").changed(){ refresh_changes(editor); }
+                ui.checkbox(&mut editor.use_textures,"Use Textures");
                 ui.separator();
-                if ui.button("Zoom In").clicked(){ editor.zoom_level*=1.2;editor.static_dirty=true;ui.close_menu(); }
-                if ui.button("Zoom Out").clicked(){ editor.zoom_level=(editor.zoom_level/1.2).max(0.1);editor.static_dirty=true;ui.close_menu(); }
-                if ui.button("Reset Zoom").clicked(){ editor.zoom_level=1.0;editor.static_dirty=true;ui.close_menu(); }
+                let center=editor.canvas_rect.map_or(ctx.available_rect().center(),|r|r.center());
+                if ui.button("Zoom In").clicked(){ zoom_by(editor,1.2,center);ui.close_menu(); }
+                if ui.button("Zoom Out").clicked(){ zoom_by(editor,1.0/1.2,center);ui.close_menu(); }
+                if ui.button("Reset Zoom").clicked(){ zoom_to(editor,1.0,center);ui.close_menu(); }
+                if ui.button("Reset View").clicked(){ reset_view(editor,editor.canvas_rect.unwrap_or_else(||ctx.available_rect()));ui.close_menu(); }
+                if ui.checkbox(&mut editor.view_settings.animate_view,"Animate Zoom and Pan").changed(){ save_view_settings(&editor.view_settings); }
+                if ui.checkbox(&mut editor.view_settings.scroll_pans,"Scroll Pans (Ctrl+Scroll Zooms)").changed(){ save_view_settings(&editor.view_settings); }
+                if ui.checkbox(&mut editor.view_settings.autoscroll,"Autoscroll at Edges While Dragging").changed(){ save_view_settings(&editor.view_settings); }
+                ui.add_enabled_ui(editor.view_settings.autoscroll,|ui|{
+                    if ui.add(egui::Slider::new(&mut editor.view_settings.autoscroll_speed,100.0..=2000.0).text("Autoscroll Speed")).changed(){ save_view_settings(&editor.view_settings); }
+                });
+                if ui.add_enabled(!editor.cached_rooms.is_empty(),egui::Button::new("Go To Room...")).clicked(){ editor.go_to_room_input.clear();editor.show_go_to_room=true;ui.close_menu(); }
+                ui.menu_button("Bookmarks",|ui|{
+                    let key=editor.bin_path.clone().unwrap_or_default();
+                    let saved: Vec<(u8,String)>=editor.bookmarks.get(&key).into_iter().flatten()
+                        .map(|(slot,b)|(*slot,format!("{}: {} @ {:.0}%",slot,editor.level_names.get(b.room).map(String::as_str).unwrap_or("?"),b.zoom*100.0)))
+                        .collect();
+                    if saved.is_empty() { ui.label("Ctrl+1..9 saves the view, 1..9 jumps back"); }
+                    for (slot,label) in saved { if ui.button(label).clicked(){ go_to_bookmark(editor,slot);ui.close_menu(); } }
+                });
                 ui.separator();
+                if ui.button("Export Tileset PNG...").clicked(){ editor.show_export_tileset_dialog=true;ui.close_menu(); }
+                if ui.button("Sprite Browser...").clicked(){ editor.show_sprite_browser=true;ui.close_menu(); }
+                if ui.button("Map Report...").clicked(){ editor.show_map_report=true;ui.close_menu(); }
+                if ui.button("Validation...").clicked(){ editor.show_validation=true;ui.close_menu(); }
                 if ui.button("Key Bindings...").clicked(){ editor.show_key_bindings_dialog=true;ui.close_menu(); }
             });
+            #[cfg(debug_assertions)]
+            ui.menu_button("Debug",|ui|{
+                ui.checkbox(&mut editor.show_solids_debug,"Solids Character Grid").on_hover_text(format!("Shown from {}x zoom",SOLIDS_DEBUG_MIN_ZOOM));
+                ui.checkbox(&mut editor.show_memory_usage,"Atlas Memory Usage");
+            });
+            ui.separator();
+            ui.label("Snap:");
+            egui::ComboBox::from_id_source("entity_snap")
+                .selected_text(snap_label(editor.entity_snap))
+                .show_ui(ui,|ui|{
+                    for snap in SNAP_OPTIONS { ui.selectable_value(&mut editor.entity_snap,snap,snap_label(snap)); }
+                });
+            ui.label("Decals:");
+            egui::ComboBox::from_id_source("decal_snap_grid")
+                .selected_text(snap_label((editor.decal_snap_grid>0).then_some(editor.decal_snap_grid as u32)))
+                .show_ui(ui,|ui|{
+                    for grid in DECAL_SNAP_OPTIONS { ui.selectable_value(&mut editor.decal_snap_grid,grid,snap_label((grid>0).then_some(grid as u32))); }
+                });
+            if ui.selectable_label(editor.active_tool==EditorTool::Entities,"Entities").on_hover_text("Entity and decal tool").clicked(){
+                editor.active_tool=if editor.active_tool==EditorTool::Entities { EditorTool::Brush } else { EditorTool::Entities };
+            }
+            if ui.selectable_label(editor.active_tool==EditorTool::Triggers,"Triggers").on_hover_text("Drag to draw a trigger; drag a grip to resize one, double-click to edit it").clicked(){
+                editor.active_tool=if editor.active_tool==EditorTool::Triggers { EditorTool::Brush } else { EditorTool::Triggers };
+            }
             ui.separator();
             if !editor.show_all_rooms {
                 ui.label("Room:");
@@ -775,23 +1850,60 @@ fn render_top_panel(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
     });
 }
 
+/// One button per open map; the active one is highlighted
+fn render_tab_bar(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
+    let mut switch_to = None;
+    let mut close = None;
+    egui::TopBottomPanel::top("tab_bar").show(ctx,|ui|{
+        ui.horizontal(|ui|{
+            for i in 0..editor.open_maps.len() {
+                if ui.selectable_label(i==editor.active_tab,tab_title(editor,i)).clicked(){ switch_to=Some(i); }
+                if ui.small_button("×").on_hover_text("Close Tab").clicked(){ close=Some(i); }
+                ui.separator();
+            }
+            if ui.small_button("+").on_hover_text("New Tab").clicked(){ new_tab(editor); }
+        });
+    });
+    if let Some(i) = switch_to { switch_tab(editor,i); }
+    if let Some(i) = close { request_close_tab(editor,i); }
+}
+
 fn render_bottom_panel(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
     egui::TopBottomPanel::bottom("bottom_panel").show(ctx,|ui|{
         ui.horizontal(|ui|{
             if let Some(p)=editor.drag_start { ui.label(format!("Drag: ({:.1},{:.1})",p.x,p.y)); }
             ui.label(format!("Mouse: ({:.1},{:.1})",editor.mouse_pos.x,editor.mouse_pos.y));
             let (tx,ty)=editor.screen_to_map(editor.mouse_pos);
-            ui.label(format!("Tile: ({},{})",tx,ty));
-            if let Some(path)=&editor.bin_path { ui.with_layout(egui::Layout::right_to_left(egui::Align::Center),|ui|{ ui.label(format!("File: {}",path)); }); }
+            let local=hovered_room(editor,editor.mouse_pos)
+                .and_then(|i|map_model::levels(editor.map_data.as_ref()?)?.get(i))
+                .map(|level|level_local_tile(editor,level,editor.mouse_pos));
+            match local {
+                Some((lx,ly))=>{ ui.label(format!("Tile: ({},{}) → ({},{}) px ({},{})",tx,ty,lx,ly,TileCoord(lx).to_pixels().0,TileCoord(ly).to_pixels().0)); }
+                None=>{ ui.label(format!("Tile: ({},{})",tx,ty)); }
+            }
+            ui.separator();
+            ui.label(format!("Tool: {} [{}]",editor.active_tool.label(),editor.brush_tile));
+            if !editor.cached_rooms.is_empty() {
+                let berries=editor.collectibles.iter().filter(|c|c.kind==CollectibleKind::Strawberry).count();
+                ui.separator();
+                ui.label(format!("Berries: {}",berries));
+                ui.separator();
+                ui.label(format!("Visible rooms: {}/{}",visible_room_count(editor),editor.cached_rooms.len()));
+            }
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center),|ui|{
+                if let Some(v)=&editor.cairn_version { ui.label(format!("cairn v{}",v)); }
+                if let Some(t)=editor.bin_modified { ui.label(format!("Modified: {}",format_utc(t))); }
+                if let Some(path)=&editor.bin_path { ui.label(format!("File: {}",path)); }
+            });
         });
     });
 }
 
 fn render_central_panel(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
     egui::CentralPanel::default().show(ctx,|ui|{
-        if let Some(err)=&editor.error_message { ui.heading("Error");ui.label(err);return; }
         let (resp,painter)=ui.allocate_painter(ui.available_size(),egui::Sense::hover());
         editor.mouse_pos=resp.hover_pos().unwrap_or_default();
+        editor.canvas_rect=Some(resp.rect);
         painter.rect_filled(
                 resp.rect,
                 0.0,
@@ -802,48 +1914,150 @@ fn render_central_panel(editor: &mut CelesteMapEditor, ctx: &egui::Context) {
                 let size = TILE_SIZE * editor.zoom_level;
                 draw_grid(&painter, resp.rect, editor.camera_pos, size, editor.zoom_level);
             }
+            if editor.show_screen_grid {
+                draw_screen_grid(editor, &painter, resp.rect);
+            }
             let size=TILE_SIZE*editor.zoom_level;
         if editor.show_all_rooms { render_all_rooms(editor,&painter,size,&resp,ctx); }
         else { render_current_room(editor,&painter,size,resp.rect,ctx); }
+        if editor.active_tool==EditorTool::Entities { draw_snap_subgrid(editor,&painter); }
+        if editor.map_data.is_some() { render_resize_handles(editor,&painter); }
+        if cfg!(debug_assertions) && editor.show_solids_debug && editor.zoom_level>=SOLIDS_DEBUG_MIN_ZOOM { render_solids_debug(editor,&painter,resp.rect); }
+        if cfg!(debug_assertions) && editor.show_memory_usage { render_memory_usage(editor,&painter,resp.rect); }
+        if editor.show_change_overlay { render_change_overlay(editor,&painter,resp.rect); }
+        render_selection(editor,&painter);
+        if editor.active_tool==EditorTool::Entities { render_object_selection(editor,&painter); }
+        if editor.active_tool==EditorTool::Triggers { render_trigger_tool(editor,&painter); }
+        render_drag_indicator(editor,&painter);
+        if editor.show_checkpoints { render_checkpoint_order(editor,&painter); }
+        if editor.show_collectibles { render_collectible_markers(editor,&painter); }
+        if editor.show_camera_frames { render_camera_frames(editor,&painter); }
+        render_find_flash(editor,&painter,ctx);
+        if let Some(pointer)=resp.hover_pos() { room_label_tooltip(editor,&painter,ctx,pointer); }
+        if editor.map_data.is_none() { render_welcome_screen(editor,ui); }
     });
 }
 
-// Helper: get the ForegroundTiles.xml path for the current platform/editor
-fn get_celeste_fgtiles_xml_path_from_editor(editor: &CelesteMapEditor) -> String {
-    if let Some(ref celeste_dir) = editor.celeste_assets.celeste_dir {
-        #[cfg(target_os = "macos")]
-        {
-            let mut p = std::path::PathBuf::from(celeste_dir);
-            if !p.ends_with("Celeste.app") {
-                p = p.join("Celeste.app");
-            }
-            p.join("Contents/Resources/Content/Graphics/ForegroundTiles.xml").to_string_lossy().to_string()
-        }
-        #[cfg(not(target_os = "macos") )]
-        {
-            std::path::PathBuf::from(celeste_dir).join("Content/Graphics/ForegroundTiles.xml").to_string_lossy().to_string()
-        }
-    } else {
-        String::new()
+/// Quick actions shown over the empty canvas while no map is loaded
+fn render_welcome_screen(editor: &mut CelesteMapEditor, ui: &mut egui::Ui) {
+    let button_size = Vec2::new(220.0, 36.0);
+    let mut open_recent = None;
+    egui::Area::new("welcome_screen")
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ui.ctx(), |ui| {
+            egui::Frame::popup(ui.style()).inner_margin(20.0).show(ui, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.heading("Summit - Celeste Map Editor");
+                    ui.add_space(15.0);
+                    if ui.add_sized(button_size, egui::Button::new(egui::RichText::new("Open Map...").size(18.0))).clicked() {
+                        open_open_dialog(editor);
+                    }
+                    if ui.add_sized(button_size, egui::Button::new(egui::RichText::new("New Map").size(18.0))).clicked() {
+                        editor.show_new_map_dialog = true;
+                    }
+
+                    if !editor.recent_maps.is_empty() {
+                        ui.add_space(15.0);
+                        ui.label(egui::RichText::new("Recent maps").strong());
+                        for path in &editor.recent_maps {
+                            let name = std::path::Path::new(path).file_name().map_or(path.clone(), |n| n.to_string_lossy().to_string());
+                            if ui.link(name).on_hover_text(path).clicked() {
+                                open_recent = Some(path.clone());
+                            }
+                        }
+                    }
+
+                    ui.add_space(15.0);
+                    ui.separator();
+                    match &editor.celeste_assets.celeste_dir {
+                        Some(dir) => { ui.label(format!("Celeste: {}", dir.display())); }
+                        None => {
+                            ui.colored_label(egui::Color32::from_rgb(220, 160, 60), "Celeste path not configured; textures are unavailable");
+                            if ui.button("Set Celeste Path...").clicked() { editor.show_celeste_path_dialog = true; }
+                        }
+                    }
+                });
+            });
+        });
+    if let Some(path) = open_recent {
+        load_map(editor, &path);
     }
 }
 
-// Helper: get the BackgroundTiles.xml path for the current platform/editor
-fn get_celeste_bgtiles_xml_path_from_editor(editor: &CelesteMapEditor) -> String {
-    if let Some(ref celeste_dir) = editor.celeste_assets.celeste_dir {
-        #[cfg(target_os = "macos")]
-        {
-            let mut p = std::path::PathBuf::from(celeste_dir);
-            if !p.ends_with("Celeste.app") {
-                p = p.join("Celeste.app");
+/// Tint the solids cells changed since the last load or save, in the rooms being shown
+fn render_change_overlay(editor: &CelesteMapEditor, painter: &egui::Painter, view: Rect) {
+    let transform = ViewTransform::of(editor);
+    let tile = Vec2::splat(transform.tile_size());
+    for change in &editor.map_changes {
+        let Some(idx) = change.room_idx else { continue };
+        if !editor.show_all_rooms && idx != editor.current_level_index { continue; }
+        let Some(room) = editor.cached_rooms.get(idx) else { continue };
+        let grid = RoomGrid::of_render_data(&room.level_data);
+        for &(x, y) in &change.changed_tiles {
+            let rect = Rect::from_min_size(transform.room_tile_to_screen(grid, (x as i32, y as i32)), tile);
+            if rect.intersects(view) {
+                painter.rect_filled(rect, 0.0, CHANGE_OVERLAY_COLOR);
             }
-            p.join("Contents/Resources/Content/Graphics/BackgroundTiles.xml").to_string_lossy().to_string()
-        }
-        #[cfg(not(target_os = "macos") )]
-        {
-            std::path::PathBuf::from(celeste_dir).join("Content/Graphics/BackgroundTiles.xml").to_string_lossy().to_string()
         }
-    } else {
-        String::new()
     }
+}
+
+/// Outline the current tile selection, if any
+fn render_selection(editor: &CelesteMapEditor, painter: &egui::Painter) {
+    let Some(sel) = &editor.selection else { return };
+    if !editor.show_all_rooms && sel.room != editor.current_level_index { return; }
+    let Some(room) = editor.cached_rooms.get(sel.room) else { return };
+    let ld = &room.level_data;
+    let (min_x, min_y, max_x, max_y) = sel.bounds();
+    let tile_size = TILE_SIZE * editor.zoom_level;
+    let origin = ViewTransform::of(editor).room_tile_to_screen(RoomGrid::of_render_data(ld), (0, 0));
+    let rect = Rect::from_min_max(
+        origin + Vec2::new(min_x as f32, min_y as f32) * tile_size,
+        origin + Vec2::new((max_x + 1) as f32, (max_y + 1) as f32) * tile_size,
+    );
+    painter.rect_filled(rect, 0.0, SELECTION_COLOR.linear_multiply(0.15));
+    painter.rect_stroke(rect, 0.0, Stroke::new(2.0, SELECTION_COLOR));
+}
+
+/// Outline each selected object and the group's bounding box, plus the rubber band while dragging
+fn render_object_selection(editor: &CelesteMapEditor, painter: &egui::Painter) {
+    if let Some((start, end)) = editor.selection_band {
+        let band = Rect::from_two_pos(start, end);
+        painter.rect_filled(band, 0.0, SELECTION_COLOR.linear_multiply(0.1));
+        painter.rect_stroke(band, 0.0, Stroke::new(1.0, SELECTION_COLOR));
+    }
+    let rects = selection_rects(editor);
+    for rect in &rects {
+        painter.rect_stroke(*rect, 0.0, Stroke::new(1.0, SELECTION_COLOR));
+    }
+    if rects.len() > 1 {
+        let group = rects.iter().skip(1).fold(rects[0], |acc, r| acc.union(*r));
+        painter.rect_stroke(group.expand(3.0), 0.0, Stroke::new(2.0, SELECTION_COLOR));
+    }
+}
+
+/// The rectangle being drawn or waiting for a name, and the grips of the current room's triggers
+fn render_trigger_tool(editor: &CelesteMapEditor, painter: &egui::Painter) {
+    let pending = editor.trigger_draft.as_ref().map(|d| (d.room_idx, d.bounds()))
+        .or_else(|| editor.new_trigger.as_ref().map(|t| (t.room_idx, t.bounds)));
+    if let Some(rect) = pending.and_then(|(room_idx, bounds)| bounds_rect(editor, room_idx, bounds)) {
+        painter.rect_filled(rect, 0.0, SELECTION_COLOR.linear_multiply(0.1));
+        painter.rect_stroke(rect, 0.0, Stroke::new(1.0, SELECTION_COLOR));
+    }
+    let dragged = editor.trigger_resize.as_ref().map(|d| (d.trigger_id, d.handle));
+    for (id, handle, rect) in trigger_handle_rects(editor) {
+        let fill = if dragged == Some((id, handle)) { SELECTION_COLOR } else { TRIGGER_COLOR };
+        painter.rect(rect.shrink(1.0), 0.0, fill, Stroke::new(1.0, Color32::BLACK));
+    }
+}
+
+/// Mark where the current pan started and link it to the cursor
+fn render_drag_indicator(editor: &CelesteMapEditor, painter: &egui::Painter) {
+    let Some(start) = editor.drag_start else { return };
+    let stroke = Stroke::new(1.0, DRAG_INDICATOR_COLOR);
+    let arm = 6.0;
+    painter.line_segment([start - Vec2::new(arm, 0.0), start + Vec2::new(arm, 0.0)], stroke);
+    painter.line_segment([start - Vec2::new(0.0, arm), start + Vec2::new(0.0, arm)], stroke);
+    painter.circle_filled(start, 2.0, DRAG_INDICATOR_COLOR);
+    painter.extend(egui::Shape::dashed_line(&[start, editor.mouse_pos], stroke, 6.0, 4.0));
 }
\ No newline at end of file
@@ -3,28 +3,82 @@
 #[derive(Clone, Default)]
 pub struct TileNeighbors(pub u8);
 
+// Bit of each direction in the mask
+pub const N: u8 = 1 << 0;
+pub const NE: u8 = 1 << 1;
+pub const E: u8 = 1 << 2;
+pub const SE: u8 = 1 << 3;
+pub const S: u8 = 1 << 4;
+pub const SW: u8 = 1 << 5;
+pub const W: u8 = 1 << 6;
+pub const NW: u8 = 1 << 7;
+
+// (dx, dy) offsets in bit order; y grows downwards, so north is dy = -1
+const DIRECTIONS: [(isize, isize); 8] = [
+    (0, -1),  // N
+    (1, -1),  // NE
+    (1, 0),   // E
+    (1, 1),   // SE
+    (0, 1),   // S
+    (-1, 1),  // SW
+    (-1, 0),  // W
+    (-1, -1), // NW
+];
+
 impl TileNeighbors {
     pub fn is_internal(&self) -> bool {
-        self.0 == 0b1111_1111
+        self.0 == N | NE | E | SE | S | SW | W | NW
     }
+
+    pub fn has(&self, direction: u8) -> bool {
+        self.0 & direction != 0
+    }
+
     pub fn from_grid<T, F: Fn(T) -> bool>(grid: &Vec<Vec<T>>, x: usize, y: usize, is_filled: F) -> Self
     where T: Copy {
         let mut mask = 0u8;
-        let dirs = [(-1,0),( -1,1), (0,1), (1,1), (1,0), (1,-1), (0,-1), (-1,-1)];
-        let h = grid.len() as isize;
-        let _w = if h > 0 { grid[0].len() as isize } else { 0 };
-        for (i, (dy, dx)) in dirs.iter().enumerate() {
-            let ny = y as isize + dy;
+        for (i, (dx, dy)) in DIRECTIONS.iter().enumerate() {
             let nx = x as isize + dx;
-            if ny >= 0 && ny < h {
-                let row = &grid[ny as usize];
-                if nx >= 0 && (nx as usize) < row.len() {
-                    if is_filled(row[nx as usize]) {
-                        mask |= 1 << i;
-                    }
-                }
+            let ny = y as isize + dy;
+            if ny < 0 || nx < 0 { continue; }
+            let filled = grid.get(ny as usize)
+                .and_then(|row| row.get(nx as usize))
+                .map_or(false, |&t| is_filled(t));
+            if filled {
+                mask |= 1 << i;
             }
         }
         TileNeighbors(mask)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &[&str]) -> Vec<Vec<char>> {
+        rows.iter().map(|r| r.chars().collect()).collect()
+    }
+
+    #[test]
+    fn fully_surrounded_cell_is_internal() {
+        let solid = grid(&["111", "111", "111"]);
+        assert!(TileNeighbors::from_grid(&solid, 1, 1, |c| c != '0').is_internal());
+    }
+
+    #[test]
+    fn one_missing_neighbor_is_not_internal() {
+        let notched = grid(&["110", "111", "111"]);
+        let neighbors = TileNeighbors::from_grid(&notched, 1, 1, |c| c != '0');
+        assert!(!neighbors.is_internal());
+        assert!(!neighbors.has(NE));
+        assert!(neighbors.has(N) && neighbors.has(SW));
+    }
+
+    #[test]
+    fn cells_past_the_grid_edge_count_as_air() {
+        let solid = grid(&["11", "11"]);
+        let corner = TileNeighbors::from_grid(&solid, 0, 0, |c| c != '0');
+        assert_eq!(corner.0, E | SE | S);
+    }
+}
@@ -0,0 +1,147 @@
+use std::ops::Range;
+
+use eframe::egui::{Pos2, Rect, Vec2};
+use serde_json::Value;
+
+use crate::app::CelesteMapEditor;
+use crate::data::map_model::PIXELS_PER_TILE;
+use crate::map::editor::get_solids_offset;
+use crate::ui::render::{LevelRenderData, TILE_SIZE};
+
+const TILE_PIXELS: f32 = PIXELS_PER_TILE as f32;
+/// Zoom range every zoom path is clamped to. Past 16x a tile is wider than most screens.
+pub const MIN_ZOOM: f32 = 0.1;
+pub const MAX_ZOOM: f32 = 16.0;
+
+/// Where a room's tile grid starts, in world pixels (Celeste pixels on the map): the room
+/// position plus its solids offset.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RoomGrid {
+    pub origin: Pos2,
+}
+
+impl RoomGrid {
+    /// Grid of a room at (`x`, `y`) whose solids are shifted by `offset` pixels.
+    pub fn new(x: f32, y: f32, offset: (i32, i32)) -> Self {
+        Self { origin: Pos2::new(x + offset.0 as f32, y + offset.1 as f32) }
+    }
+
+    /// Grid of a level node of the map.
+    pub fn of_level(level: &Value) -> Self {
+        let x = level["x"].as_f64().unwrap_or(0.0) as f32;
+        let y = level["y"].as_f64().unwrap_or(0.0) as f32;
+        Self::new(x, y, get_solids_offset(level))
+    }
+
+    /// Grid of a cached room.
+    pub fn of_render_data(ld: &LevelRenderData) -> Self {
+        Self::new(ld.x, ld.y, (ld.offset_x, ld.offset_y))
+    }
+}
+
+/// The camera: how world pixels map to screen points. Every screen, world and tile conversion
+/// goes through here so placement and drawing agree.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewTransform {
+    /// Screen offset of the world origin, in points at the current zoom.
+    pub camera_pos: Vec2,
+    pub zoom: f32,
+}
+
+impl ViewTransform {
+    pub fn new(camera_pos: Vec2, zoom: f32) -> Self {
+        Self { camera_pos, zoom }
+    }
+
+    /// The editor's current view.
+    pub fn of(editor: &CelesteMapEditor) -> Self {
+        Self::new(editor.camera_pos, editor.zoom_level)
+    }
+
+    /// This view at `zoom` (clamped to MIN_ZOOM..=MAX_ZOOM), with the camera moved so the world
+    /// point under the screen point `anchor` stays under it. A zoom that isn't a number is ignored.
+    pub fn zoomed(&self, zoom: f32, anchor: Pos2) -> Self {
+        if zoom.is_nan() { return *self; }
+        let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        let world = self.screen_to_world(anchor);
+        let camera_pos = world.to_vec2() * (self.scale() / self.zoom * zoom) - anchor.to_vec2();
+        Self::new(camera_pos, zoom)
+    }
+
+    /// Screen points per world pixel.
+    pub fn scale(&self) -> f32 {
+        TILE_SIZE / TILE_PIXELS * self.zoom
+    }
+
+    /// Screen points per tile.
+    pub fn tile_size(&self) -> f32 {
+        TILE_SIZE * self.zoom
+    }
+
+    pub fn world_to_screen(&self, world: Pos2) -> Pos2 {
+        (world.to_vec2() * self.scale() - self.camera_pos).to_pos2()
+    }
+
+    pub fn screen_to_world(&self, screen: Pos2) -> Pos2 {
+        ((screen.to_vec2() + self.camera_pos) / self.scale()).to_pos2()
+    }
+
+    pub fn world_rect_to_screen(&self, world: Rect) -> Rect {
+        Rect::from_min_max(self.world_to_screen(world.min), self.world_to_screen(world.max))
+    }
+
+    /// Screen rectangle of a cached room.
+    pub fn room_rect(&self, ld: &LevelRenderData) -> Rect {
+        self.world_rect_to_screen(Rect::from_min_size(Pos2::new(ld.x, ld.y), Vec2::new(ld.width, ld.height)))
+    }
+
+    /// Tile of the map-wide grid, aligned on world pixel 0, under a screen position.
+    pub fn screen_to_world_tile(&self, screen: Pos2) -> (i32, i32) {
+        let world = self.screen_to_world(screen);
+        ((world.x / TILE_PIXELS).floor() as i32, (world.y / TILE_PIXELS).floor() as i32)
+    }
+
+    /// Position under a screen point relative to a cached room's top-left corner, in Celeste
+    /// pixels as entity positions are stored.
+    pub fn screen_to_room_pixels(&self, ld: &LevelRenderData, screen: Pos2) -> (f64, f64) {
+        let world = self.screen_to_world(screen);
+        ((world.x - ld.x) as f64, (world.y - ld.y) as f64)
+    }
+
+    /// Tile of `room`'s grid holding a world position. Not bounds-checked against the room size.
+    pub fn world_to_room_tile(room: RoomGrid, world: Pos2) -> (i32, i32) {
+        let local = (world - room.origin) / TILE_PIXELS;
+        (local.x.floor() as i32, local.y.floor() as i32)
+    }
+
+    /// World position of the top-left corner of a tile of `room`'s grid.
+    pub fn room_tile_to_world(room: RoomGrid, tile: (i32, i32)) -> Pos2 {
+        room.origin + Vec2::new(tile.0 as f32, tile.1 as f32) * TILE_PIXELS
+    }
+
+    /// Tile of `room`'s grid under a screen position.
+    pub fn screen_to_room_tile(&self, room: RoomGrid, screen: Pos2) -> (i32, i32) {
+        Self::world_to_room_tile(room, self.screen_to_world(screen))
+    }
+
+    /// Screen position of the top-left corner of a tile of `room`'s grid.
+    pub fn room_tile_to_screen(&self, room: RoomGrid, tile: (i32, i32)) -> Pos2 {
+        self.world_to_screen(Self::room_tile_to_world(room, tile))
+    }
+
+    /// Columns and rows of a `cols` x `rows` tile grid of `room` that overlap the screen rectangle
+    /// `rect`, clamped to the grid. Empty when the rectangle misses the grid, or when it or the
+    /// camera isn't finite, so a broken camera can't turn into a loop over billions of tiles.
+    pub fn visible_room_tiles(&self, room: RoomGrid, cols: usize, rows: usize, rect: Rect) -> (Range<usize>, Range<usize>) {
+        let camera_finite = self.camera_pos.is_finite() && self.zoom.is_finite() && self.zoom > 0.0;
+        if !camera_finite || !rect.is_finite() || !rect.is_positive() { return (0..0, 0..0); }
+        let (start_x, start_y) = self.screen_to_room_tile(room, rect.min);
+        let (end_x, end_y) = self.screen_to_room_tile(room, rect.max);
+        let span = |start: i32, end: i32, len: usize| {
+            // i64 so the exclusive end of a saturated i32::MAX tile doesn't overflow
+            let end = (end as i64 + 1).clamp(0, len as i64) as usize;
+            (start as i64).clamp(0, end as i64) as usize..end
+        };
+        (span(start_x, end_x, cols), span(start_y, end_y, rows))
+    }
+}
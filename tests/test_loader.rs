@@ -0,0 +1,116 @@
+//! Load and save through the library API, with no editor involved.
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use serde_json::Value;
+use summit::data::map_model::{self, LevelNode};
+use summit::data::mods::{extract_zipped_map, mod_of_map, scan_mods};
+use summit::map::loader::{blank_map_json, NEW_ROOM_HEIGHT, NEW_ROOM_NAME, NEW_ROOM_WIDTH};
+use summit::{load_map_file, save_map_file, LoadError};
+
+/// A fresh directory under the system temp dir; each test gets its own so they can run in parallel.
+fn scratch_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("summit_test_loader_{}_{}", test, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// Solids of the fixture room before the edit: two rows of air
+const FIXTURE_SOLIDS: &str = "000\n000";
+
+/// `map` with the solids of its first room replaced by `text`.
+fn with_solids(map: &Value, text: &str) -> Value {
+    let mut map = map.clone();
+    let levels = map_model::levels_mut(&mut map).unwrap();
+    let mut level = LevelNode::from_value(&levels[0]).unwrap();
+    assert!(level.set_tiles("solids", text));
+    levels[0] = level.to_value();
+    map
+}
+
+/// Write the one-room map the New Map dialog creates as a .bin, to load back in the tests.
+fn fixture_bin(test: &str) -> PathBuf {
+    let path = scratch_dir(test).join(format!("{}.bin", test));
+    let map = blank_map_json("loader_test", NEW_ROOM_NAME, NEW_ROOM_WIDTH, NEW_ROOM_HEIGHT);
+    save_map_file(&with_solids(&map, FIXTURE_SOLIDS), &path).unwrap();
+    path
+}
+
+fn solids(map: &Value) -> String {
+    let level = LevelNode::from_value(&map_model::levels(map).unwrap()[0]).unwrap();
+    level.solids().unwrap().inner_text.clone()
+}
+
+#[test]
+fn load_reads_the_saved_map() {
+    let bin = fixture_bin("load");
+    let loaded = load_map_file(&bin).unwrap();
+    assert_eq!(loaded.data["package"], "loader_test");
+    let levels = map_model::levels(&loaded.data).unwrap();
+    assert_eq!(levels.len(), 1);
+    assert_eq!(levels[0]["name"], NEW_ROOM_NAME);
+    assert!(loaded.modified.is_some());
+}
+
+#[test]
+fn edited_tile_survives_save_and_reload() {
+    let bin = fixture_bin("round_trip");
+    let original = load_map_file(&bin).unwrap().data;
+    assert_eq!(solids(&original), FIXTURE_SOLIDS);
+
+    // Put one tile at (2, 1) of the first room
+    save_map_file(&with_solids(&original, "000\n003"), &bin).unwrap();
+    let reloaded = load_map_file(&bin).unwrap().data;
+    assert_eq!(solids(&reloaded), "000\n003");
+
+    // Nothing but that tile changed
+    assert_eq!(with_solids(&reloaded, FIXTURE_SOLIDS), with_solids(&original, FIXTURE_SOLIDS));
+}
+
+#[test]
+fn missing_file_is_a_cairn_error() {
+    let path = scratch_dir("missing").join("does_not_exist.bin");
+    assert!(matches!(load_map_file(&path), Err(LoadError::Cairn(_))));
+}
+
+#[test]
+fn scan_mods_lists_bin_maps_by_campaign() {
+    let mods = scratch_dir("scan_mods");
+    let maps = mods.join("MyMod").join("Maps");
+    std::fs::create_dir_all(maps.join("me").join("campaign")).unwrap();
+    std::fs::write(maps.join("me").join("campaign").join("2-b.bin"), b"").unwrap();
+    std::fs::write(maps.join("me").join("campaign").join("1-a.bin"), b"").unwrap();
+    std::fs::write(maps.join("loose.bin"), b"").unwrap();
+    std::fs::write(maps.join("notes.txt"), b"").unwrap();
+    std::fs::create_dir_all(mods.join("NoMaps")).unwrap();
+    // Not a zip at all; skipped rather than failing the scan
+    std::fs::write(mods.join("Broken.zip"), b"").unwrap();
+
+    let projects = scan_mods(&mods);
+    assert_eq!(projects.len(), 1);
+    let names: Vec<(&str, &str)> = projects[0].maps.iter().map(|m| (m.campaign.as_str(), m.name.as_str())).collect();
+    assert_eq!(names, [("", "loose"), ("me/campaign", "1-a"), ("me/campaign", "2-b")]);
+    assert_eq!(projects[0].campaigns().len(), 2);
+    assert_eq!(mod_of_map(&mods, &projects[0].maps[1].path).as_deref(), Some("MyMod"));
+    assert_eq!(mod_of_map(&mods, &mods.join("MyMod").join("notes.bin")), None);
+}
+
+#[test]
+fn zipped_mod_maps_are_listed_and_extracted() {
+    let mods = scratch_dir("zipped_mods");
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(mods.join("Packed.zip")).unwrap());
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    zip.start_file("Maps/me/campaign/1-a.bin", options).unwrap();
+    zip.write_all(b"map bytes").unwrap();
+    zip.start_file("Graphics/Atlases/Gameplay/x.png", options).unwrap();
+    zip.finish().unwrap();
+
+    let projects = scan_mods(&mods);
+    assert_eq!(projects.len(), 1);
+    assert!(projects[0].zipped);
+    assert_eq!(projects[0].name, "Packed");
+    let map = &projects[0].maps[0];
+    assert_eq!((map.campaign.as_str(), map.name.as_str()), ("me/campaign", "1-a"));
+    assert_eq!(std::fs::read(extract_zipped_map(map).unwrap()).unwrap(), b"map bytes");
+}
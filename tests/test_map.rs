@@ -0,0 +1,266 @@
+//! Map regression tests over synthetic maps, using a fake tileset XML so no game assets are needed.
+
+use serde_json::{json, Value};
+use summit::app::CelesteMapEditor;
+use summit::data::map_model::{self, Entity};
+use summit::data::tile_xml::load_tileset_id_path_map;
+use summit::map::changes::{changed_cells, diff_map, snapshot_map, RoomStatus};
+use summit::map::history::undo;
+use summit::map::image_import::{apply_image_import, image_to_solids, ImageImport};
+use summit::ui::render::LevelRenderData;
+
+const FIXTURE_XML: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/ForegroundTiles.xml");
+// A map whose first child is a `meta` node, so `levels` is not `__children[0]`
+const META_FIRST_MAP: &str = include_str!("fixtures/meta_first_map.json");
+
+// A shape with edges, corners, a one-tile pillar and a solid interior, drawn with `#`
+const ROOM_SHAPE: [&str; 7] = [
+    "##########",
+    "##########",
+    "###....###",
+    "##......#.",
+    "##..#...#.",
+    "##..#.....",
+    "##########",
+];
+
+/// A map with one room per tile id in the fixture, each filled with `ROOM_SHAPE`.
+fn generate_test_map() -> Value {
+    let mut ids: Vec<char> = load_tileset_id_path_map(FIXTURE_XML).into_keys().collect();
+    ids.sort_unstable();
+    let rooms: Vec<Value> = ids.iter().enumerate().map(|(i, &id)| {
+        let solids: Vec<String> = ROOM_SHAPE.iter()
+            .map(|row| row.chars().map(|c| if c == '#' { id } else { '0' }).collect())
+            .collect();
+        json!({
+            "__name": "level",
+            "name": format!("tile_{}", id),
+            "x": i as i64 * 128,
+            "y": 0,
+            "width": ROOM_SHAPE[0].len() * 8,
+            "height": ROOM_SHAPE.len() * 8,
+            "__children": [
+                { "__name": "solids", "innerText": solids.join("\n") },
+                { "__name": "bg", "innerText": "" }
+            ]
+        })
+    }).collect();
+    json!({
+        "__name": "Map",
+        "package": "autotile_test",
+        "__children": [
+            { "__name": "levels", "__children": rooms }
+        ]
+    })
+}
+
+#[test]
+fn every_solid_tile_gets_an_autotile_coordinate() {
+    let map = generate_test_map();
+    let levels = map_model::levels(&map).unwrap();
+    // Every id in the fixture, template included
+    assert_eq!(levels.len(), 6);
+
+    for level in levels {
+        let text = level["__children"][0]["innerText"].as_str().unwrap();
+        let mut ld = LevelRenderData {
+            name: level["name"].as_str().unwrap().to_string(),
+            solids: text.split('\n').map(|r| r.chars().collect()).collect(),
+            ..Default::default()
+        };
+        ld.compute_autotile_coords(FIXTURE_XML);
+
+        for (y, row) in ld.solids.iter().enumerate() {
+            for (x, &tile) in row.iter().enumerate() {
+                if tile == '0' { continue; }
+                assert!(
+                    ld.autotile_coords[y][x].is_some(),
+                    "{}: tile '{}' at ({}, {}) has no autotile coordinate", ld.name, tile, x, y
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn levels_are_found_after_a_meta_child() {
+    let map: Value = serde_json::from_str(META_FIRST_MAP).unwrap();
+    let levels = map_model::levels(&map).unwrap();
+    assert_eq!(levels.len(), 2);
+    assert_eq!(levels[1]["name"], "a-01");
+    assert_eq!(map_model::parse_map(&map).unwrap().levels.len(), 2);
+}
+
+#[test]
+fn solids_are_read_and_written_in_the_levels_child() {
+    let map: Value = serde_json::from_str(META_FIRST_MAP).unwrap();
+    let mut editor = CelesteMapEditor::default();
+    editor.map_data = Some(map);
+    editor.current_level_index = 1;
+    assert_eq!(editor.get_solids_data().as_deref(), Some("303\n333"));
+    assert_eq!(editor.get_current_level().unwrap().name, "a-01");
+
+    editor.update_solids_data("333\n333");
+    let map = editor.map_data.as_ref().unwrap();
+    assert_eq!(map["__children"][0], json!({ "__name": "meta", "Icon": "areas/null" }));
+    let levels = map_model::levels(map).unwrap();
+    assert_eq!(levels[0]["__children"][0]["innerText"], "111\n111");
+    assert_eq!(levels[1]["__children"][0]["innerText"], "333\n333");
+    assert_eq!(editor.cached_rooms.len(), 2);
+}
+
+#[test]
+fn an_unreadable_level_keeps_its_place() {
+    let mut map: Value = serde_json::from_str(META_FIRST_MAP).unwrap();
+    map_model::levels_mut(&mut map).unwrap()[0]["name"] = json!(5);
+    let data = map_model::parse_map(&map).unwrap();
+    assert_eq!(data.levels.len(), 1);
+    assert_eq!(data.level_names(), ["5", "a-01"]);
+    assert_eq!(map_model::write_map(&data), map);
+    // Rooms are counted with the unreadable one, as the editor counts them
+    let mut added = data.clone();
+    assert_eq!(added.add_entity(0, "triggers", Entity::new("cameraOffsetTrigger", 0.0, 0.0)), None);
+    assert!(added.add_entity(1, "triggers", Entity::new("cameraOffsetTrigger", 0.0, 0.0)).is_some());
+    assert_eq!(added.level(1).unwrap().max_entity_id(), Some(added.next_entity_id() as i64 - 1));
+
+    let mut editor = CelesteMapEditor::default();
+    editor.map_data = Some(map);
+    editor.extract_level_names();
+    assert_eq!(editor.level_names, ["5", "a-01"]);
+    editor.update_solids_data("333\n333");
+    assert_eq!(map_model::levels(editor.map_data.as_ref().unwrap()).unwrap()[0]["__children"][0]["innerText"], "333\n333");
+}
+
+#[test]
+fn a_level_without_a_position_keeps_its_cached_slot() {
+    let mut map: Value = serde_json::from_str(META_FIRST_MAP).unwrap();
+    map_model::levels_mut(&mut map).unwrap()[0]["x"] = Value::Null;
+    let mut editor = CelesteMapEditor::default();
+    editor.map_data = Some(map);
+    editor.extract_level_names();
+    editor.cache_rooms();
+    // Room i is level i everywhere, drawn or not
+    assert_eq!(editor.cached_rooms.len(), editor.level_names.len());
+    assert_eq!(editor.cached_rooms[1].level_data.name, editor.level_names[1]);
+    assert_eq!(editor.cached_rooms[0].level_data.width, 0.0);
+}
+
+#[test]
+fn added_entities_get_ids_free_across_rooms() {
+    let mut map: Value = serde_json::from_str(META_FIRST_MAP).unwrap();
+    map_model::levels_mut(&mut map).unwrap()[1]["__children"][2]["__children"] = json!([{ "__name": "spring", "id": 7, "x": 0, "y": 0 }]);
+    assert_eq!(map_model::next_entity_id(&map), 8);
+    let mut data = map_model::parse_map(&map).unwrap();
+
+    let berry = Entity::new("strawberry", 16.0, 4.5).with_attribute("winged", true).with_attribute("order", 2i64);
+    assert_eq!(data.add_entity(0, "entities", berry), Some(8));
+    assert_eq!(data.add_entity(0, "entities", Entity::new("player", 8.0, 8.0)), Some(9));
+    assert_eq!(data.add_entity(5, "entities", Entity::new("player", 0.0, 0.0)), None);
+
+    let written = map_model::write_map(&data);
+    let entities = &map_model::levels(&written).unwrap()[0]["__children"][2]["__children"];
+    assert_eq!(entities[0], json!({ "__name": "strawberry", "id": 8, "x": 16, "y": 4.5, "winged": true, "order": 2 }));
+    assert_eq!(entities[1]["id"], 9);
+}
+
+/// A level node with the given solids, entity ids and decal textures.
+fn change_room(name: &str, x: i64, solids: &str, ids: &[i64], decals: &[&str]) -> Value {
+    let entities: Vec<Value> = ids.iter().map(|id| json!({ "__name": "spring", "id": id, "x": 0, "y": 0 })).collect();
+    let decals: Vec<Value> = decals.iter().map(|t| json!({ "texture": t, "x": 8, "y": 8 })).collect();
+    json!({
+        "__name": "level", "name": name, "x": x, "y": 0,
+        "__children": [
+            { "__name": "entities", "__children": entities },
+            { "__name": "fgdecals", "__children": decals },
+            { "__name": "solids", "innerText": solids }
+        ]
+    })
+}
+
+fn change_map(rooms: Vec<Value>) -> Value {
+    json!({ "__name": "Map", "__children": [{ "__name": "levels", "__children": rooms }] })
+}
+
+#[test]
+fn changed_cells_treat_missing_cells_as_air() {
+    assert_eq!(changed_cells("00\n01", "00\n01"), vec![]);
+    assert_eq!(changed_cells("01\n1", "00\n10"), vec![(1, 0)]);
+    assert_eq!(changed_cells("", "0\n003"), vec![(2, 1)]);
+    assert_eq!(changed_cells("11", ""), vec![(0, 0), (1, 0)]);
+}
+
+#[test]
+fn review_changes_reports_added_removed_renamed_and_edited_rooms() {
+    let saved = change_map(vec![
+        change_room("a-00", 0, "000\n000", &[1, 2], &["grass"]),
+        change_room("a-01", 320, "111", &[3], &[]),
+        change_room("a-02", 640, "1", &[], &[]),
+        change_room("gone", 960, "1", &[4], &[]),
+    ]);
+    let snapshot = snapshot_map(&saved);
+    assert!(diff_map(&snapshot, &saved).is_empty());
+
+    let edited = change_map(vec![
+        change_room("a-00", 0, "000\n010", &[2, 5], &["grass", "rock"]),
+        change_room("a-01", 320, "111", &[3], &[]),
+        change_room("b-02", 640, "1", &[], &[]),
+        change_room("new", 1280, "11", &[6], &[]),
+    ]);
+    let changes = diff_map(&snapshot, &edited);
+    let summary: Vec<(Option<usize>, &str, &RoomStatus)> = changes.iter().map(|c| (c.room_idx, c.name.as_str(), &c.status)).collect();
+    assert_eq!(summary, vec![
+        (Some(0), "a-00", &RoomStatus::Edited),
+        (Some(2), "b-02", &RoomStatus::Renamed { from: "a-02".to_string() }),
+        (Some(3), "new", &RoomStatus::Added),
+        (None, "gone", &RoomStatus::Removed),
+    ]);
+    let a00 = &changes[0];
+    assert_eq!(a00.changed_tiles, vec![(1, 1)]);
+    assert_eq!((a00.entities_added, a00.entities_removed, a00.decals_added, a00.decals_removed), (1, 1, 1, 0));
+    assert_eq!(changes[2].changed_tiles.len(), 2);
+    assert_eq!(changes[3].entities_removed, 1);
+}
+
+/// A 4x2 image: black on the left, white on the right, with one transparent black pixel.
+fn import_image() -> image::RgbaImage {
+    let mut img = image::RgbaImage::from_pixel(4, 2, image::Rgba([255, 255, 255, 255]));
+    img.put_pixel(0, 0, image::Rgba([0, 0, 0, 255]));
+    img.put_pixel(1, 0, image::Rgba([0, 0, 0, 255]));
+    img.put_pixel(0, 1, image::Rgba([40, 40, 40, 255]));
+    img.put_pixel(1, 1, image::Rgba([0, 0, 0, 0]));
+    img
+}
+
+#[test]
+fn dark_pixels_become_the_chosen_tile() {
+    let (rows, cropped) = image_to_solids(&import_image(), 1, 128, '3');
+    assert_eq!(rows, vec!["3300", "3000"]);
+    assert!(!cropped);
+    // Two pixels per tile: the top-left block averages dark, the top-right one light
+    let (rows, _) = image_to_solids(&import_image(), 2, 128, '3');
+    assert_eq!(rows, vec!["30"]);
+}
+
+#[test]
+fn importing_a_room_from_an_image_is_one_undo_step() {
+    let path = std::env::temp_dir().join(format!("summit_test_import_{}.png", std::process::id()));
+    import_image().save(&path).unwrap();
+    let mut editor = CelesteMapEditor::default();
+    editor.map_data = Some(serde_json::from_str(META_FIRST_MAP).unwrap());
+    editor.extract_level_names();
+    editor.cache_rooms();
+
+    let import = ImageImport::open(&path, '5').unwrap();
+    let name = apply_image_import(&mut editor, &import).unwrap();
+    assert!(name.starts_with("summit_test_import_"));
+    let levels = map_model::levels(editor.map_data.as_ref().unwrap()).unwrap();
+    assert_eq!(levels.len(), 3);
+    assert_eq!((levels[2]["width"].as_i64(), levels[2]["height"].as_i64()), (Some(32), Some(16)));
+    assert_eq!(editor.level_names.len(), 3);
+    assert_eq!(editor.current_level_index, 2);
+
+    undo(&mut editor);
+    assert_eq!(map_model::levels(editor.map_data.as_ref().unwrap()).unwrap().len(), 2);
+    assert_eq!(editor.level_names.len(), 2);
+    assert!(editor.current_level_index < 2);
+}
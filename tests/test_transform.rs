@@ -0,0 +1,186 @@
+//! Screen, world and tile conversions of the editor camera.
+
+use eframe::egui::{Pos2, Rect, Vec2};
+use serde_json::json;
+use summit::app::CelesteMapEditor;
+use summit::ui::transform::{RoomGrid, ViewTransform, MAX_ZOOM, MIN_ZOOM};
+
+// Both ends of the zoom range and a few steps in between
+const ZOOMS: [f32; 5] = [MIN_ZOOM, 0.5, 1.0, 4.0, MAX_ZOOM];
+const EPSILON: f32 = 1e-3;
+
+fn assert_close(a: Pos2, b: Pos2) {
+    let tolerance = EPSILON * a.to_vec2().length().max(1.0);
+    assert!((a - b).length() <= tolerance, "{:?} != {:?}", a, b);
+}
+
+#[test]
+fn scale_follows_zoom() {
+    let view = ViewTransform::new(Vec2::ZERO, 1.0);
+    assert_eq!(view.scale(), 2.5);
+    assert_eq!(view.tile_size(), 20.0);
+    let view = ViewTransform::new(Vec2::ZERO, 4.0);
+    assert_eq!(view.scale(), 10.0);
+    assert_eq!(view.tile_size(), 80.0);
+}
+
+#[test]
+fn world_to_screen_applies_camera_after_scale() {
+    let view = ViewTransform::new(Vec2::new(100.0, -50.0), 2.0);
+    assert_eq!(view.world_to_screen(Pos2::new(8.0, 16.0)), Pos2::new(-60.0, 130.0));
+    assert_eq!(view.screen_to_world(Pos2::new(-60.0, 130.0)), Pos2::new(8.0, 16.0));
+}
+
+#[test]
+fn screen_world_round_trip_at_every_zoom() {
+    let camera = Vec2::new(-1234.5, 678.25);
+    let points = [Pos2::ZERO, Pos2::new(-4000.0, -320.0), Pos2::new(12345.0, 9876.0), Pos2::new(0.5, -0.5)];
+    for zoom in ZOOMS {
+        let view = ViewTransform::new(camera, zoom);
+        for world in points {
+            assert_close(view.screen_to_world(view.world_to_screen(world)), world);
+        }
+    }
+}
+
+#[test]
+fn world_rect_keeps_its_size_in_world_pixels() {
+    let mut editor = CelesteMapEditor::default();
+    editor.zoom_level = 0.5;
+    editor.camera_pos = Vec2::new(10.0, 20.0);
+    let view = ViewTransform::of(&editor);
+    let rect = view.world_rect_to_screen(Rect::from_min_size(Pos2::new(-320.0, 160.0), Vec2::new(320.0, 184.0)));
+    assert_eq!(rect.min, Pos2::new(-410.0, 180.0));
+    assert_eq!(rect.size(), Vec2::new(400.0, 230.0));
+}
+
+#[test]
+fn room_tiles_floor_towards_negative_infinity() {
+    let view = ViewTransform::new(Vec2::ZERO, 1.0);
+    let room = RoomGrid::new(-64.0, -32.0, (0, 0));
+    assert_eq!(ViewTransform::world_to_room_tile(room, Pos2::new(-64.0, -32.0)), (0, 0));
+    assert_eq!(ViewTransform::world_to_room_tile(room, Pos2::new(-56.5, -24.0)), (0, 1));
+    // Left of and above the room: negative tiles, not tile 0
+    assert_eq!(ViewTransform::world_to_room_tile(room, Pos2::new(-64.5, -33.0)), (-1, -1));
+    assert_eq!(ViewTransform::world_to_room_tile(room, Pos2::new(-72.0, -40.0)), (-1, -1));
+    assert_eq!(ViewTransform::world_to_room_tile(room, Pos2::new(-72.5, -40.5)), (-2, -2));
+}
+
+#[test]
+fn room_tile_round_trip_with_negative_rooms() {
+    let room = RoomGrid::new(-1000.0, -2008.0, (0, 0));
+    for zoom in ZOOMS {
+        let view = ViewTransform::new(Vec2::new(-300.0, 75.0), zoom);
+        for tile in [(0, 0), (3, 7), (-2, -5), (39, 22)] {
+            let corner = view.room_tile_to_screen(room, tile);
+            // Nudge into the tile so rounding can't land on the previous one
+            let inside = corner + Vec2::splat(view.tile_size() / 2.0);
+            assert_eq!(view.screen_to_room_tile(room, inside), tile, "zoom {}", zoom);
+        }
+    }
+}
+
+#[test]
+fn solids_offset_shifts_the_room_grid() {
+    let level = json!({
+        "x": 160,
+        "y": -80,
+        "__children": [{ "__name": "solids", "offsetX": 4, "offsetY": -8, "innerText": "" }]
+    });
+    let room = RoomGrid::of_level(&level);
+    assert_eq!(room.origin, Pos2::new(164.0, -88.0));
+    let view = ViewTransform::new(Vec2::ZERO, 1.0);
+    assert_eq!(ViewTransform::room_tile_to_world(room, (2, 3)), Pos2::new(180.0, -64.0));
+    // The room's own corner falls in the tile left of the shifted grid
+    assert_eq!(ViewTransform::world_to_room_tile(room, Pos2::new(160.0, -80.0)), (-1, 1));
+    assert_eq!(ViewTransform::world_to_room_tile(room, Pos2::new(164.0, -88.0)), (0, 0));
+}
+
+#[test]
+fn missing_solids_offset_is_zero() {
+    let level = json!({ "x": -24, "y": 40, "__children": [{ "__name": "solids", "innerText": "" }] });
+    assert_eq!(RoomGrid::of_level(&level).origin, Pos2::new(-24.0, 40.0));
+    let level = json!({ "x": 8, "y": 16 });
+    assert_eq!(RoomGrid::of_level(&level).origin, Pos2::new(8.0, 16.0));
+}
+
+#[test]
+fn screen_to_map_matches_the_world_grid() {
+    let mut editor = CelesteMapEditor::default();
+    for zoom in ZOOMS {
+        editor.zoom_level = zoom;
+        editor.camera_pos = Vec2::new(-517.0, 233.0);
+        let view = ViewTransform::of(&editor);
+        let origin_room = RoomGrid::new(0.0, 0.0, (0, 0));
+        for tile in [(0, 0), (-3, 4), (25, -12)] {
+            let screen = view.room_tile_to_screen(origin_room, tile) + Vec2::splat(view.tile_size() / 2.0);
+            assert_eq!(editor.screen_to_map(screen), tile, "zoom {}", zoom);
+        }
+    }
+}
+
+#[test]
+fn set_zoom_keeps_the_anchored_world_point() {
+    let anchors = [Pos2::new(640.0, 360.0), Pos2::ZERO, Pos2::new(1919.0, 17.5)];
+    for from in ZOOMS {
+        for to in ZOOMS {
+            for anchor in anchors {
+                let mut editor = CelesteMapEditor::default();
+                editor.zoom_level = from;
+                editor.camera_pos = Vec2::new(-2500.0, 1800.0);
+                let world = ViewTransform::of(&editor).screen_to_world(anchor);
+                editor.set_zoom(to, anchor);
+                assert_eq!(editor.zoom_level, to);
+                assert_close(ViewTransform::of(&editor).screen_to_world(anchor), world);
+            }
+        }
+    }
+}
+
+#[test]
+fn set_zoom_clamps_and_still_keeps_the_anchor() {
+    let anchor = Pos2::new(400.0, 300.0);
+    let mut editor = CelesteMapEditor::default();
+    editor.camera_pos = Vec2::new(8000.0, -600.0);
+    let world = ViewTransform::of(&editor).screen_to_world(anchor);
+
+    editor.set_zoom(MAX_ZOOM * 10.0, anchor);
+    assert_eq!(editor.zoom_level, MAX_ZOOM);
+    assert_close(ViewTransform::of(&editor).screen_to_world(anchor), world);
+
+    editor.set_zoom(0.0, anchor);
+    assert_eq!(editor.zoom_level, MIN_ZOOM);
+    assert_close(ViewTransform::of(&editor).screen_to_world(anchor), world);
+}
+
+#[test]
+fn max_zoom_over_a_huge_room_visits_only_the_screen_tiles() {
+    let view = ViewTransform::new(Vec2::new(40_000.0, 25_000.0), MAX_ZOOM);
+    let room = RoomGrid::new(0.0, 0.0, (0, 0));
+    let screen = Rect::from_min_size(Pos2::ZERO, Vec2::new(1920.0, 1080.0));
+    let (cols, rows) = view.visible_room_tiles(room, 100_000, 100_000, screen);
+    // A tile straddling each edge adds one column and one row
+    let max_cols = (screen.width() / view.tile_size()).ceil() as usize + 1;
+    let max_rows = (screen.height() / view.tile_size()).ceil() as usize + 1;
+    assert!(!cols.is_empty() && cols.len() <= max_cols, "{:?}", cols);
+    assert!(!rows.is_empty() && rows.len() <= max_rows, "{:?}", rows);
+}
+
+#[test]
+fn visible_tiles_stay_inside_the_grid() {
+    let room = RoomGrid::new(0.0, 0.0, (0, 0));
+    let screen = Rect::from_min_size(Pos2::ZERO, Vec2::new(800.0, 600.0));
+    // Far past the room's bottom-right corner, and far before its top-left one
+    let view = ViewTransform::new(Vec2::splat(1.0e9), MAX_ZOOM);
+    assert_eq!(view.visible_room_tiles(room, 40, 23, screen), (40..40, 23..23));
+    let view = ViewTransform::new(Vec2::splat(-1.0e9), MAX_ZOOM);
+    assert_eq!(view.visible_room_tiles(room, 40, 23, screen), (0..0, 0..0));
+    // A broken camera draws nothing rather than everything
+    let view = ViewTransform::new(Vec2::splat(f32::NAN), 1.0);
+    assert_eq!(view.visible_room_tiles(room, 40, 23, screen), (0..0, 0..0));
+    let view = ViewTransform::new(Vec2::ZERO, f32::INFINITY);
+    assert_eq!(view.visible_room_tiles(room, 40, 23, screen), (0..0, 0..0));
+    // The whole room fits on screen at low zoom
+    let view = ViewTransform::new(Vec2::ZERO, MIN_ZOOM);
+    assert_eq!(view.visible_room_tiles(room, 40, 23, screen), (0..40, 0..23));
+}